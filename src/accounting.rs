@@ -0,0 +1,87 @@
+use crate::encoding::{comment_text, escape_string};
+use std::fmt::Write;
+
+// Job accounting details written as header comments for spool scrapers.
+pub struct Accounting {
+    client: Option<String>,
+    routing: Option<String>,
+    cost_center: Option<String>,
+    copies: Option<u32>,
+    comments: Vec<(String, String)>,
+}
+
+impl Default for Accounting {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn text(value: &str) -> String {
+    escape_string(&comment_text(value))
+}
+
+impl Accounting {
+    pub fn new() -> Self {
+        Accounting {
+            client: None,
+            routing: None,
+            cost_center: None,
+            copies: None,
+            comments: Vec::new(),
+        }
+    }
+
+    pub fn client(mut self, client: &str) -> Self {
+        self.client = Some(client.to_string());
+        self
+    }
+
+    pub fn routing(mut self, routing: &str) -> Self {
+        self.routing = Some(routing.to_string());
+        self
+    }
+
+    pub fn cost_center(mut self, cost_center: &str) -> Self {
+        self.cost_center = Some(cost_center.to_string());
+        self
+    }
+
+    pub fn copies(mut self, copies: u32) -> Self {
+        self.copies = Some(copies.max(1));
+        self
+    }
+
+    // Adds a %%Pslib<name>: (<value>) comment. The prefix keeps custom names from being read as
+    // structural DSC comments, eg: EndComments. Characters that aren't letters, digits, '-' or '_'
+    // are dropped from the name.
+    pub fn comment(mut self, name: &str, value: &str) -> Self {
+        let name: String = name
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+            .collect();
+        if !name.is_empty() {
+            self.comments.push((name, value.to_string()));
+        }
+        self
+    }
+
+    pub(crate) fn comments(&self) -> String {
+        let mut result = String::new();
+        if let Some(client) = &self.client {
+            writeln!(&mut result, "%%For: ({})", text(client)).unwrap();
+        }
+        if let Some(routing) = &self.routing {
+            writeln!(&mut result, "%%Routing: ({})", text(routing)).unwrap();
+        }
+        if let Some(cost_center) = &self.cost_center {
+            writeln!(&mut result, "%%PslibCostCenter: ({})", text(cost_center)).unwrap();
+        }
+        if let Some(copies) = self.copies {
+            writeln!(&mut result, "%%Requirements: numcopies({})", copies).unwrap();
+        }
+        for (name, value) in &self.comments {
+            writeln!(&mut result, "%%Pslib{}: ({})", name, text(value)).unwrap();
+        }
+        result
+    }
+}
@@ -0,0 +1,151 @@
+use crate::{
+    style::{write_paint, FillStyle, StrokeStyle, TransformStyle},
+    FillRule, Group, GroupItem, LineCap, LineJoin, PaintOrder, Serialize, Transform,
+};
+use std::fmt::Write;
+
+pub struct Arc {
+    x: f32,
+    y: f32,
+    radius: f32,
+    start_angle: f32,
+    end_angle: f32,
+    pie: bool,
+    fill: FillStyle,
+    stroke: StrokeStyle,
+    transform: TransformStyle,
+}
+
+impl Arc {
+    pub fn new(x: f32, y: f32, radius: f32, start_angle: f32, end_angle: f32) -> Self {
+        Arc {
+            x,
+            y,
+            radius: radius.max(0.0),
+            start_angle,
+            end_angle,
+            pie: false,
+            fill: FillStyle::new(),
+            stroke: StrokeStyle::new(0.0),
+            transform: TransformStyle::new(),
+        }
+    }
+
+    pub fn pie(mut self) -> Self {
+        self.pie = true;
+        self
+    }
+
+    pub fn fill_rgb(mut self, r: f32, g: f32, b: f32) -> Self {
+        self.fill.rgb(r, g, b);
+        self
+    }
+
+    pub fn fill_cmyk(mut self, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.fill.cmyk(c, m, y, k);
+        self
+    }
+
+    pub fn fill_rule(mut self, rule: FillRule) -> Self {
+        self.fill.rule(&rule);
+        self
+    }
+
+    pub fn opacity(mut self, opacity: f32) -> Self {
+        self.fill.opacity(opacity);
+        self
+    }
+
+    pub fn backdrop_rgb(mut self, r: f32, g: f32, b: f32) -> Self {
+        self.fill.backdrop_rgb(r, g, b);
+        self
+    }
+
+    pub fn backdrop_cmyk(mut self, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.fill.backdrop_cmyk(c, m, y, k);
+        self
+    }
+
+    pub fn stroke_rgb(mut self, width: f32, r: f32, g: f32, b: f32) -> Self {
+        self.stroke.rgb(width, r, g, b);
+        self
+    }
+
+    pub fn stroke_cmyk(mut self, width: f32, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.stroke.cmyk(width, c, m, y, k);
+        self
+    }
+
+    pub fn paint_order(mut self, order: PaintOrder) -> Self {
+        self.stroke.paint_order(order);
+        self
+    }
+
+    pub fn line_cap(mut self, cap: LineCap) -> Self {
+        self.stroke.cap(cap);
+        self
+    }
+
+    pub fn line_join(mut self, join: LineJoin) -> Self {
+        self.stroke.join(join);
+        self
+    }
+
+    pub fn miter_limit(mut self, limit: f32) -> Self {
+        self.stroke.miter_limit(limit);
+        self
+    }
+
+    pub fn transform(mut self, matrix: Transform) -> Self {
+        self.transform.matrix(matrix);
+        self
+    }
+
+    fn serialize(&self, fill: &FillStyle, stroke: &StrokeStyle) -> String {
+        let mut result = String::new();
+
+        if self.radius == 0.0 || (!fill.is_enabled() && !stroke.is_enabled()) {
+            return result;
+        }
+
+        let radius = self.radius;
+        self.transform.begin(
+            &mut result,
+            self.x - radius,
+            self.y - radius,
+            radius * 2.0,
+            radius * 2.0,
+        );
+
+        write!(
+            &mut result,
+            "{} {} {} {} {} {} ",
+            self.x,
+            self.y,
+            self.radius,
+            self.start_angle,
+            self.end_angle,
+            if self.pie { "sector" } else { "arcline" }
+        )
+        .unwrap();
+
+        write_paint(&mut result, stroke, &self.stroke, |result| {
+            fill.write_as(result, &self.fill)
+        });
+        self.transform.end(&mut result);
+
+        result
+    }
+}
+
+impl Serialize for Arc {
+    fn to_postscript_string(&self) -> String {
+        self.serialize(&self.fill, &self.stroke)
+    }
+}
+
+impl GroupItem for Arc {
+    fn to_postscript_string_in(&self, group: &Group) -> String {
+        self.serialize(group.fill_for(&self.fill), group.stroke_for(&self.stroke))
+    }
+}
@@ -0,0 +1,165 @@
+use crate::{
+    style::{write_paint, FillStyle, StrokeStyle},
+    Group, GroupItem, MarkerSymbol, PaintOrder, Serialize,
+};
+use std::fmt::Write;
+
+pub enum BorderMotif {
+    Marker(MarkerSymbol),
+    // A procedure taking `x y size` that builds a path centered on the point.
+    Procedure(String),
+}
+
+impl BorderMotif {
+    fn procedure(&self) -> &str {
+        match self {
+            BorderMotif::Marker(symbol) => symbol.procedure(),
+            BorderMotif::Procedure(name) => name,
+        }
+    }
+}
+
+pub struct Border {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    motif: BorderMotif,
+    size: f32,
+    spacing: f32,
+    corner: Option<(BorderMotif, f32)>,
+    fill: FillStyle,
+    stroke: StrokeStyle,
+}
+
+impl Border {
+    pub fn new(x: f32, y: f32, width: f32, height: f32, motif: BorderMotif, size: f32) -> Self {
+        let size = size.max(0.0);
+        Border {
+            x,
+            y,
+            width: width.max(0.0),
+            height: height.max(0.0),
+            motif,
+            size,
+            spacing: size * 2.0,
+            corner: None,
+            fill: FillStyle::new(),
+            stroke: StrokeStyle::new(0.0),
+        }
+    }
+
+    // The spacing is adjusted so each side fits a whole number of motifs.
+    pub fn spacing(mut self, spacing: f32) -> Self {
+        self.spacing = spacing.max(0.0);
+        self
+    }
+
+    pub fn corner(mut self, motif: BorderMotif, size: f32) -> Self {
+        self.corner = Some((motif, size.max(0.0)));
+        self
+    }
+
+    pub fn fill_rgb(mut self, r: f32, g: f32, b: f32) -> Self {
+        self.fill.rgb(r, g, b);
+        self
+    }
+
+    pub fn fill_cmyk(mut self, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.fill.cmyk(c, m, y, k);
+        self
+    }
+
+    pub fn stroke_rgb(mut self, width: f32, r: f32, g: f32, b: f32) -> Self {
+        self.stroke.rgb(width, r, g, b);
+        self
+    }
+
+    pub fn stroke_cmyk(mut self, width: f32, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.stroke.cmyk(width, c, m, y, k);
+        self
+    }
+
+    pub fn paint_order(mut self, order: PaintOrder) -> Self {
+        self.stroke.paint_order(order);
+        self
+    }
+
+    fn write_motif(
+        &self,
+        result: &mut String,
+        point: (f32, f32),
+        motif: &BorderMotif,
+        size: f32,
+        fill: &FillStyle,
+        stroke: &StrokeStyle,
+    ) {
+        write!(
+            result,
+            "{} {} {} {} ",
+            round(point.0),
+            round(point.1),
+            size,
+            motif.procedure()
+        )
+        .unwrap();
+        write_paint(result, stroke, &self.stroke, |result| {
+            fill.write_as(result, &self.fill)
+        });
+    }
+
+    fn serialize(&self, fill: &FillStyle, stroke: &StrokeStyle) -> String {
+        let mut result = String::new();
+
+        if self.size == 0.0 || (!fill.is_enabled() && !stroke.is_enabled()) {
+            return result;
+        }
+
+        let corners = [
+            (self.x, self.y),
+            (self.x + self.width, self.y),
+            (self.x + self.width, self.y + self.height),
+            (self.x, self.y + self.height),
+        ];
+        for (i, start) in corners.iter().enumerate() {
+            let (motif, size) = match &self.corner {
+                Some((motif, size)) => (motif, *size),
+                None => (&self.motif, self.size),
+            };
+            self.write_motif(&mut result, *start, motif, size, fill, stroke);
+
+            let end = corners[(i + 1) % corners.len()];
+            let length = (end.0 - start.0).abs() + (end.1 - start.1).abs();
+            if self.spacing == 0.0 || length == 0.0 {
+                continue;
+            }
+            let count = (length / self.spacing).round().max(1.0) as usize;
+            for step in 1..count {
+                let t = step as f32 / count as f32;
+                let point = (
+                    start.0 + (end.0 - start.0) * t,
+                    start.1 + (end.1 - start.1) * t,
+                );
+                self.write_motif(&mut result, point, &self.motif, self.size, fill, stroke);
+            }
+        }
+
+        result
+    }
+}
+
+fn round(value: f32) -> f32 {
+    (value * 1000.0).round() / 1000.0 + 0.0
+}
+
+impl Serialize for Border {
+    fn to_postscript_string(&self) -> String {
+        self.serialize(&self.fill, &self.stroke)
+    }
+}
+
+impl GroupItem for Border {
+    fn to_postscript_string_in(&self, group: &Group) -> String {
+        self.serialize(group.fill_for(&self.fill), group.stroke_for(&self.stroke))
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{style::write_vertices, FillRule, GroupItem, LanguageLevel, Path, Serialize};
+use std::fmt::Write;
+
+pub struct Clip {
+    outline: String,
+    even_odd: bool,
+    buffer: String,
+    warnings: Vec<String>,
+}
+
+impl Clip {
+    fn new(outline: String) -> Self {
+        Clip {
+            outline,
+            even_odd: false,
+            buffer: String::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    pub fn rect(x: f32, y: f32, width: f32, height: f32) -> Self {
+        let width = width.max(0.0);
+        let height = height.max(0.0);
+        Self::new(format!(
+            "-{} 0 0 -{} {} 0 0 {} {} {} rect ",
+            width, height, width, height, x, y
+        ))
+    }
+
+    pub fn ellipse(x: f32, y: f32, radius_x: f32, radius_y: f32) -> Self {
+        Self::new(format!(
+            "{} {} {} {} ellipse ",
+            x,
+            y,
+            radius_x.max(0.0),
+            radius_y.max(0.0)
+        ))
+    }
+
+    pub fn polygon(points: Vec<(f32, f32)>) -> Self {
+        let mut outline = String::new();
+        if points.len() >= 2 {
+            write_vertices(
+                &mut outline,
+                &points,
+                "poly",
+                LanguageLevel::Level1.operand_stack_limit(),
+            );
+        }
+        Self::new(outline)
+    }
+
+    // Only the geometry of the path is used, not its styles or transforms.
+    pub fn path(path: &Path) -> Self {
+        let mut outline = String::new();
+        path.write_outline(&mut outline);
+        Self::new(outline)
+    }
+
+    pub fn fill_rule(mut self, rule: FillRule) -> Self {
+        self.even_odd = matches!(rule, FillRule::EvenOdd);
+        self
+    }
+
+    pub fn add<T: Serialize>(&mut self, item: &T) {
+        self.buffer.push_str(&item.to_postscript_string());
+        self.warnings.extend(item.warnings());
+    }
+}
+
+impl Serialize for Clip {
+    fn to_postscript_string(&self) -> String {
+        let mut result = String::new();
+
+        // A degenerate clipping path hides all of the content.
+        if self.buffer.is_empty() || self.outline.is_empty() {
+            return result;
+        }
+
+        write!(
+            &mut result,
+            "gsave {}{} newpath ",
+            self.outline,
+            if self.even_odd { "eoclip" } else { "clip" }
+        )
+        .unwrap();
+        result.push_str(&self.buffer);
+        result.push_str("grestore ");
+
+        result
+    }
+
+    fn warnings(&self) -> Vec<String> {
+        self.warnings.clone()
+    }
+}
+
+impl GroupItem for Clip {}
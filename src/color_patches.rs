@@ -0,0 +1,103 @@
+use std::fmt::Write;
+
+pub struct ColorPatch {
+    cmyk: [f32; 4],
+    lab: Option<[f32; 3]>,
+}
+
+impl ColorPatch {
+    pub fn cmyk(c: f32, m: f32, y: f32, k: f32) -> Self {
+        ColorPatch {
+            cmyk: [
+                c.clamp(0.0, 1.0),
+                m.clamp(0.0, 1.0),
+                y.clamp(0.0, 1.0),
+                k.clamp(0.0, 1.0),
+            ],
+            lab: None,
+        }
+    }
+
+    // The CIELAB value the patch should measure at.
+    pub fn expected_lab(mut self, l: f32, a: f32, b: f32) -> Self {
+        self.lab = Some([l, a, b]);
+        self
+    }
+}
+
+// A row of solid patches printed in the slug area so an inline spectrophotometer can check the
+// color of every sheet. Each patch is preceded by a comment with its position and expected values.
+pub struct ColorPatches {
+    patches: Vec<ColorPatch>,
+    size: f32,
+    gap: f32,
+}
+
+impl ColorPatches {
+    pub fn new(size: f32) -> Self {
+        ColorPatches {
+            patches: Vec::new(),
+            size: size.max(1.0),
+            gap: 0.0,
+        }
+    }
+
+    pub fn gap(mut self, gap: f32) -> Self {
+        self.gap = gap.max(0.0);
+        self
+    }
+
+    pub fn patch(mut self, patch: ColorPatch) -> Self {
+        self.patches.push(patch);
+        self
+    }
+
+    pub(crate) fn size(&self) -> f32 {
+        self.size
+    }
+
+    pub(crate) fn width(&self) -> f32 {
+        let count = self.patches.len() as f32;
+        (count * self.size + (count - 1.0) * self.gap).max(0.0)
+    }
+
+    // Writes the patches from left to right starting at the bottom left corner of the row.
+    pub(crate) fn place(&self, x: f32, y: f32) -> String {
+        let mut result = String::new();
+        for (i, patch) in self.patches.iter().enumerate() {
+            let left = x + i as f32 * (self.size + self.gap);
+            let [c, m, k_y, k] = patch.cmyk;
+            write!(
+                &mut result,
+                "%%PslibColorPatch: {} {} {} {} CMYK {} {} {} {}",
+                i + 1,
+                left,
+                y,
+                self.size,
+                c,
+                m,
+                k_y,
+                k
+            )
+            .unwrap();
+            if let Some([l, a, b]) = patch.lab {
+                write!(&mut result, " Lab {} {} {}", l, a, b).unwrap();
+            }
+            write!(
+                &mut result,
+                "\ngsave {} {} {} {} setcmykcolor newpath {} {} moveto {} 0 rlineto 0 {} rlineto {} 0 rlineto closepath fill grestore\n",
+                c,
+                m,
+                k_y,
+                k,
+                left,
+                y,
+                self.size,
+                self.size,
+                -self.size
+            )
+            .unwrap();
+        }
+        result
+    }
+}
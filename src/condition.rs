@@ -0,0 +1,31 @@
+use std::collections::HashSet;
+
+type Predicate = Box<dyn Fn(&HashSet<String>) -> bool>;
+
+pub enum Condition {
+    Flag(String),
+    NotFlag(String),
+    Predicate(Predicate),
+}
+
+impl Condition {
+    pub fn flag(name: &str) -> Self {
+        Condition::Flag(name.to_string())
+    }
+
+    pub fn not_flag(name: &str) -> Self {
+        Condition::NotFlag(name.to_string())
+    }
+
+    pub fn predicate<F: Fn(&HashSet<String>) -> bool + 'static>(predicate: F) -> Self {
+        Condition::Predicate(Box::new(predicate))
+    }
+
+    pub fn is_met(&self, flags: &HashSet<String>) -> bool {
+        match self {
+            Condition::Flag(name) => flags.contains(name),
+            Condition::NotFlag(name) => !flags.contains(name),
+            Condition::Predicate(predicate) => predicate(flags),
+        }
+    }
+}
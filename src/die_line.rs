@@ -0,0 +1,59 @@
+use crate::{separation::separation_space, GroupItem, Path, Serialize};
+
+// A cutting path stroked in a spot color that cutting plotters and RIPs recognise by name. The
+// stroke overprints so the artwork beneath it is never knocked out, and the alternate CMYK color
+// is only used on devices without the separation.
+pub struct DieLine {
+    path: Path,
+    name: String,
+    alternate: [f32; 4],
+    width: f32,
+}
+
+impl DieLine {
+    pub fn new(path: Path) -> Self {
+        DieLine {
+            path,
+            name: "CutContour".to_string(),
+            alternate: [0.0, 1.0, 0.0, 0.0],
+            width: 0.25,
+        }
+    }
+
+    pub fn spot_color(mut self, name: &str, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.name = name.to_string();
+        self.alternate = [
+            c.clamp(0.0, 1.0),
+            m.clamp(0.0, 1.0),
+            y.clamp(0.0, 1.0),
+            k.clamp(0.0, 1.0),
+        ];
+        self
+    }
+
+    pub fn width(mut self, width: f32) -> Self {
+        self.width = width.max(0.0);
+        self
+    }
+}
+
+impl Serialize for DieLine {
+    fn to_postscript_string(&self) -> String {
+        let mut result = String::new();
+
+        if self.width == 0.0 || self.name.is_empty() {
+            return result;
+        }
+
+        let paint = format!(
+            "gsave {}1 setcolor true setoverprint {} setlinewidth stroke grestore ",
+            separation_space(&self.name, self.alternate),
+            self.width
+        );
+        self.path.write_painted(&mut result, &paint);
+
+        result
+    }
+}
+
+impl GroupItem for DieLine {}
@@ -0,0 +1,702 @@
+use crate::LineEnding;
+use std::collections::HashSet;
+
+fn is_delimiter(byte: u8) -> bool {
+    matches!(
+        byte,
+        b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%'
+    )
+}
+
+fn is_whitespace(byte: u8) -> bool {
+    matches!(byte, b' ' | b'\t' | b'\r' | b'\n' | b'\x0c' | b'\0')
+}
+
+// Returns the index just past the token starting at `start`. Strings, hex strings and comments
+// are treated as a single token.
+fn token_end(input: &[u8], start: usize) -> usize {
+    let mut i = start;
+    match input[i] {
+        b'%' => {
+            while i < input.len() && input[i] != b'\n' && input[i] != b'\r' {
+                i += 1;
+            }
+        }
+        b'(' => {
+            let mut depth = 0;
+            while i < input.len() {
+                match input[i] {
+                    b'\\' => i += 1,
+                    b'(' => depth += 1,
+                    b')' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            i += 1;
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+                i += 1;
+            }
+        }
+        b'<' if input.get(i + 1) == Some(&b'<') => i += 2,
+        b'>' if input.get(i + 1) == Some(&b'>') => i += 2,
+        b'<' if input.get(i + 1) == Some(&b'~') => {
+            while i < input.len() && !input[i..].starts_with(b"~>") {
+                i += 1;
+            }
+            i += 2;
+        }
+        b'<' => {
+            while i < input.len() && input[i] != b'>' {
+                i += 1;
+            }
+            i += 1;
+        }
+        b'/' => {
+            i += 1;
+            if input.get(i) == Some(&b'/') {
+                i += 1;
+            }
+            while i < input.len() && !is_whitespace(input[i]) && !is_delimiter(input[i]) {
+                i += 1;
+            }
+        }
+        b')' | b'>' | b'[' | b']' | b'{' | b'}' => i += 1,
+        _ => {
+            while i < input.len() && !is_whitespace(input[i]) && !is_delimiter(input[i]) {
+                i += 1;
+            }
+        }
+    }
+    i.min(input.len())
+}
+
+fn binary_number(token: &[u8]) -> Option<Vec<u8>> {
+    let token = std::str::from_utf8(token).ok()?;
+    if !token.contains(['.', 'e', 'E']) {
+        let value: i32 = token.parse().ok()?;
+        let mut result = Vec::new();
+        if let Ok(value) = i8::try_from(value) {
+            result.push(136);
+            result.push(value as u8);
+        } else if let Ok(value) = i16::try_from(value) {
+            result.push(134);
+            result.extend_from_slice(&value.to_be_bytes());
+        } else {
+            result.push(132);
+            result.extend_from_slice(&value.to_be_bytes());
+        }
+        return Some(result);
+    }
+    let value: f32 = token.parse().ok()?;
+    if !value.is_finite() {
+        return None;
+    }
+    let mut result = vec![138];
+    result.extend_from_slice(&value.to_be_bytes());
+    Some(result)
+}
+
+// Rewrites numeric operands as binary tokens. Comments, strings and names are left as they are so
+// DSC comment lines remain readable. Numbers whose encoding contains a line break byte are left
+// as text, otherwise a line could start with a '%' byte and be read as a comment.
+pub(crate) fn binary_tokens(input: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(input.len());
+    let mut pending = Vec::new();
+    let mut last_binary = false;
+    let mut i = 0;
+
+    while i < input.len() {
+        if is_whitespace(input[i]) {
+            pending.push(input[i]);
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        let end = token_end(input, i);
+        i = end;
+
+        let token = binary_number(&input[start..end])
+            .filter(|token| !token.iter().any(|byte| *byte == b'\n' || *byte == b'\r'));
+        match token {
+            Some(token) => {
+                if !last_binary {
+                    result.append(&mut pending);
+                }
+                pending.clear();
+                result.extend_from_slice(&token);
+                last_binary = true;
+            }
+            None => {
+                result.append(&mut pending);
+                result.extend_from_slice(&input[start..end]);
+                last_binary = false;
+            }
+        }
+    }
+    result.append(&mut pending);
+
+    result
+}
+
+pub(crate) fn line_endings(input: &[u8], line_ending: &LineEnding) -> Vec<u8> {
+    let ending: &[u8] = match line_ending {
+        LineEnding::LF => b"\n",
+        LineEnding::CRLF => b"\r\n",
+        LineEnding::CR => b"\r",
+    };
+    let mut result = Vec::with_capacity(input.len());
+    for byte in input {
+        if *byte == b'\n' {
+            result.extend_from_slice(ending);
+        } else {
+            result.push(*byte);
+        }
+    }
+    result
+}
+
+// Escapes 8-bit characters within strings and replaces any others (eg: in comments) so the
+// output only ever contains 7-bit ASCII. Characters outside strings are replaced one character
+// at a time, accented letters with their base letter and anything else with '?'. Hex and ASCII85
+// strings are skipped as whole tokens since they may contain '(' and '%'.
+pub(crate) fn seven_bit(input: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(input.len());
+    let mut depth = 0;
+    let mut comment = false;
+    let mut escaped = false;
+
+    let mut i = 0;
+    while i < input.len() {
+        let byte = input[i];
+        if depth == 0 && !comment && byte == b'<' && input.get(i + 1) != Some(&b'<') {
+            let end = token_end(input, i).min(input.len());
+            result.extend(
+                input[i..end]
+                    .iter()
+                    .map(|b| if b.is_ascii() { *b } else { b'?' }),
+            );
+            i = end;
+            continue;
+        }
+        i += 1;
+        if byte >= 128 {
+            if depth > 0 && escaped {
+                // The preceding backslash already starts the escape sequence.
+                result.extend_from_slice(format!("{:03o}", byte).as_bytes());
+            } else if depth > 0 {
+                result.extend_from_slice(format!("\\{:03o}", byte).as_bytes());
+            } else {
+                let length = match byte {
+                    0xC0..=0xDF => 2,
+                    0xE0..=0xEF => 3,
+                    0xF0..=0xF7 => 4,
+                    _ => 1,
+                };
+                let c = input
+                    .get(i - 1..i - 1 + length)
+                    .and_then(|bytes| std::str::from_utf8(bytes).ok())
+                    .and_then(|text| text.chars().next());
+                match c {
+                    Some(c) => {
+                        let base = base_letter(c);
+                        result.push(if base.is_ascii() { base as u8 } else { b'?' });
+                        i += length - 1;
+                    }
+                    None => result.push(b'?'),
+                }
+            }
+            escaped = false;
+            continue;
+        }
+        result.push(byte);
+
+        if comment {
+            comment = byte != b'\n' && byte != b'\r';
+        } else if depth > 0 {
+            if escaped {
+                escaped = false;
+            } else if byte == b'\\' {
+                escaped = true;
+            } else if byte == b'(' {
+                depth += 1;
+            } else if byte == b')' {
+                depth -= 1;
+            }
+        } else if byte == b'%' {
+            comment = true;
+        } else if byte == b'(' {
+            depth = 1;
+        }
+    }
+
+    result
+}
+
+// The unaccented letter of an accented Latin-1 letter, and the ASCII quotes for curly quotes.
+pub(crate) fn base_letter(c: char) -> char {
+    match c {
+        'À'..='Å' => 'A',
+        'Ç' => 'C',
+        'È'..='Ë' => 'E',
+        'Ì'..='Ï' => 'I',
+        'Ð' => 'D',
+        'Ñ' => 'N',
+        'Ò'..='Ö' | 'Ø' => 'O',
+        'Ù'..='Ü' => 'U',
+        'Ý' => 'Y',
+        'à'..='å' => 'a',
+        'ç' => 'c',
+        'è'..='ë' => 'e',
+        'ì'..='ï' => 'i',
+        'ñ' => 'n',
+        'ò'..='ö' | 'ø' => 'o',
+        'ù'..='ü' => 'u',
+        'ý' | 'ÿ' => 'y',
+        '\u{2018}' => '`',
+        '\u{2019}' => '\'',
+        _ => c,
+    }
+}
+
+fn escape_length(token: &[u8], start: usize) -> usize {
+    if token[start] != b'\\' || start + 1 >= token.len() {
+        return 1;
+    }
+    let mut length = 2;
+    while length < 4
+        && start + length < token.len()
+        && (b'0'..=b'7').contains(&token[start + length])
+        && (b'0'..=b'7').contains(&token[start + 1])
+    {
+        length += 1;
+    }
+    length
+}
+
+// Breaks lines at whitespace so no line is longer than `max` characters. Strings are continued
+// with a backslash-newline and hex/ASCII85 data is split, neither of which changes their value.
+// Comments and names are never split.
+pub(crate) fn wrap_lines(input: &[u8], max: usize) -> Vec<u8> {
+    let mut result = Vec::with_capacity(input.len() + input.len() / max.max(1));
+    let mut pending = Vec::new();
+    let mut column = 0;
+    let mut i = 0;
+
+    while i < input.len() {
+        if is_whitespace(input[i]) {
+            pending.push(input[i]);
+            i += 1;
+            continue;
+        }
+        let end = token_end(input, i);
+        let token = &input[i..end];
+        i = end;
+
+        if let Some(position) = pending.iter().rposition(|b| *b == b'\n' || *b == b'\r') {
+            column = pending.len() - position - 1;
+            result.append(&mut pending);
+        } else if column > 0 && column + pending.len() + token.len() > max {
+            result.push(b'\n');
+            pending.clear();
+            column = 0;
+        } else {
+            column += pending.len();
+            result.append(&mut pending);
+        }
+
+        if column + token.len() <= max || token[0] == b'%' {
+            result.extend_from_slice(token);
+            column += token.len();
+        } else if token[0] == b'(' {
+            let mut j = 0;
+            while j < token.len() {
+                let length = escape_length(token, j);
+                if column + length >= max {
+                    result.extend_from_slice(b"\\\n");
+                    column = 0;
+                }
+                // A '%' starting a line would look like a comment to DSC parsers.
+                let piece: &[u8] = if column == 0 && token[j] == b'%' {
+                    b"\\045"
+                } else {
+                    &token[j..j + length]
+                };
+                result.extend_from_slice(piece);
+                column += piece.len();
+                if token[j] == b'\n' {
+                    column = 0;
+                }
+                j += length;
+            }
+        } else if token[0] == b'<' && token.get(1) != Some(&b'<') {
+            // Lines can't start with a '%' (valid ASCII85 data) or split the delimiters, so a
+            // line is broken early when the next place it could be broken would be too late.
+            let breakable = |k: usize| {
+                k > 0
+                    && token[k] != b'%'
+                    && !(token[k - 1] == b'<' && token[k] == b'~')
+                    && !(token[k - 1] == b'~' && token[k] == b'>')
+            };
+            for j in 0..token.len() {
+                if column > 0 && breakable(j) {
+                    let next = (j + 1..token.len())
+                        .find(|k| breakable(*k))
+                        .unwrap_or(token.len());
+                    if column + next - j > max {
+                        result.push(b'\n');
+                        column = 0;
+                    }
+                }
+                result.push(token[j]);
+                column += 1;
+            }
+        } else {
+            result.extend_from_slice(token);
+            column += token.len();
+        }
+    }
+    result.append(&mut pending);
+
+    result
+}
+
+// The length of the input up to the end of its last line that ends outside any string, so it
+// can be transformed without the strings or comments the rest of the input continues.
+pub(crate) fn complete_lines(input: &[u8]) -> usize {
+    let mut complete = 0;
+    let mut i = 0;
+    while i < input.len() {
+        if is_whitespace(input[i]) {
+            if input[i] == b'\n' || input[i] == b'\r' {
+                complete = i + 1;
+            }
+            i += 1;
+            continue;
+        }
+        // The last token may be continued by the next write.
+        i = token_end(input, i);
+        if i >= input.len() {
+            break;
+        }
+    }
+    complete
+}
+
+// Roughly estimates the interpreter VM taken by the given code: a fixed cost per object plus the
+// contents of strings, which is close enough to warn about resources that won't fit.
+pub(crate) fn estimate_vm(input: &[u8]) -> usize {
+    let mut total = 0;
+    let mut i = 0;
+    while i < input.len() {
+        if is_whitespace(input[i]) {
+            i += 1;
+            continue;
+        }
+        let end = token_end(input, i);
+        match input[i] {
+            b'%' => {}
+            b'(' | b'<' => total += 8 + (end - i),
+            _ => total += 8,
+        }
+        i = end;
+    }
+    total
+}
+
+// Checks whether the code references the given name, either executed or as a literal.
+pub(crate) fn contains_token(input: &[u8], name: &[u8]) -> bool {
+    let mut i = 0;
+    while i < input.len() {
+        if is_whitespace(input[i]) {
+            i += 1;
+            continue;
+        }
+        let end = token_end(input, i);
+        let token = &input[i..end];
+        if token == name || (token.first() == Some(&b'/') && &token[1..] == name) {
+            return true;
+        }
+        i = end;
+    }
+    false
+}
+
+// The characters, glyph names and two byte glyph indexes that code may show: every character in
+// its strings, every name shown with glyphshow and every pair of bytes in its hex strings. Not all
+// of them are shown, which only makes a font subset larger than it needs to be.
+#[derive(Default)]
+pub(crate) struct UsedGlyphs {
+    pub(crate) characters: HashSet<char>,
+    pub(crate) names: HashSet<String>,
+    pub(crate) indexes: HashSet<u16>,
+}
+
+// Decodes the bytes of a string literal, including its parentheses, as Latin-1 characters.
+fn string_characters(token: &[u8], characters: &mut HashSet<char>) {
+    let body = &token[1..token.len().saturating_sub(1).max(1)];
+    let mut i = 0;
+    while i < body.len() {
+        if body[i] != b'\\' {
+            characters.insert(body[i] as char);
+            i += 1;
+            continue;
+        }
+        i += 1;
+        let Some(escaped) = body.get(i) else {
+            break;
+        };
+        match escaped {
+            b'0'..=b'7' => {
+                let mut code = 0u32;
+                let mut digits = 0;
+                while digits < 3 && matches!(body.get(i), Some(b'0'..=b'7')) {
+                    code = code * 8 + (body[i] - b'0') as u32;
+                    i += 1;
+                    digits += 1;
+                }
+                if let Some(c) = char::from_u32(code & 0xFF) {
+                    characters.insert(c);
+                }
+                continue;
+            }
+            b'n' => characters.insert('\n'),
+            b'r' => characters.insert('\r'),
+            b't' => characters.insert('\t'),
+            b'\r' | b'\n' => false,
+            _ => characters.insert(*escaped as char),
+        };
+        i += 1;
+    }
+}
+
+pub(crate) fn used_glyphs(input: &[u8]) -> UsedGlyphs {
+    let mut used = UsedGlyphs::default();
+    let mut previous: &[u8] = &[];
+    let mut i = 0;
+    while i < input.len() {
+        if is_whitespace(input[i]) {
+            i += 1;
+            continue;
+        }
+        let end = token_end(input, i);
+        let token = &input[i..end];
+        match token {
+            [b'(', ..] => string_characters(token, &mut used.characters),
+            [b'<', b'<', ..] | [b'<', b'~', ..] => {}
+            [b'<', ..] => {
+                let digits: Vec<u8> = token
+                    .iter()
+                    .filter_map(|b| (*b as char).to_digit(16).map(|d| d as u8))
+                    .collect();
+                for index in digits.chunks_exact(4) {
+                    used.indexes.insert(
+                        index
+                            .iter()
+                            .fold(0u16, |value, digit| value * 16 + *digit as u16),
+                    );
+                }
+            }
+            b"glyphshow" if previous.first() == Some(&b'/') => {
+                used.names
+                    .insert(String::from_utf8_lossy(&previous[1..]).to_string());
+            }
+            _ => {}
+        }
+        previous = token;
+        i = end;
+    }
+    used
+}
+
+// The first of the CID-keyed fonts that code shows a string literal in, or re-encodes with
+// latin1font, ie: text that was shown without the font's glyph indexes. Fonts are selected with
+// findfont and are forgotten at the next grestore.
+pub(crate) fn single_byte_text<'a>(
+    input: &[u8],
+    cid_fonts: &'a HashSet<String>,
+) -> Option<&'a str> {
+    let mut current = None;
+    let mut previous: &[u8] = &[];
+    let mut i = 0;
+    while i < input.len() {
+        if is_whitespace(input[i]) {
+            i += 1;
+            continue;
+        }
+        let end = token_end(input, i);
+        let token = &input[i..end];
+        let font = || {
+            std::str::from_utf8(previous.strip_prefix(b"/")?)
+                .ok()
+                .and_then(|name| cid_fonts.get(name))
+                .map(String::as_str)
+        };
+        match token {
+            b"findfont" => current = font(),
+            b"latin1font" if font().is_some() => return font(),
+            b"grestore" => current = None,
+            [b'(', ..] if current.is_some() => return current,
+            _ => {}
+        }
+        previous = token;
+        i = end;
+    }
+    None
+}
+
+// Makes user text safe to write in a DSC comment. Line breaks and other control characters are
+// replaced with spaces and runs of '%' are collapsed so the text can't start or imitate another
+// comment.
+pub(crate) fn comment_text(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c.is_control() {
+            result.push(' ');
+        } else if c != '%' || !result.ends_with('%') {
+            result.push(c);
+        }
+    }
+    result
+}
+
+// Makes user text usable as a PostScript name (fonts, forms) by dropping whitespace, control
+// characters and the delimiters that would end the name.
+pub(crate) fn name_text(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control() && !"()<>[]{}/%".contains(*c))
+        .collect()
+}
+
+// Escapes text for use within a PostScript string literal.
+pub(crate) fn escape_string(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                result.push('\\');
+                result.push(c);
+            }
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+// A PDF text string for pdfmark: a literal string for ASCII text, otherwise UTF-16 with a byte
+// order mark.
+pub(crate) fn pdf_text(text: &str) -> String {
+    if text.is_ascii() {
+        return format!("({})", escape_string(text));
+    }
+    let mut result = String::from("<FEFF");
+    for unit in text.encode_utf16() {
+        result.push_str(&format!("{:04X}", unit));
+    }
+    result.push('>');
+    result
+}
+
+// Encodes binary data as an ASCII85 string literal, decoded by the scanner with LanguageLevel 2.
+pub(crate) fn ascii85(data: &[u8]) -> String {
+    let mut result = String::with_capacity(data.len() * 5 / 4 + 4);
+    result.push_str("<~");
+    for chunk in data.chunks(4) {
+        if chunk == [0, 0, 0, 0] {
+            result.push('z');
+            continue;
+        }
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        let mut value = u32::from_be_bytes(word);
+        let mut digits = [0u8; 5];
+        for digit in digits.iter_mut().rev() {
+            *digit = (value % 85) as u8 + b'!';
+            value /= 85;
+        }
+        for digit in &digits[..chunk.len() + 1] {
+            result.push(*digit as char);
+        }
+    }
+    result.push_str("~>");
+    result
+}
+
+// Escapes text for a string shown with a Latin-1 encoded font. Characters above 126 are written
+// as octal escapes so the output stays 7-bit clean. Characters outside Latin-1 are dropped; they
+// are shown with glyphshow instead.
+pub(crate) fn escape_latin1(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                result.push('\\');
+                result.push(c);
+            }
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\u{7F}'..='\u{FF}' => result.push_str(&format!("\\{:03o}", c as u32)),
+            c if (c as u32) < 0x7F => result.push(c),
+            _ => {}
+        }
+    }
+    result
+}
+
+const GREEK: [&str; 25] = [
+    "Alpha", "Beta", "Gamma", "Delta", "Epsilon", "Zeta", "Eta", "Theta", "Iota", "Kappa",
+    "Lambda", "Mu", "Nu", "Xi", "Omicron", "Pi", "Rho", "sigma1", "Sigma", "Tau", "Upsilon", "Phi",
+    "Chi", "Psi", "Omega",
+];
+
+// The glyph name used to show a character with glyphshow. Characters in the standard fonts
+// outside Latin-1 (including the Greek letters in Symbol) use their Adobe glyph names, anything
+// else is named uniXXXX.
+pub(crate) fn glyph_name(c: char) -> String {
+    let name = match c {
+        '\u{0391}'..='\u{03A9}' if c != '\u{03A2}' => GREEK[c as usize - 0x391],
+        '\u{03B1}'..='\u{03C9}' => return GREEK[c as usize - 0x3B1].to_lowercase(),
+        '\u{0131}' => "dotlessi",
+        '\u{0141}' => "Lslash",
+        '\u{0142}' => "lslash",
+        '\u{0152}' => "OE",
+        '\u{0153}' => "oe",
+        '\u{0160}' => "Scaron",
+        '\u{0161}' => "scaron",
+        '\u{0178}' => "Ydieresis",
+        '\u{017D}' => "Zcaron",
+        '\u{017E}' => "zcaron",
+        '\u{0192}' => "florin",
+        '\u{02C6}' => "circumflex",
+        '\u{02C7}' => "caron",
+        '\u{02DC}' => "tilde",
+        '\u{2013}' => "endash",
+        '\u{2014}' => "emdash",
+        '\u{2018}' => "quoteleft",
+        '\u{2019}' => "quoteright",
+        '\u{201A}' => "quotesinglbase",
+        '\u{201C}' => "quotedblleft",
+        '\u{201D}' => "quotedblright",
+        '\u{201E}' => "quotedblbase",
+        '\u{2020}' => "dagger",
+        '\u{2021}' => "daggerdbl",
+        '\u{2022}' => "bullet",
+        '\u{2026}' => "ellipsis",
+        '\u{2030}' => "perthousand",
+        '\u{2039}' => "guilsinglleft",
+        '\u{203A}' => "guilsinglright",
+        '\u{2044}' => "fraction",
+        '\u{20AC}' => "Euro",
+        '\u{2122}' => "trademark",
+        '\u{2212}' => "minus",
+        '\u{FB01}' => "fi",
+        '\u{FB02}' => "fl",
+        _ => return format!("uni{:04X}", c as u32),
+    };
+    name.to_string()
+}
@@ -0,0 +1,193 @@
+use std::fmt::Write;
+
+pub enum ScoreDirection {
+    Horizontal,
+    Vertical,
+}
+
+// Marks telling the finisher where to punch grommets, drill holes and score, positioned relative
+// to the page's trim box. Grommet and drill marks are inside the trim where the holes are made.
+// Score marks are dashed lines in the slug area on both sides of the trim so they're cut away.
+pub struct FinishingMarks {
+    grommets: Option<(f32, f32, f32)>,
+    drill_holes: Vec<(f32, f32, f32)>,
+    scores: Vec<(ScoreDirection, f32)>,
+    width: f32,
+    length: f32,
+    offset: f32,
+}
+
+impl Default for FinishingMarks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn round(value: f32) -> f32 {
+    (value * 1000.0).round() / 1000.0 + 0.0
+}
+
+impl FinishingMarks {
+    pub fn new() -> Self {
+        FinishingMarks {
+            grommets: None,
+            drill_holes: Vec::new(),
+            scores: Vec::new(),
+            width: 0.5,
+            length: 12.0,
+            offset: 3.0,
+        }
+    }
+
+    // Grommets are placed in every corner, inset from both edges, and evenly along each edge no
+    // more than the spacing apart.
+    pub fn grommets(mut self, spacing: f32, inset: f32, diameter: f32) -> Self {
+        self.grommets = Some((spacing.max(1.0), inset.max(0.0), diameter.max(0.0)));
+        self
+    }
+
+    // The hole's center is relative to the bottom left corner of the trim box.
+    pub fn drill_hole(mut self, x: f32, y: f32, diameter: f32) -> Self {
+        self.drill_holes.push((x, y, diameter.max(0.0)));
+        self
+    }
+
+    // The position of a vertical score is measured from the left of the trim box and a horizontal
+    // score from the bottom.
+    pub fn score(mut self, direction: ScoreDirection, position: f32) -> Self {
+        self.scores.push((direction, position));
+        self
+    }
+
+    pub fn width(mut self, width: f32) -> Self {
+        self.width = width.max(0.0);
+        self
+    }
+
+    // The length of the score marks and their distance from the trim box.
+    pub fn score_length(mut self, length: f32, offset: f32) -> Self {
+        self.length = length.max(0.0);
+        self.offset = offset.max(0.0);
+        self
+    }
+
+    // The space the score marks need outside the trim box.
+    pub(crate) fn slug(&self) -> f32 {
+        if self.scores.is_empty() {
+            0.0
+        } else {
+            self.length + self.offset
+        }
+    }
+
+    // The grommet centers, starting in the bottom left corner and going around the trim box
+    // counterclockwise.
+    fn grommet_centers(&self, x: f32, y: f32, width: f32, height: f32) -> Vec<(f32, f32)> {
+        let Some((spacing, inset, _)) = self.grommets else {
+            return Vec::new();
+        };
+        let corners = [
+            (x + inset, y + inset),
+            (x + width - inset, y + inset),
+            (x + width - inset, y + height - inset),
+            (x + inset, y + height - inset),
+        ];
+        let mut centers = Vec::new();
+        for (i, start) in corners.iter().enumerate() {
+            let end = corners[(i + 1) % 4];
+            let length = (end.0 - start.0).abs() + (end.1 - start.1).abs();
+            let steps = (length / spacing).ceil().max(1.0) as usize;
+            for step in 0..steps {
+                let t = step as f32 / steps as f32;
+                centers.push((
+                    start.0 + (end.0 - start.0) * t,
+                    start.1 + (end.1 - start.1) * t,
+                ));
+            }
+        }
+        centers
+    }
+
+    pub(crate) fn fits(&self, width: f32, height: f32) -> bool {
+        match self.grommets {
+            Some((_, inset, _)) => inset * 2.0 < width && inset * 2.0 < height,
+            None => true,
+        }
+    }
+
+    // Marks are drawn in registration black so they appear on every separation.
+    pub(crate) fn place(&self, x: f32, y: f32, width: f32, height: f32) -> String {
+        let mut result = String::new();
+        let centers = self.grommet_centers(x, y, width, height);
+        if (centers.is_empty() && self.drill_holes.is_empty() && self.scores.is_empty())
+            || self.width == 0.0
+        {
+            return result;
+        }
+
+        write!(
+            &mut result,
+            "gsave 1 1 1 1 setcmykcolor {} setlinewidth ",
+            self.width
+        )
+        .unwrap();
+        if let Some((_, _, diameter)) = self.grommets {
+            for (cx, cy) in centers {
+                write!(
+                    &mut result,
+                    "newpath {} {} {} 0 360 arc stroke ",
+                    round(cx),
+                    round(cy),
+                    diameter / 2.0
+                )
+                .unwrap();
+            }
+        }
+        // Drill marks are a circle with a cross through its center.
+        for (hole_x, hole_y, diameter) in &self.drill_holes {
+            let (cx, cy) = (x + hole_x, y + hole_y);
+            let radius = diameter / 2.0;
+            write!(
+                &mut result,
+                "newpath {} {} {} 0 360 arc stroke newpath {} {} moveto {} 0 rlineto {} {} moveto 0 {} rlineto stroke ",
+                cx,
+                cy,
+                radius,
+                cx - radius * 1.5,
+                cy,
+                radius * 3.0,
+                cx,
+                cy - radius * 1.5,
+                radius * 3.0
+            )
+            .unwrap();
+        }
+        if !self.scores.is_empty() {
+            result.push_str("[3 2] 0 setdash ");
+        }
+        for (direction, position) in &self.scores {
+            match direction {
+                ScoreDirection::Vertical => write!(
+                    &mut result,
+                    "newpath {x} {} moveto 0 {l} rlineto {x} {} moveto 0 -{l} rlineto stroke ",
+                    y + height + self.offset,
+                    y - self.offset,
+                    x = x + position,
+                    l = self.length
+                ),
+                ScoreDirection::Horizontal => write!(
+                    &mut result,
+                    "newpath {} {y} moveto -{l} 0 rlineto {} {y} moveto {l} 0 rlineto stroke ",
+                    x - self.offset,
+                    x + width + self.offset,
+                    y = y + position,
+                    l = self.length
+                ),
+            }
+            .unwrap();
+        }
+        result.push_str("grestore ");
+
+        result
+    }
+}
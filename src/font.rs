@@ -0,0 +1,127 @@
+// The 35 fonts built into PostScript devices, plus any other font by name.
+pub enum Font {
+    AvantGardeBook,
+    AvantGardeBookOblique,
+    AvantGardeDemi,
+    AvantGardeDemiOblique,
+    BookmanDemi,
+    BookmanDemiItalic,
+    BookmanLight,
+    BookmanLightItalic,
+    Courier,
+    CourierBold,
+    CourierBoldOblique,
+    CourierOblique,
+    Helvetica,
+    HelveticaBold,
+    HelveticaBoldOblique,
+    HelveticaOblique,
+    HelveticaNarrow,
+    HelveticaNarrowBold,
+    HelveticaNarrowBoldOblique,
+    HelveticaNarrowOblique,
+    NewCenturySchoolbookRoman,
+    NewCenturySchoolbookBold,
+    NewCenturySchoolbookItalic,
+    NewCenturySchoolbookBoldItalic,
+    PalatinoRoman,
+    PalatinoBold,
+    PalatinoItalic,
+    PalatinoBoldItalic,
+    Symbol,
+    TimesRoman,
+    TimesBold,
+    TimesItalic,
+    TimesBoldItalic,
+    ZapfChanceryMediumItalic,
+    ZapfDingbats,
+    Custom(String),
+}
+
+impl Font {
+    pub fn name(&self) -> &str {
+        match self {
+            Font::AvantGardeBook => "AvantGarde-Book",
+            Font::AvantGardeBookOblique => "AvantGarde-BookOblique",
+            Font::AvantGardeDemi => "AvantGarde-Demi",
+            Font::AvantGardeDemiOblique => "AvantGarde-DemiOblique",
+            Font::BookmanDemi => "Bookman-Demi",
+            Font::BookmanDemiItalic => "Bookman-DemiItalic",
+            Font::BookmanLight => "Bookman-Light",
+            Font::BookmanLightItalic => "Bookman-LightItalic",
+            Font::Courier => "Courier",
+            Font::CourierBold => "Courier-Bold",
+            Font::CourierBoldOblique => "Courier-BoldOblique",
+            Font::CourierOblique => "Courier-Oblique",
+            Font::Helvetica => "Helvetica",
+            Font::HelveticaBold => "Helvetica-Bold",
+            Font::HelveticaBoldOblique => "Helvetica-BoldOblique",
+            Font::HelveticaOblique => "Helvetica-Oblique",
+            Font::HelveticaNarrow => "Helvetica-Narrow",
+            Font::HelveticaNarrowBold => "Helvetica-Narrow-Bold",
+            Font::HelveticaNarrowBoldOblique => "Helvetica-Narrow-BoldOblique",
+            Font::HelveticaNarrowOblique => "Helvetica-Narrow-Oblique",
+            Font::NewCenturySchoolbookRoman => "NewCenturySchlbk-Roman",
+            Font::NewCenturySchoolbookBold => "NewCenturySchlbk-Bold",
+            Font::NewCenturySchoolbookItalic => "NewCenturySchlbk-Italic",
+            Font::NewCenturySchoolbookBoldItalic => "NewCenturySchlbk-BoldItalic",
+            Font::PalatinoRoman => "Palatino-Roman",
+            Font::PalatinoBold => "Palatino-Bold",
+            Font::PalatinoItalic => "Palatino-Italic",
+            Font::PalatinoBoldItalic => "Palatino-BoldItalic",
+            Font::Symbol => "Symbol",
+            Font::TimesRoman => "Times-Roman",
+            Font::TimesBold => "Times-Bold",
+            Font::TimesItalic => "Times-Italic",
+            Font::TimesBoldItalic => "Times-BoldItalic",
+            Font::ZapfChanceryMediumItalic => "ZapfChancery-MediumItalic",
+            Font::ZapfDingbats => "ZapfDingbats",
+            Font::Custom(name) => name,
+        }
+    }
+}
+
+// The bold face of a standard font, eg: Times-Italic is Times-BoldItalic.
+pub(crate) fn bold_name(name: &str) -> Option<&'static str> {
+    match name {
+        "AvantGarde-Book" => Some("AvantGarde-Demi"),
+        "AvantGarde-BookOblique" => Some("AvantGarde-DemiOblique"),
+        "Bookman-Light" => Some("Bookman-Demi"),
+        "Bookman-LightItalic" => Some("Bookman-DemiItalic"),
+        "Courier" => Some("Courier-Bold"),
+        "Courier-Oblique" => Some("Courier-BoldOblique"),
+        "Helvetica" => Some("Helvetica-Bold"),
+        "Helvetica-Oblique" => Some("Helvetica-BoldOblique"),
+        "Helvetica-Narrow" => Some("Helvetica-Narrow-Bold"),
+        "Helvetica-Narrow-Oblique" => Some("Helvetica-Narrow-BoldOblique"),
+        "NewCenturySchlbk-Roman" => Some("NewCenturySchlbk-Bold"),
+        "NewCenturySchlbk-Italic" => Some("NewCenturySchlbk-BoldItalic"),
+        "Palatino-Roman" => Some("Palatino-Bold"),
+        "Palatino-Italic" => Some("Palatino-BoldItalic"),
+        "Times-Roman" => Some("Times-Bold"),
+        "Times-Italic" => Some("Times-BoldItalic"),
+        _ => None,
+    }
+}
+
+// Whether the font is one of the standard fonts with the Adobe standard Latin character set.
+pub(crate) fn is_standard_latin(name: &str) -> bool {
+    [
+        "AvantGarde-",
+        "Bookman-",
+        "Courier",
+        "Helvetica",
+        "NewCenturySchlbk-",
+        "Palatino-",
+        "Times-",
+        "ZapfChancery-",
+    ]
+    .iter()
+    .any(|family| name.starts_with(family))
+}
+
+impl From<&str> for Font {
+    fn from(name: &str) -> Self {
+        Font::Custom(name.to_string())
+    }
+}
@@ -0,0 +1,78 @@
+use std::{collections::HashMap, fmt::Write};
+
+use crate::{encoding, Serialize};
+
+pub struct Form {
+    name: String,
+    width: i32,
+    height: i32,
+    buffer: String,
+    warnings: Vec<String>,
+}
+
+impl Form {
+    // Characters that would end a PostScript name are dropped from the name.
+    pub fn new(name: &str, width: i32, height: i32) -> Self {
+        Form {
+            name: encoding::name_text(name),
+            width: width.max(1),
+            height: height.max(1),
+            buffer: String::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    pub fn add<T: Serialize>(&mut self, item: &T) {
+        self.buffer.push_str(&item.to_postscript_string());
+        self.warnings.extend(item.warnings());
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    pub fn definition(&self) -> String {
+        let mut result = String::new();
+        write!(
+            &mut result,
+            "/{} << /FormType 1 /BBox [0 0 {} {}] /Matrix [1 0 0 1 0 0] /PaintProc {{ pop {}}} >> def",
+            self.name, self.width, self.height, self.buffer
+        )
+        .unwrap();
+        result
+    }
+}
+
+pub struct FormRegistry {
+    forms: HashMap<String, Form>,
+}
+
+impl Default for FormRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FormRegistry {
+    pub fn new() -> Self {
+        FormRegistry {
+            forms: HashMap::new(),
+        }
+    }
+
+    pub fn add_form(&mut self, form: Form) {
+        self.forms.insert(form.name.clone(), form);
+    }
+
+    pub fn get_form(&self, name: &str) -> Option<&Form> {
+        self.forms.get(name)
+    }
+
+    pub fn list_forms(&self) -> Vec<&Form> {
+        self.forms.values().collect()
+    }
+}
@@ -0,0 +1,138 @@
+use crate::{
+    style::{FillStyle, StrokeStyle, TransformStyle},
+    Serialize, Transform,
+};
+
+// Elements that can be added to a `Group`. Elements that have their own fill and stroke use the
+// group's defaults when they weren't styled explicitly; the default implementation ignores them.
+pub trait GroupItem: Serialize {
+    fn to_postscript_string_in(&self, _group: &Group) -> String {
+        self.to_postscript_string()
+    }
+}
+
+pub struct Group {
+    buffer: String,
+    warnings: Vec<String>,
+    fill: FillStyle,
+    stroke: StrokeStyle,
+    transform: TransformStyle,
+}
+
+impl Default for Group {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Group {
+    pub fn new() -> Self {
+        Group {
+            buffer: String::new(),
+            warnings: Vec::new(),
+            fill: FillStyle::new(),
+            stroke: StrokeStyle::new(0.0),
+            transform: TransformStyle::new(),
+        }
+    }
+
+    // Defaults are applied as items are added, so they should be set first.
+    pub fn add<T: GroupItem>(&mut self, item: &T) {
+        self.buffer.push_str(&item.to_postscript_string_in(self));
+        self.warnings.extend(item.warnings());
+    }
+
+    pub fn fill_rgb(mut self, r: f32, g: f32, b: f32) -> Self {
+        self.fill.rgb(r, g, b);
+        self
+    }
+
+    pub fn fill_cmyk(mut self, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.fill.cmyk(c, m, y, k);
+        self
+    }
+
+    pub fn stroke_rgb(mut self, width: f32, r: f32, g: f32, b: f32) -> Self {
+        self.stroke.rgb(width, r, g, b);
+        self
+    }
+
+    pub fn stroke_cmyk(mut self, width: f32, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.stroke.cmyk(width, c, m, y, k);
+        self
+    }
+
+    pub fn translate(mut self, x: f32, y: f32) -> Self {
+        self.transform.translate(x, y);
+        self
+    }
+
+    pub fn scale(mut self, x: f32, y: f32) -> Self {
+        self.transform.scale(x, y);
+        self
+    }
+
+    pub fn rotate(mut self, angle: f32) -> Self {
+        self.transform.rotate(angle);
+        self
+    }
+
+    pub fn flip_horizontal(mut self) -> Self {
+        self.transform.flip_horizontal();
+        self
+    }
+
+    pub fn flip_vertical(mut self) -> Self {
+        self.transform.flip_vertical();
+        self
+    }
+
+    pub fn skew(mut self, x: f32, y: f32) -> Self {
+        self.transform.skew(x, y);
+        self
+    }
+
+    pub fn transform(mut self, matrix: Transform) -> Self {
+        self.transform.matrix(matrix);
+        self
+    }
+
+    pub(crate) fn fill_for<'a>(&'a self, own: &'a FillStyle) -> &'a FillStyle {
+        if own.is_enabled() || !self.fill.is_enabled() {
+            own
+        } else {
+            &self.fill
+        }
+    }
+
+    pub(crate) fn stroke_for<'a>(&'a self, own: &'a StrokeStyle) -> &'a StrokeStyle {
+        if own.is_set() || !self.stroke.is_set() {
+            own
+        } else {
+            &self.stroke
+        }
+    }
+}
+
+impl Serialize for Group {
+    fn to_postscript_string(&self) -> String {
+        let mut result = String::new();
+
+        if self.buffer.is_empty() {
+            return result;
+        }
+
+        // Group transforms are relative to the user space origin.
+        self.transform.begin(&mut result, 0.0, 0.0, 0.0, 0.0);
+        result.push_str(&self.buffer);
+        self.transform.end(&mut result);
+
+        result
+    }
+
+    fn warnings(&self) -> Vec<String> {
+        self.warnings.clone()
+    }
+}
+
+impl GroupItem for Group {}
@@ -1,15 +1,47 @@
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::HashMap,
+    io::{Error, ErrorKind},
+    path::Path,
+};
+
+use crate::{ImageFit, InlineImage, Serialize};
 
 pub struct RawImage {
     file_name: String,
-    file_path: Box<Path>,
+    image: InlineImage,
     procedure_name: String,
 }
+
+impl RawImage {
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    pub fn procedure_name(&self) -> &str {
+        &self.procedure_name
+    }
+
+    // A procedure that draws the image stretched over the unit square.
+    pub fn definition(&self) -> String {
+        format!(
+            "/{} {{ {}}} def",
+            self.procedure_name,
+            self.image.to_postscript_string()
+        )
+    }
+}
+
 pub struct ImageRegistry {
     images: HashMap<String, RawImage>,
     count: u32,
 }
 
+impl Default for ImageRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ImageRegistry {
     pub fn new() -> Self {
         ImageRegistry {
@@ -18,29 +50,28 @@ impl ImageRegistry {
         }
     }
 
-    pub fn add(mut self, path: &Path) -> Self {
+    // The image is decoded when it is added, so unsupported or damaged files are reported here
+    // rather than when the document is built.
+    pub fn add_image(&mut self, path: &Path) -> Result<(), Error> {
         let file_name = path
             .file_name()
-            .expect("Unable to determine file name.")
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "Unable to determine file name."))?
             .to_string_lossy()
             .to_string();
+        let image = InlineImage::load(path, 0.0, 0.0, 1.0, 1.0)?.fit(ImageFit::Stretch);
         self.count += 1;
         let proc_name = format!("imager{}", self.count);
         let image = RawImage {
             file_name: file_name.clone(),
-            file_path: path.into(),
+            image,
             procedure_name: proc_name,
         };
         self.images.insert(file_name, image);
-        self
+        Ok(())
     }
 
-    pub fn get_procedure_id(self, file_name: String) -> Option<String> {
-        let raw = self.images.get(&file_name);
-        if raw.is_none() {
-            return None;
-        }
-        let raw = raw.unwrap();
+    pub fn get_procedure_id(&self, file_name: &str) -> Option<String> {
+        let raw = self.images.get(file_name)?;
         Some(raw.procedure_name.clone())
     }
 
@@ -1,29 +1,136 @@
-use std::path::Path;
+use std::{fmt::Write, fs, io::Error, path::Path};
 
-use crate::ImageFit;
+use crate::{encoding::ascii85, raster::Raster, GroupItem, ImageFit, Serialize};
 
+// The largest string the image data is split into, well below the 65535 byte string limit.
+const MAX_STRING: usize = 32768;
+
+// An image written into the page each time it is added. Requires LanguageLevel 2.
 pub struct InlineImage {
     x: f32,
     y: f32,
     width: f32,
     height: f32,
-    rotate: f32,
-    scale: [f32; 2],
-    file_path: Box<Path>,
     fit: ImageFit,
+    raster: Raster,
 }
 
 impl InlineImage {
-    pub fn new(file_path: &Path, x: f32, y: f32, width: f32, height: f32) -> Self {
-        InlineImage {
-            x: x.max(0.0),
-            y: y.max(0.0),
+    // The box is positioned by its bottom left corner.
+    pub fn load(file_path: &Path, x: f32, y: f32, width: f32, height: f32) -> Result<Self, Error> {
+        InlineImage::from_bytes(&fs::read(file_path)?, x, y, width, height)
+    }
+
+    pub fn from_bytes(data: &[u8], x: f32, y: f32, width: f32, height: f32) -> Result<Self, Error> {
+        Ok(InlineImage {
+            x,
+            y,
             width: width.max(0.0),
             height: height.max(0.0),
-            rotate: 0.0,
-            scale: [0.0, 0.0],
-            file_path: file_path.into(),
             fit: ImageFit::Contain,
+            raster: Raster::decode(data)?,
+        })
+    }
+
+    pub fn fit(mut self, fit: ImageFit) -> Self {
+        self.fit = fit;
+        self
+    }
+
+    // The size the image is drawn at. Contain keeps the whole image in the box and Crop covers
+    // the box; both keep the aspect ratio. The stretch modes fill the box in one or both
+    // directions and keep the contained size in the other.
+    fn size(&self) -> (f32, f32) {
+        let (width, height) = (self.raster.width as f32, self.raster.height as f32);
+        let contain = (self.width / width).min(self.height / height);
+        let cover = (self.width / width).max(self.height / height);
+        match self.fit {
+            ImageFit::Contain => (width * contain, height * contain),
+            ImageFit::Crop => (width * cover, height * cover),
+            ImageFit::Stretch => (self.width, self.height),
+            ImageFit::StretchHorizontal => (self.width, height * contain),
+            ImageFit::StretchVertical => (width * contain, self.height),
+        }
+    }
+}
+
+impl Serialize for InlineImage {
+    fn to_postscript_string(&self) -> String {
+        let mut result = String::new();
+
+        if self.width == 0.0 || self.height == 0.0 {
+            return result;
+        }
+
+        // The image is centered in the box.
+        let (width, height) = self.size();
+        result.push_str("gsave ");
+        if let ImageFit::Crop = self.fit {
+            write!(
+                &mut result,
+                "newpath {} {} moveto {} 0 rlineto 0 {} rlineto {} 0 rlineto closepath clip newpath ",
+                self.x, self.y, self.width, self.height, -self.width
+            )
+            .unwrap();
         }
+        write!(
+            &mut result,
+            "{} {} translate {} {} scale ",
+            self.x + (self.width - width) / 2.0,
+            self.y + (self.height - height) / 2.0,
+            width,
+            height
+        )
+        .unwrap();
+
+        // Data too long for a single string is read from an array of strings. Once they run out an
+        // empty string signals the end of the data, as filters may read ahead.
+        let strings: Vec<String> = self
+            .raster
+            .samples
+            .chunks(MAX_STRING)
+            .map(ascii85)
+            .collect();
+        let source = if strings.len() == 1 {
+            strings[0].clone()
+        } else {
+            write!(
+                &mut result,
+                "2 dict begin /pslib_data [ {} ] def /pslib_index -1 def ",
+                strings.join(" ")
+            )
+            .unwrap();
+            "{ /pslib_index pslib_index 1 add def pslib_index pslib_data length lt \
+             { pslib_data pslib_index get } { () } ifelse }"
+                .to_string()
+        };
+        let source = match self.raster.filter {
+            Some(filter) => format!("{} /{} filter", source, filter),
+            None => source,
+        };
+        let (space, decode) = match (self.raster.components, self.raster.inverted) {
+            (4, true) => ("DeviceCMYK", "1 0 1 0 1 0 1 0"),
+            (4, false) => ("DeviceCMYK", "0 1 0 1 0 1 0 1"),
+            (3, _) => ("DeviceRGB", "0 1 0 1 0 1"),
+            _ => ("DeviceGray", "0 1"),
+        };
+        write!(
+            &mut result,
+            "/{} setcolorspace << /ImageType 1 /Width {w} /Height {h} /BitsPerComponent 8 /Decode [{}] /ImageMatrix [{w} 0 0 -{h} 0 {h}] /DataSource {} >> image ",
+            space,
+            decode,
+            source,
+            w = self.raster.width,
+            h = self.raster.height
+        )
+        .unwrap();
+        if strings.len() > 1 {
+            result.push_str("end ");
+        }
+        result.push_str("grestore ");
+
+        result
     }
 }
+
+impl GroupItem for InlineImage {}
@@ -1,11 +1,12 @@
 use std::{
-    collections::HashMap,
-    io::{BufWriter, Error, Write},
+    collections::{HashMap, HashSet},
+    fmt,
+    io::{BufWriter, Error, ErrorKind, Write},
+    sync::atomic::{AtomicBool, Ordering},
 };
 
 mod rect;
 use chrono::Utc;
-use image_registry::ImageRegistry;
 pub use rect::Rect;
 
 mod page;
@@ -15,17 +16,145 @@ mod line;
 pub use line::Line;
 
 mod image_registry;
-
-mod image;
+pub use image_registry::{ImageRegistry, RawImage};
 
 mod inline_image;
+pub use inline_image::InlineImage;
+
+mod raster;
+
+mod condition;
+pub use condition::Condition;
+
+mod style;
+
+mod sha256;
+use sha256::Sha256;
+
+mod encoding;
+
+mod polygon;
+pub use polygon::Polygon;
+
+mod arc;
+pub use arc::Arc;
+
+mod regular_polygon;
+pub use regular_polygon::RegularPolygon;
+
+mod star;
+pub use star::Star;
+
+mod polyline;
+pub use polyline::Polyline;
+
+mod ring;
+pub use ring::Ring;
+
+mod marker;
+pub use marker::{Marker, MarkerSymbol};
+
+mod vignette;
+pub use vignette::Vignette;
+
+mod path;
+pub use path::Path;
+
+mod svg_path;
+
+mod tee;
+pub use tee::TeeWriter;
+
+mod paced;
+pub use paced::PacedWriter;
+
+mod transform;
+pub use transform::Transform;
+
+mod clip;
+pub use clip::Clip;
+
+mod group;
+pub use group::{Group, GroupItem};
+
+mod border;
+pub use border::{Border, BorderMotif};
+
+mod text;
+pub use text::Text;
+
+mod font;
+pub use font::Font;
+
+mod truetype;
+pub use truetype::{FontRegistry, TrueTypeFont};
+
+mod metrics;
+pub use metrics::FontMetrics;
+
+mod paragraph;
+pub use paragraph::{Hyphenator, Paragraph, TabAlignment, TabStop};
+
+mod rich_text;
+pub use rich_text::{RichText, Span};
+
+mod accounting;
+pub use accounting::Accounting;
+
+mod numbering;
+pub use numbering::{CheckDigit, Numbering};
+
+mod report;
+pub use report::Report;
+
+mod color_patches;
+pub use color_patches::{ColorPatch, ColorPatches};
+
+mod slug;
+pub use slug::{SlugLine, SlugPosition};
+
+mod step_repeat;
+pub use step_repeat::{StepOrientation, StepRepeat};
+
+mod die_line;
+pub use die_line::DieLine;
+
+mod separation;
+pub use separation::TechnicalSeparation;
+
+mod finishing;
+pub use finishing::{FinishingMarks, ScoreDirection};
+
+mod wedge;
+pub use wedge::Wedge;
+
+mod form;
+pub use form::{Form, FormRegistry};
+
+mod revision;
+pub use revision::{ChangeBar, RevisionCloud, RevisionMark};
 
 pub trait Fabricate {
-    fn fabricate<W: Write>(&self, doc_type: &DocumentType, writer: &mut BufWriter<W>) -> Result<(), Error>;
+    fn fabricate<W: Write>(
+        &self,
+        doc_type: &DocumentType,
+        flags: &HashSet<String>,
+        writer: &mut BufWriter<W>,
+    ) -> Result<(), Error>;
+
+    // Problems found while the content rendered with the given flags was laid out, eg: text that
+    // doesn't fit, which are added to the document's report.
+    fn warnings(&self, _flags: &HashSet<String>) -> Vec<String> {
+        Vec::new()
+    }
 }
 
 pub trait Serialize {
     fn to_postscript_string(&self) -> String;
+
+    fn warnings(&self) -> Vec<String> {
+        Vec::new()
+    }
 }
 
 pub enum DocumentType {
@@ -47,6 +176,80 @@ pub enum TransformLineOrigin {
     Right,
 }
 
+pub enum LanguageLevel {
+    Level1,
+    Level2, // Default
+    Level3,
+}
+
+impl LanguageLevel {
+    pub fn operand_stack_limit(&self) -> usize {
+        match self {
+            LanguageLevel::Level1 => 500,
+            // Level 2 and 3 interpreters grow the operand stack dynamically.
+            LanguageLevel::Level2 | LanguageLevel::Level3 => 65535,
+        }
+    }
+
+    pub fn typical_vm(&self) -> Option<usize> {
+        match self {
+            LanguageLevel::Level1 => Some(240_000),
+            LanguageLevel::Level2 | LanguageLevel::Level3 => None,
+        }
+    }
+}
+
+pub enum LineEnding {
+    LF, // Default
+    CRLF,
+    CR,
+}
+
+pub enum ResourcePolicy {
+    Preload, // Default
+    PerPage,
+}
+
+pub enum ChapterStart {
+    AnyPage, // Default
+    OddPage,
+    EvenPage,
+}
+
+pub enum LineCap {
+    Butt, // Default
+    Round,
+    Square,
+}
+
+pub enum LineJoin {
+    Miter, // Default
+    Round,
+    Bevel,
+}
+
+pub enum PaintOrder {
+    FillFirst, // Default
+    StrokeFirst,
+}
+
+pub enum FillRule {
+    NonZero, // Default
+    EvenOdd,
+}
+
+pub enum HatchPattern {
+    Horizontal,
+    Vertical,
+    Diagonal,
+    Cross,
+}
+
+pub enum Orientation {
+    Portrait, // Default
+    Landscape,
+}
+
 pub enum ColorMode {
     CMYK,
     RGB,
@@ -60,10 +263,55 @@ pub enum ImageFit {
     Crop,
 }
 
+// Called after each page is written with the number of pages and bytes written so far.
+type ProgressCallback = Box<dyn FnMut(u32, usize)>;
+
+// Pages are written in pieces of this size, checking for cancellation before each, so a page
+// with large images can be cancelled part way through.
+const CANCEL_INTERVAL: usize = 32768;
+
+// The error wrapped by the error returned once the cancel flag is set. Its kind is
+// ErrorKind::Other; callers can tell it apart from other errors with
+// `error.get_ref().is_some_and(|inner| inner.is::<Cancelled>())`.
+#[derive(Debug)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Document generation was cancelled.")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
 pub struct Document<W: Write> {
     doc_type: DocumentType,
     buffer: BufWriter<W>,
     page_count: u32,
+    flags: HashSet<String>,
+    binary_tokens: bool,
+    seven_bit: bool,
+    line_ending: LineEnding,
+    max_line_length: usize,
+    language_level: LanguageLevel,
+    vm_usage: usize,
+    warnings: Vec<String>,
+    resources: Vec<String>,
+    resource_policy: ResourcePolicy,
+    chapter_start: ChapterStart,
+    duplex: bool,
+    page_resources: Vec<(String, String)>,
+    error_handler: bool,
+    bytes_written: usize,
+    progress: Option<ProgressCallback>,
+    cancel: Option<std::sync::Arc<AtomicBool>>,
+    digest: Option<Sha256>,
+    subset_fonts: Vec<TrueTypeFont>,
+    held: Option<Vec<u8>>,
+    held_setup: (usize, String),
+    unfinished: Vec<u8>,
+    cid_fonts: HashSet<String>,
+    bookmarks: Vec<(String, u32)>,
 }
 
 impl<W: Write> Document<W> {
@@ -72,64 +320,554 @@ impl<W: Write> Document<W> {
             doc_type: DocumentType::PS,
             buffer: writer,
             page_count: 0,
+            flags: HashSet::new(),
+            binary_tokens: false,
+            seven_bit: false,
+            line_ending: LineEnding::LF,
+            max_line_length: usize::MAX,
+            language_level: LanguageLevel::Level2,
+            vm_usage: 0,
+            warnings: Vec::new(),
+            resources: Vec::new(),
+            resource_policy: ResourcePolicy::Preload,
+            chapter_start: ChapterStart::AnyPage,
+            duplex: false,
+            page_resources: Vec::new(),
+            error_handler: false,
+            bytes_written: 0,
+            progress: None,
+            cancel: None,
+            digest: None,
+            subset_fonts: Vec::new(),
+            held: None,
+            held_setup: (0, String::new()),
+            unfinished: Vec::new(),
+            cid_fonts: HashSet::new(),
+            bookmarks: Vec::new(),
         };
-        doc.buffer
-            .write_all(
-                format!(
-                    r#"%!PS-Adobe-3.0
+        doc.write_output(
+            format!(
+                r#"%!PS-Adobe-3.0
 %%Creator: pslib {}
 %%CreationDate: {}
 %%Pages: (atend)
 %%EndComments
 "#,
-                    env!("CARGO_PKG_VERSION"),
-                    Utc::now().to_rfc3339()
-                )
-                .as_bytes(),
+                env!("CARGO_PKG_VERSION"),
+                Utc::now().to_rfc3339()
             )
-            .unwrap();
+            .as_bytes(),
+        )
+        .unwrap();
         let registry = ProcedureRegistry::with_builtins();
         for procedure in registry.list_procedures() {
-            doc.buffer.write_all(procedure.body.as_bytes()).unwrap();
-            doc.buffer.write_all("\n".as_bytes()).unwrap();
+            doc.define_resource(&procedure.name, procedure.body.as_bytes())
+                .unwrap();
         }
         doc
     }
 
     pub fn add<T: Fabricate>(&mut self, item: &T) -> Result<(), Error> {
-        match self.doc_type {
-            DocumentType::PS => {
-                self.page_count += 1;
-                self.buffer.write_all(
-                    format!("%%Page: {} {}\n", self.page_count, self.page_count).as_bytes(),
-                )?;
+        self.check_cancelled()?;
+        self.page_count += 1;
+        if let DocumentType::PS = self.doc_type {
+            self.write_output(
+                format!("%%Page: {} {}\n", self.page_count, self.page_count).as_bytes(),
+            )?;
+        }
+        let mut page = BufWriter::new(Vec::new());
+        item.fabricate(&self.doc_type, &self.flags, &mut page)?;
+        let page = self.wrap_page(page.into_inner().map_err(|e| e.into_error())?);
+        self.check_cid_text(&page)?;
+        for warning in item.warnings(&self.flags) {
+            self.warnings
+                .push(format!("Page {}: {}", self.page_count, warning));
+        }
+        if let Some(limit) = self.language_level.typical_vm() {
+            if self.vm_usage + encoding::estimate_vm(&page) > limit {
+                self.warnings.push(format!(
+                    "Page {} may exceed the typical LanguageLevel 1 VM of {} bytes.",
+                    self.page_count, limit
+                ));
             }
-            _ => {}
         }
-        item.fabricate(&self.doc_type, &mut self.buffer)
+        // Images are encoded while the page is generated, which can't be interrupted, but
+        // writing them out can.
+        for piece in page.chunks(CANCEL_INTERVAL) {
+            self.check_cancelled()?;
+            self.write_output(piece)?;
+        }
+        let bytes_written = self.bytes_written();
+        if let Some(callback) = &mut self.progress {
+            callback(self.page_count, bytes_written);
+        }
+        Ok(())
+    }
+
+    // A blank page still has its own page comments, setup and showpage so the page count and
+    // duplex sides stay correct.
+    pub fn add_blank_page(&mut self, width: i32, height: i32) -> Result<(), Error> {
+        self.add(&Page::new(width, height))
     }
 
-    pub fn close(mut self) -> Result<(), Error> {
-        self.buffer.write_all("%%EOF".as_bytes())?;
+    // Adds the first page of a chapter, preceded by a blank page of the same size when needed to
+    // start the chapter on the side set by the chapter_start policy.
+    pub fn add_chapter(&mut self, page: &Page) -> Result<(), Error> {
+        let next = self.page_count + 1;
+        let blank = match self.chapter_start {
+            ChapterStart::AnyPage => false,
+            ChapterStart::OddPage => next.is_multiple_of(2),
+            ChapterStart::EvenPage => !next.is_multiple_of(2),
+        };
+        if blank {
+            let (width, height) = page.size();
+            self.add_blank_page(width, height)?;
+        }
+        self.add(page)
+    }
+
+    // Adds the page only when the condition is met by the document's flags, eg: a terms page for
+    // new customers. On duplex documents a skipped page is replaced with a blank page so the pages
+    // after it stay on the same side of the sheet.
+    pub fn add_if(&mut self, condition: Condition, page: &Page) -> Result<(), Error> {
+        if condition.is_met(&self.flags) {
+            return self.add(page);
+        }
+        if self.duplex {
+            let (width, height) = page.size();
+            return self.add_blank_page(width, height);
+        }
+        Ok(())
+    }
+
+    // Adds a PDF bookmark for the next page added, eg: one per customer in a batch of statements.
+    // The pages each bookmark covers are listed in the report. Interpreters without pdfmark
+    // ignore the bookmarks.
+    pub fn bookmark(&mut self, title: &str) {
+        self.bookmarks
+            .push((title.to_string(), self.page_count + 1));
+    }
+
+    fn check_cancelled(&self) -> Result<(), Error> {
+        match &self.cancel {
+            Some(cancel) if cancel.load(Ordering::Relaxed) => Err(Error::other(Cancelled)),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.buffer.flush()
+    }
+
+    // Includes any output held for font subsetting.
+    pub fn bytes_written(&self) -> usize {
+        self.bytes_written + self.held.as_ref().map_or(0, Vec::len)
+    }
+
+    pub fn vm_usage(&self) -> usize {
+        self.vm_usage
+    }
+
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    // Writes a resource that persists for the rest of the document (procedures, forms, fonts) and
+    // tracks the approximate VM it occupies.
+    fn define_resource(&mut self, name: &str, body: &[u8]) -> Result<(), Error> {
+        self.write_output(body)?;
+        self.resources.push(name.to_string());
+        self.write_output("\n".as_bytes())?;
+        let exceeded = self
+            .language_level
+            .typical_vm()
+            .map(|limit| self.vm_usage > limit);
+        self.vm_usage += encoding::estimate_vm(body);
+        if let Some(limit) = self.language_level.typical_vm() {
+            if exceeded == Some(false) && self.vm_usage > limit {
+                self.warnings.push(format!(
+                    "Document resources exceed the typical LanguageLevel 1 VM of {} bytes.",
+                    limit
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // `kind` is the DSC resource type, or `None` when the definition already carries its own
+    // resource comments (fonts).
+    fn add_resource(
+        &mut self,
+        name: &str,
+        kind: Option<&str>,
+        definition: String,
+    ) -> Result<(), Error> {
+        // Resources in the prolog can't be reclaimed, so on targets with little VM the resources
+        // that would exceed it are defined on the pages that use them instead, where they're
+        // discarded after each page.
+        if let (ResourcePolicy::Preload, Some(limit)) =
+            (&self.resource_policy, self.language_level.typical_vm())
+        {
+            if self.vm_usage + encoding::estimate_vm(definition.as_bytes()) > limit {
+                self.warnings.push(format!(
+                    "Resources from {} on are defined on each page that uses them to stay within the typical LanguageLevel 1 VM of {} bytes.",
+                    name, limit
+                ));
+                self.resource_policy = ResourcePolicy::PerPage;
+            }
+        }
+        match self.resource_policy {
+            ResourcePolicy::Preload => self.define_resource(name, definition.as_bytes()),
+            ResourcePolicy::PerPage => {
+                let definition = match kind {
+                    Some(kind) => format!(
+                        "%%BeginResource: {} {}\n{}\n%%EndResource",
+                        kind, name, definition
+                    ),
+                    None => definition,
+                };
+                self.resources.push(name.to_string());
+                self.page_resources.push((name.to_string(), definition));
+                Ok(())
+            }
+        }
+    }
+
+    // Inserts any per-page setup into the page setup section. Resources the page uses are defined
+    // within the page itself so it can be extracted or reordered by a spooler, and are discarded
+    // once the page has been shown.
+    fn wrap_page(&self, page: Vec<u8>) -> Vec<u8> {
+        let mut setup = String::new();
+        let mut cleanup = String::new();
+        if self.error_handler && matches!(self.doc_type, DocumentType::PS) {
+            setup.push_str(&format!("/pslib_page {} def\n", self.page_count));
+        }
+        let bookmarks: Vec<&String> = self
+            .bookmarks
+            .iter()
+            .filter(|(_, page)| *page == self.page_count)
+            .map(|(title, _)| title)
+            .collect();
+        if !bookmarks.is_empty() && matches!(self.doc_type, DocumentType::PS) {
+            setup.push_str(
+                "/pdfmark where { pop } { userdict /pdfmark /cleartomark load put } ifelse\n",
+            );
+            for title in bookmarks {
+                setup.push_str(&format!(
+                    "[ /Title {} /OUT pdfmark\n",
+                    encoding::pdf_text(title)
+                ));
+            }
+        }
+        // Resources used by the page's resources (eg: the fonts in a form) are included as well.
+        let mut used = vec![false; self.page_resources.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (i, (name, _)) in self.page_resources.iter().enumerate() {
+                if used[i] {
+                    continue;
+                }
+                let name = name.as_bytes();
+                used[i] = encoding::contains_token(&page, name)
+                    || self
+                        .page_resources
+                        .iter()
+                        .zip(&used)
+                        .any(|((_, definition), used)| {
+                            *used && encoding::contains_token(definition.as_bytes(), name)
+                        });
+                changed |= used[i];
+            }
+        }
+        let mut resources = String::new();
+        for ((_, definition), used) in self.page_resources.iter().zip(&used) {
+            if *used {
+                resources.push_str(definition);
+                resources.push('\n');
+            }
+        }
+        if !resources.is_empty() {
+            setup.push_str("/pslib_resourcesave save def\n");
+            setup.push_str(&resources);
+            cleanup.push_str("pslib_resourcesave restore\n");
+        }
+        if setup.is_empty() {
+            return page;
+        }
+        let mut start = 0;
+        while page[start..].starts_with(b"%%Page") {
+            start += page[start..]
+                .iter()
+                .position(|b| *b == b'\n')
+                .map_or(page.len() - start, |i| i + 1);
+        }
+        // The setup goes in the page's setup section, which is added when the page has none.
+        if matches!(self.doc_type, DocumentType::PS) {
+            if page[start..].starts_with(b"%%BeginPageSetup\n") {
+                start += "%%BeginPageSetup\n".len();
+            } else {
+                setup = format!("%%BeginPageSetup\n{}%%EndPageSetup\n", setup);
+            }
+        }
+        let mut result = Vec::with_capacity(page.len() + setup.len() + cleanup.len());
+        result.extend_from_slice(&page[..start]);
+        result.extend_from_slice(setup.as_bytes());
+        result.extend_from_slice(&page[start..]);
+        result.extend_from_slice(cleanup.as_bytes());
+        result
+    }
+
+    fn is_plain_output(&self) -> bool {
+        !self.binary_tokens
+            && !self.seven_bit
+            && matches!(self.line_ending, LineEnding::LF)
+            && self.max_line_length == usize::MAX
+    }
+
+    fn write_output(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        if let Some(held) = &mut self.held {
+            held.extend_from_slice(bytes);
+            return Ok(());
+        }
+        if self.is_plain_output() {
+            self.bytes_written += bytes.len();
+            if let Some(digest) = &mut self.digest {
+                digest.update(bytes);
+            }
+            return self.buffer.write_all(bytes);
+        }
+        // Output is transformed a line at a time, so strings and comments are never split across
+        // transforms. A trailing line that isn't finished waits for the next write.
+        self.unfinished.extend_from_slice(bytes);
+        let complete = encoding::complete_lines(&self.unfinished);
+        if complete == 0 {
+            return Ok(());
+        }
+        let lines: Vec<u8> = self.unfinished.drain(..complete).collect();
+        self.transform_output(&lines)
+    }
+
+    fn transform_output(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        let mut output = bytes.to_vec();
+        if self.seven_bit {
+            output = encoding::seven_bit(&output);
+        }
+        if self.max_line_length < usize::MAX {
+            output = encoding::wrap_lines(&output, self.max_line_length);
+        }
+        if !matches!(self.line_ending, LineEnding::LF) {
+            output = encoding::line_endings(&output, &self.line_ending);
+        }
+        // Binary tokens are never written to 7-bit clean documents.
+        if self.binary_tokens && !self.seven_bit {
+            output = encoding::binary_tokens(&output);
+        }
+        self.bytes_written += output.len();
+        if let Some(digest) = &mut self.digest {
+            digest.update(&output);
+        }
+        self.buffer.write_all(&output)
+    }
+
+    pub fn set_flag(&mut self, name: &str, enabled: bool) {
+        if enabled {
+            self.flags.insert(name.to_string());
+        } else {
+            self.flags.remove(name);
+        }
+    }
+
+    pub fn clear_flags(&mut self) {
+        self.flags.clear();
+    }
+
+    // The digest covers every byte written before the digest comment itself, which is placed in
+    // the trailer.
+    pub fn close(mut self) -> Result<Report, Error> {
+        self.write_subset_fonts()?;
+        self.write_output("%%Trailer\n".as_bytes())?;
+        if let DocumentType::PS = self.doc_type {
+            self.write_output(format!("%%Pages: {}\n", self.page_count).as_bytes())?;
+        }
+        let digest = self.digest.take().map(|digest| digest.finish());
+        if let Some(digest) = &digest {
+            self.write_output(format!("%%PslibDigest: SHA-256 {}\n", digest).as_bytes())?;
+        }
+        self.write_output("%%EOF".as_bytes())?;
+        let unfinished = std::mem::take(&mut self.unfinished);
+        self.transform_output(&unfinished)?;
         self.buffer.flush()?;
+        // Each bookmark covers its pages up to the next bookmark on a later page.
+        let mut bookmarks = Vec::new();
+        for (title, first) in &self.bookmarks {
+            if *first > self.page_count {
+                continue;
+            }
+            let last = self
+                .bookmarks
+                .iter()
+                .map(|(_, page)| *page)
+                .find(|page| page > first)
+                .map_or(self.page_count, |page| (page - 1).min(self.page_count));
+            bookmarks.push((title.clone(), *first, last));
+        }
+        Ok(Report {
+            page_count: self.page_count,
+            bookmarks,
+            warnings: self.warnings,
+            resources: self.resources,
+            bytes_written: self.bytes_written,
+            digest,
+        })
+    }
+
+    // Defines the subsets of the fonts with the glyphs used anywhere in the held output (the
+    // prolog resources as well as the pages) in the setup section, between the prolog and the
+    // first page.
+    fn write_subset_fonts(&mut self) -> Result<(), Error> {
+        let Some(held) = self.held.take() else {
+            return Ok(());
+        };
+        let used = encoding::used_glyphs(&held);
+        let (offset, setup) = std::mem::take(&mut self.held_setup);
+        self.write_output(&held[..offset])?;
+        let fonts = std::mem::take(&mut self.subset_fonts);
+        if !fonts.is_empty() || !setup.is_empty() {
+            self.write_output("%%BeginSetup\n".as_bytes())?;
+            for font in fonts {
+                let subset = font.subset(&used)?;
+                self.define_resource(subset.name(), subset.definition().as_bytes())?;
+            }
+            self.write_output(setup.as_bytes())?;
+            self.write_output("%%EndSetup\n".as_bytes())?;
+        }
+        self.write_output(&held[offset..])
+    }
+
+    pub fn load_forms(&mut self, registry: FormRegistry) -> Result<(), Error> {
+        for form in registry.list_forms() {
+            let definition = form.definition();
+            self.check_cid_text(definition.as_bytes())?;
+            for warning in form.warnings() {
+                self.warnings
+                    .push(format!("Form {}: {}", form.name(), warning));
+            }
+            self.add_resource(form.name(), Some("form"), definition)?;
+        }
+        Ok(())
+    }
+
+    // CIDFontType 2 fonts require LanguageLevel 3.
+    fn register_font(&mut self, font: &TrueTypeFont) {
+        if !font.is_cid_keyed() {
+            return;
+        }
+        self.cid_fonts.insert(font.name().to_string());
+        if !matches!(self.language_level, LanguageLevel::Level3) {
+            self.warnings.push(format!(
+                "The CID-keyed font {} requires LanguageLevel 3.",
+                font.name()
+            ));
+        }
+    }
+
+    // Rejects code that shows text in a CID-keyed font without its glyph indexes, which the font
+    // would read two bytes at a time as the wrong glyphs.
+    fn check_cid_text(&self, code: &[u8]) -> Result<(), Error> {
+        match encoding::single_byte_text(code, &self.cid_fonts) {
+            Some(font) => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Text in the CID-keyed font {} must be given the font's metrics.",
+                    font
+                ),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    // Subset fonts are always defined in the setup when the document is closed, regardless of the
+    // resource policy.
+    pub fn load_fonts(&mut self, registry: FontRegistry) -> Result<(), Error> {
+        for font in registry.list_fonts() {
+            self.register_font(font);
+        }
+        if self.held.is_some() {
+            self.subset_fonts.extend(registry.into_fonts());
+            return Ok(());
+        }
+        for font in registry.list_fonts() {
+            self.add_resource(font.name(), None, font.definition())?;
+        }
         Ok(())
     }
 
+    // Each image is defined as a procedure that draws it over the unit square, so an image drawn
+    // several times is only written once.
     pub fn load_images(&mut self, registry: ImageRegistry) -> Result<(), Error> {
         for image in registry.list_images() {
-            todo!("Generate procedures for images");
+            self.check_cancelled()?;
+            self.add_resource(image.procedure_name(), Some("procset"), image.definition())?;
         }
         Ok(())
     }
 }
 
+// Replaces the default error handler with one that prints a diagnostic page describing the error,
+// the offending command, the page being rendered, and the operand stack (top first).
+const ERROR_HANDLER: &str = r#"%%BeginProcSet: pslib_errorhandler
+/pslib_page 0 def
+errordict /handleerror {
+    $error begin
+    initgraphics
+    /Courier findfont 10 scalefont setfont
+    72 720 moveto (ERROR: ) show errorname 128 string cvs show
+    72 706 moveto (OFFENDING COMMAND: ) show /command load 128 string cvs show
+    72 692 moveto (PAGE: ) show pslib_page 128 string cvs show
+    72 678 moveto (STACK:) show
+    userdict /pslib_y 664 put
+    currentdict /ostack known {
+        ostack aload length {
+            72 pslib_y moveto 128 string cvs show
+            userdict /pslib_y pslib_y 14 sub put
+        } repeat
+    } if
+    showpage
+    end
+} put
+%%EndProcSet"#;
+
 pub struct DocumentBuilder<W: Write> {
     doc_type: DocumentType,
     buffer: Option<BufWriter<W>>,
     width: i32,
     height: i32,
     registry: ProcedureRegistry,
+    forms: FormRegistry,
+    fonts: FontRegistry,
     images: ImageRegistry,
+    binary_tokens: bool,
+    seven_bit: bool,
+    line_ending: LineEnding,
+    max_line_length: usize,
+    language_level: Option<LanguageLevel>,
+    resource_policy: ResourcePolicy,
+    chapter_start: ChapterStart,
+    duplex: bool,
+    error_handler: bool,
+    job_name: Option<String>,
+    job_timeout: Option<u32>,
+    wait_timeout: Option<u32>,
+    dpi: Option<f32>,
+    progress: Option<ProgressCallback>,
+    cancel: Option<std::sync::Arc<AtomicBool>>,
+    digest: bool,
+    subset_fonts: bool,
+    transparency: bool,
+    sidecars: Vec<String>,
+    custom_colors: Vec<(String, [f32; 4])>,
+    accounting: Option<Accounting>,
 }
 
 impl<W: Write> DocumentBuilder<W> {
@@ -140,7 +878,30 @@ impl<W: Write> DocumentBuilder<W> {
             width: 0,
             height: 0,
             registry: ProcedureRegistry::new(),
+            forms: FormRegistry::new(),
+            fonts: FontRegistry::new(),
             images: ImageRegistry::new(),
+            binary_tokens: false,
+            seven_bit: false,
+            line_ending: LineEnding::LF,
+            max_line_length: usize::MAX,
+            language_level: None,
+            resource_policy: ResourcePolicy::Preload,
+            chapter_start: ChapterStart::AnyPage,
+            duplex: false,
+            error_handler: false,
+            job_name: None,
+            job_timeout: None,
+            wait_timeout: None,
+            dpi: None,
+            progress: None,
+            cancel: None,
+            digest: false,
+            subset_fonts: false,
+            transparency: false,
+            sidecars: Vec::new(),
+            custom_colors: Vec::new(),
+            accounting: None,
         }
     }
 
@@ -160,17 +921,163 @@ impl<W: Write> DocumentBuilder<W> {
         self
     }
 
+    pub fn binary_tokens(mut self, enabled: bool) -> Self {
+        self.binary_tokens = enabled;
+        self
+    }
+
+    pub fn seven_bit(mut self, enabled: bool) -> Self {
+        self.seven_bit = enabled;
+        self
+    }
+
+    pub fn line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    pub fn max_line_length(mut self, length: usize) -> Self {
+        self.max_line_length = length.max(1);
+        self
+    }
+
+    pub fn language_level(mut self, level: LanguageLevel) -> Self {
+        self.language_level = Some(level);
+        self
+    }
+
+    pub fn resource_policy(mut self, policy: ResourcePolicy) -> Self {
+        self.resource_policy = policy;
+        self
+    }
+
+    pub fn chapter_start(mut self, start: ChapterStart) -> Self {
+        self.chapter_start = start;
+        self
+    }
+
+    // Requests two-sided printing from the device.
+    pub fn duplex(mut self, enabled: bool) -> Self {
+        self.duplex = enabled;
+        self
+    }
+
+    pub fn error_handler(mut self, enabled: bool) -> Self {
+        self.error_handler = enabled;
+        self
+    }
+
+    pub fn job_name(mut self, name: &str) -> Self {
+        self.job_name = Some(name.to_string());
+        self
+    }
+
+    pub fn job_timeout(mut self, seconds: u32) -> Self {
+        self.job_timeout = Some(seconds);
+        self
+    }
+
+    pub fn wait_timeout(mut self, seconds: u32) -> Self {
+        self.wait_timeout = Some(seconds);
+        self
+    }
+
+    pub fn dpi(mut self, dpi: f32) -> Self {
+        if dpi > 0.0 {
+            self.dpi = Some(dpi);
+        }
+        self
+    }
+
+    pub fn on_progress<F: FnMut(u32, usize) + 'static>(mut self, callback: F) -> Self {
+        self.progress = Some(Box::new(callback));
+        self
+    }
+
+    pub fn cancel_flag(mut self, flag: std::sync::Arc<AtomicBool>) -> Self {
+        self.cancel = Some(flag);
+        self
+    }
+
+    pub fn digest(mut self, enabled: bool) -> Self {
+        self.digest = enabled;
+        self
+    }
+
+    // Embeds only the glyphs the document uses. Pages are held in memory until the document is
+    // closed, when the fonts are subset and written ahead of them.
+    pub fn subset_fonts(mut self, enabled: bool) -> Self {
+        self.subset_fonts = enabled;
+        self
+    }
+
+    // Uses the SetTransparency pdfmark for translucent fills, for documents that are distilled
+    // to PDF. Otherwise translucent fills are blended with their backdrop color.
+    pub fn transparency(mut self, enabled: bool) -> Self {
+        self.transparency = enabled;
+        self
+    }
+
+    pub fn accounting(mut self, accounting: Accounting) -> Self {
+        self.accounting = Some(accounting);
+        self
+    }
+
+    // Records a file of structured data (eg: invoice XML) that accompanies the document. The
+    // data isn't embedded; its name, media type, length and SHA-256 are written in the header so
+    // an archive can link the two files.
+    pub fn sidecar(mut self, file_name: &str, media_type: &str, data: &[u8]) -> Self {
+        let mut digest = Sha256::new();
+        digest.update(data);
+        self.sidecars.push(format!(
+            "%%PslibSidecar: ({}) ({}) {} SHA-256 {}\n",
+            encoding::escape_string(&encoding::comment_text(file_name)),
+            encoding::escape_string(&encoding::comment_text(media_type)),
+            data.len(),
+            digest.finish()
+        ));
+        self
+    }
+
+    // Declares a spot color used in the document, eg: the CutContour of a die-line, so
+    // separating software knows to create a plate for it.
+    pub fn custom_color(mut self, name: &str, c: f32, m: f32, y: f32, k: f32) -> Self {
+        let name = encoding::comment_text(name);
+        self.custom_colors.retain(|(existing, _)| *existing != name);
+        self.custom_colors.push((
+            name,
+            [
+                c.clamp(0.0, 1.0),
+                m.clamp(0.0, 1.0),
+                y.clamp(0.0, 1.0),
+                k.clamp(0.0, 1.0),
+            ],
+        ));
+        self
+    }
+
     pub fn load_procedures(mut self, registry: ProcedureRegistry) -> Self {
         self.registry = registry;
         self
     }
 
+    pub fn load_forms(mut self, registry: FormRegistry) -> Self {
+        self.forms = registry;
+        self
+    }
+
+    pub fn load_fonts(mut self, registry: FontRegistry) -> Self {
+        self.fonts = registry;
+        self
+    }
+
     pub fn load_images(mut self, registry: ImageRegistry) -> Self {
         self.images = registry;
         self
     }
 
     pub fn build(self) -> Document<W> {
+        let setup = self.setup();
         let mut doc = Document {
             doc_type: self.doc_type,
             buffer: Option::expect(
@@ -178,54 +1085,220 @@ impl<W: Write> DocumentBuilder<W> {
                 "Write buffer must be set before calling build.",
             ),
             page_count: 0,
+            flags: HashSet::new(),
+            binary_tokens: self.binary_tokens,
+            seven_bit: self.seven_bit,
+            line_ending: self.line_ending,
+            max_line_length: self.max_line_length,
+            language_level: LanguageLevel::Level2,
+            vm_usage: 0,
+            warnings: Vec::new(),
+            resources: Vec::new(),
+            resource_policy: self.resource_policy,
+            chapter_start: self.chapter_start,
+            duplex: self.duplex,
+            page_resources: Vec::new(),
+            error_handler: self.error_handler,
+            bytes_written: 0,
+            progress: self.progress,
+            cancel: self.cancel,
+            digest: if self.digest {
+                Some(Sha256::new())
+            } else {
+                None
+            },
+            subset_fonts: Vec::new(),
+            held: None,
+            held_setup: (0, String::new()),
+            unfinished: Vec::new(),
+            cid_fonts: HashSet::new(),
+            bookmarks: Vec::new(),
         };
+        let mut comments = String::new();
+        if let Some(name) = &self.job_name {
+            comments.push_str(&format!("%%Title: {}\n", encoding::comment_text(name)));
+        }
+        if let Some(level) = self.language_level {
+            comments.push_str(match level {
+                LanguageLevel::Level1 => "%%LanguageLevel: 1\n",
+                LanguageLevel::Level2 => "%%LanguageLevel: 2\n",
+                LanguageLevel::Level3 => "%%LanguageLevel: 3\n",
+            });
+            doc.language_level = level;
+        }
+        if let Some(accounting) = &self.accounting {
+            comments.push_str(&accounting.comments());
+        }
+        for sidecar in &self.sidecars {
+            comments.push_str(sidecar);
+        }
+        if !self.custom_colors.is_empty() {
+            comments.push_str("%%DocumentCustomColors:");
+            for (name, _) in &self.custom_colors {
+                comments.push_str(&format!(" ({})", encoding::escape_string(name)));
+            }
+            comments.push('\n');
+            for (name, [c, m, y, k]) in &self.custom_colors {
+                comments.push_str(&format!(
+                    "%%CMYKCustomColor: {} {} {} {} ({})\n",
+                    c,
+                    m,
+                    y,
+                    k,
+                    encoding::escape_string(name)
+                ));
+            }
+        }
+        // Binary tokens need LanguageLevel 2.
+        if self.binary_tokens && matches!(doc.language_level, LanguageLevel::Level1) {
+            doc.binary_tokens = false;
+            doc.warnings
+                .push("Binary tokens are not written at LanguageLevel 1.".to_string());
+        }
+        if self.seven_bit {
+            comments.push_str("%%DocumentData: Clean7Bit\n");
+        } else if doc.binary_tokens {
+            comments.push_str("%%DocumentData: Binary\n");
+        }
         match doc.doc_type {
             DocumentType::PS => {
-                doc.buffer
-                    .write_all(
-                        format!(
-                            r#"%!PS-Adobe-3.0
+                doc.write_output(
+                    format!(
+                        r#"%!PS-Adobe-3.0
 %%Creator: pslib {}
 %%CreationDate: {}
 %%Pages: (atend)
-%%EndComments
+{}%%EndComments
 "#,
-                            env!("CARGO_PKG_VERSION"),
-                            Utc::now().to_rfc3339()
-                        )
-                        .as_bytes(),
+                        env!("CARGO_PKG_VERSION"),
+                        Utc::now().to_rfc3339(),
+                        comments
                     )
-                    .unwrap();
+                    .as_bytes(),
+                )
+                .unwrap();
             }
             DocumentType::EPS => {
-                doc.buffer
-                    .write_all(
-                        format!(
-                            r#"%!PS-Adobe-3.0 EPSF-3.0
+                doc.write_output(
+                    format!(
+                        r#"%!PS-Adobe-3.0 EPSF-3.0
 %%BoundingBox: 0 0 {} {}
 %%Creator: pslib {}
 %%CreationDate: {}
-%%EndComments
+{}%%EndComments
 "#,
-                            self.width,
-                            self.height,
-                            env!("CARGO_PKG_VERSION"),
-                            Utc::now().to_rfc3339()
-                        )
-                        .as_bytes(),
+                        self.width,
+                        self.height,
+                        env!("CARGO_PKG_VERSION"),
+                        Utc::now().to_rfc3339(),
+                        comments
                     )
-                    .unwrap();
+                    .as_bytes(),
+                )
+                .unwrap();
             }
         }
+        // With font subsetting everything after the header comments is held until the document
+        // is closed, so the text in forms is counted as well as the text on pages.
+        if self.subset_fonts {
+            doc.held = Some(Vec::new());
+        }
+        if self.error_handler {
+            doc.define_resource("pslib_errorhandler", ERROR_HANDLER.as_bytes())
+                .unwrap();
+        }
         for procedure in self.registry.list_procedures() {
-            doc.buffer.write_all(procedure.body.as_bytes()).unwrap();
-            doc.buffer.write_all("\n".as_bytes()).unwrap();
+            doc.define_resource(&procedure.name, procedure.body.as_bytes())
+                .unwrap();
+        }
+        if !self.subset_fonts {
+            for font in self.fonts.list_fonts() {
+                doc.add_resource(font.name(), None, font.definition())
+                    .unwrap();
+            }
+        }
+        for font in self.fonts.list_fonts() {
+            doc.register_font(font);
+        }
+        // The builder can't fail, so text in a CID-keyed font without its glyph indexes is
+        // reported as a warning instead.
+        for form in self.forms.list_forms() {
+            let definition = form.definition();
+            if let Err(error) = doc.check_cid_text(definition.as_bytes()) {
+                doc.warnings.push(error.to_string());
+            }
+            for warning in form.warnings() {
+                doc.warnings
+                    .push(format!("Form {}: {}", form.name(), warning));
+            }
+            doc.add_resource(form.name(), Some("form"), definition)
+                .unwrap();
+        }
+        for image in self.images.list_images() {
+            doc.add_resource(image.procedure_name(), Some("procset"), image.definition())
+                .unwrap();
         }
-        for images in self.images.list_images() {
-            todo!("Generate image procedures");
+        if let Some(held) = &doc.held {
+            doc.held_setup = (held.len(), setup);
+            doc.subset_fonts = self.fonts.into_fonts();
+        } else if !setup.is_empty() {
+            doc.write_output(format!("%%BeginSetup\n{}%%EndSetup\n", setup).as_bytes())
+                .unwrap();
         }
         doc
     }
+
+    // Job parameters are set with setuserparams on LanguageLevel 2 devices and through statusdict
+    // on LanguageLevel 1 devices. Devices that reject a value keep their defaults rather than
+    // failing the job.
+    fn job_setup(&self) -> String {
+        let mut level2 = String::new();
+        let mut level1 = String::new();
+        if let Some(name) = &self.job_name {
+            let name = encoding::escape_string(name);
+            level2.push_str(&format!("/JobName ({}) ", name));
+            level1.push_str(&format!("statusdict /jobname ({}) put ", name));
+        }
+        if let Some(timeout) = self.job_timeout {
+            level2.push_str(&format!("/JobTimeout {} ", timeout));
+            level1.push_str(&format!("statusdict begin {} setjobtimeout end ", timeout));
+        }
+        if let Some(timeout) = self.wait_timeout {
+            level2.push_str(&format!("/WaitTimeout {} ", timeout));
+            level1.push_str(&format!("statusdict /waittimeout {} put ", timeout));
+        }
+        if level2.is_empty() {
+            return level2;
+        }
+        format!(
+            "mark {{ /languagelevel where {{ pop languagelevel 2 ge }} {{ false }} ifelse {{ << {}>> setuserparams }} {{ {}}} ifelse }} stopped cleartomark\n",
+            level2, level1
+        )
+    }
+
+    // The user space scale is reapplied at the start of every page by the BeginPage procedure.
+    // EPS files can't use setpagedevice so the scale is applied once.
+    fn setup(&self) -> String {
+        let mut setup = self.job_setup();
+        if self.duplex && matches!(self.doc_type, DocumentType::PS) {
+            setup.push_str("<< /Duplex true >> setpagedevice\n");
+        }
+        if self.transparency {
+            setup.push_str("/pdfmark where { pop } { userdict /pdfmark /cleartomark load put } ifelse\n/pslib_transparency true def\n");
+        }
+        // Only page content is scaled, page sizes and bounding boxes remain in points
+        if let Some(dpi) = self.dpi {
+            let scale = 72.0 / dpi;
+            match self.doc_type {
+                DocumentType::PS => setup.push_str(&format!(
+                    "<< /BeginPage {{ pop {} {} scale }} >> setpagedevice\n",
+                    scale, scale
+                )),
+                DocumentType::EPS => setup.push_str(&format!("{} {} scale\n", scale, scale)),
+            }
+        }
+        setup
+    }
 }
 
 pub struct Procedure {
@@ -237,6 +1310,12 @@ pub struct ProcedureRegistry {
     procedures: HashMap<String, Procedure>,
 }
 
+impl Default for ProcedureRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ProcedureRegistry {
     pub fn new() -> Self {
         ProcedureRegistry {
@@ -262,37 +1341,142 @@ impl ProcedureRegistry {
         registry.add_procedure(Procedure {
             name: "rect".to_string(),
             body: r#"/rect { newpath moveto rlineto rlineto rlineto rlineto closepath } def"#
+                .to_string(),
+        });
+
+        registry.add_procedure(Procedure {
+            name: "rrect".to_string(),
+            body: r#"/rrect { 5 dict begin /y exch def /x exch def /h exch def /w exch def /r exch def newpath x r add y moveto x w add y x w add y h add r arcto 4 { pop } repeat x w add y h add x y h add r arcto 4 { pop } repeat x y h add x y r arcto 4 { pop } repeat x y x w add y r arcto 4 { pop } repeat closepath end } def"#
             .to_string(),
         });
 
         registry.add_procedure(Procedure {
             name: "line".to_string(),
-            body: r#"/line { newpath moveto rlineto closepath } def"#
+            body: r#"/line { newpath moveto rlineto closepath } def"#.to_string(),
+        });
+
+        registry.add_procedure(Procedure {
+            name: "poly".to_string(),
+            body: r#"/poly { newpath moveto { lineto } repeat closepath } def"#.to_string(),
+        });
+
+        registry.add_procedure(Procedure {
+            name: "pline".to_string(),
+            body: r#"/pline { newpath moveto { lineto } repeat } def"#.to_string(),
+        });
+
+        // Quadratic curves are raised to cubic curves from the current point.
+        registry.add_procedure(Procedure {
+            name: "quadto".to_string(),
+            body: r#"/quadto { 6 dict begin /y exch def /x exch def /y1 exch def /x1 exch def currentpoint /y0 exch def /x0 exch def x0 x1 x0 sub 2 mul 3 div add y0 y1 y0 sub 2 mul 3 div add x x1 x sub 2 mul 3 div add y y1 y sub 2 mul 3 div add x y curveto end } def"#
             .to_string(),
         });
 
         registry.add_procedure(Procedure {
-            name: "fill_rgb".to_string(),
-            body: r#"/fillrgb { gsave setrgbcolor fill grestore } def"#
+            name: "arcline".to_string(),
+            body: r#"/arcline { newpath arc } def"#.to_string(),
+        });
+
+        registry.add_procedure(Procedure {
+            name: "sector".to_string(),
+            body: r#"/sector { newpath 5 copy pop pop pop moveto arc closepath } def"#.to_string(),
+        });
+
+        // Strokes parallel lines at the given angle across the bounding box, clipped to the current path.
+        registry.add_procedure(Procedure {
+            name: "hatch".to_string(),
+            body: r#"/hatch { 8 dict begin /lw exch def /a exch def /s exch def /h exch def /w exch def /y exch def /x exch def gsave clip x w 2 div add y h 2 div add translate a rotate /d w h add def newpath d neg s d { dup d neg exch moveto d exch lineto } for lw setlinewidth stroke grestore end } def"#
             .to_string(),
         });
 
+        registry.add_procedure(Procedure {
+            name: "ellipse".to_string(),
+            body: r#"/ellipse { newpath matrix currentmatrix 5 1 roll 4 2 roll translate scale 0 0 1 0 360 arc closepath setmatrix } def"#
+            .to_string(),
+        });
+
+        // The inner circle is drawn counterclockwise so it is left unfilled by the nonzero rule.
+        registry.add_procedure(Procedure {
+            name: "ring".to_string(),
+            body: r#"/ring { newpath 4 copy pop 0 360 arc closepath exch pop 3 copy 3 -1 roll add exch moveto 360 0 arcn closepath } def"#
+            .to_string(),
+        });
+
+        // Marker symbols take the center point and the overall size of the symbol.
+        registry.add_procedure(Procedure {
+            name: "mcircle".to_string(),
+            body: r#"/mcircle { newpath 2 div 0 360 arc closepath } def"#.to_string(),
+        });
+
+        registry.add_procedure(Procedure {
+            name: "msquare".to_string(),
+            body: r#"/msquare { newpath 3 1 roll moveto dup 2 div neg dup rmoveto dup 0 rlineto dup 0 exch rlineto neg 0 rlineto closepath } def"#
+            .to_string(),
+        });
+
+        registry.add_procedure(Procedure {
+            name: "mcross".to_string(),
+            body: r#"/mcross { newpath 3 1 roll moveto 2 div dup neg dup rmoveto dup 2 mul dup rlineto dup -2 mul 0 rmoveto 2 mul dup neg rlineto } def"#
+            .to_string(),
+        });
+
+        registry.add_procedure(Procedure {
+            name: "mplus".to_string(),
+            body: r#"/mplus { newpath 3 1 roll moveto 2 div dup neg 0 rmoveto dup 2 mul 0 rlineto dup neg dup rmoveto 2 mul 0 exch rlineto } def"#
+            .to_string(),
+        });
+
+        registry.add_procedure(Procedure {
+            name: "mtriangle".to_string(),
+            body: r#"/mtriangle { newpath 3 1 roll moveto 2 div dup neg dup rmoveto dup 2 mul 0 rlineto dup neg exch 2 mul rlineto closepath } def"#
+            .to_string(),
+        });
+
+        registry.add_procedure(Procedure {
+            name: "mdiamond".to_string(),
+            body: r#"/mdiamond { newpath 3 1 roll moveto 2 div dup neg 0 rmoveto dup dup rlineto dup dup neg rlineto dup neg dup rlineto neg dup neg rlineto closepath } def"#
+            .to_string(),
+        });
+
+        registry.add_procedure(Procedure {
+            name: "fill_rgb".to_string(),
+            body: r#"/fillrgb { gsave setrgbcolor fill grestore } def"#.to_string(),
+        });
+
         registry.add_procedure(Procedure {
             name: "fill_cmyk".to_string(),
-            body: r#"/fillcmyk { gsave setcmykcolor fill grestore } def"#
+            body: r#"/fillcmyk { gsave setcmykcolor fill grestore } def"#.to_string(),
+        });
+
+        registry.add_procedure(Procedure {
+            name: "eofill_rgb".to_string(),
+            body: r#"/eofillrgb { gsave setrgbcolor eofill grestore } def"#.to_string(),
+        });
+
+        registry.add_procedure(Procedure {
+            name: "eofill_cmyk".to_string(),
+            body: r#"/eofillcmyk { gsave setcmykcolor eofill grestore } def"#.to_string(),
+        });
+
+        // Takes the name for the copy and the name of the font, and leaves the copy of the font
+        // re-encoded to ISOLatin1Encoding. Type42 fonts are embedded with a Latin-1 encoding and
+        // are used as they are.
+        registry.add_procedure(Procedure {
+            name: "latin1font".to_string(),
+            body: r#"/latin1font { 1 index FontDirectory exch known { pop findfont } { findfont dup /FontType get 42 eq { exch pop } { dup length dict begin { 1 index /FID ne { def } { pop pop } ifelse } forall /Encoding ISOLatin1Encoding def currentdict end definefont } ifelse } ifelse } def"#
             .to_string(),
         });
 
         registry.add_procedure(Procedure {
             name: "stroke_rgb".to_string(),
             body: r#"/strokergb { gsave setlinewidth setrgbcolor stroke grestore } def"#
-            .to_string(),
+                .to_string(),
         });
 
         registry.add_procedure(Procedure {
             name: "stroke_cmyk".to_string(),
             body: r#"/strokecmyk { gsave setlinewidth setcmykcolor stroke grestore } def"#
-            .to_string(),
+                .to_string(),
         });
 
         registry
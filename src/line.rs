@@ -1,4 +1,7 @@
-use crate::{ColorMode, Serialize, TransformLineOrigin};
+use crate::{
+    style::{write_skew, write_stroke_state},
+    ColorMode, GroupItem, LineCap, Serialize, Transform, TransformLineOrigin,
+};
 use std::fmt::Write;
 
 pub struct Line {
@@ -8,10 +11,14 @@ pub struct Line {
     stroke_width: f32,
     stroke_color_rgb: [f32; 3],
     stroke_color_cmyk: [f32; 4],
+    line_cap: Option<LineCap>,
     rotate: f32,
     scale: [f32; 2],
     do_scale: bool,
     do_rotate: bool,
+    flip: [bool; 2],
+    skew: Option<(f32, f32)>,
+    matrix: Option<Transform>,
     transform_origin: TransformLineOrigin,
     color_mode: ColorMode,
 }
@@ -25,10 +32,14 @@ impl Line {
             stroke_width: 1.0,
             stroke_color_rgb: [0.0, 0.0, 0.0],
             stroke_color_cmyk: [0.0, 0.0, 0.0, 0.0],
+            line_cap: None,
             rotate: 0.0,
             scale: [1.0, 1.0],
             do_scale: false,
             do_rotate: false,
+            flip: [false, false],
+            skew: None,
+            matrix: None,
             transform_origin: TransformLineOrigin::Center,
             color_mode: ColorMode::RGB,
         }
@@ -53,6 +64,11 @@ impl Line {
         self
     }
 
+    pub fn line_cap(mut self, cap: LineCap) -> Self {
+        self.line_cap = Some(cap);
+        self
+    }
+
     pub fn scale(mut self, x: f32, y: f32) -> Self {
         self.scale[0] = x;
         self.scale[1] = y;
@@ -60,6 +76,26 @@ impl Line {
         self
     }
 
+    pub fn flip_horizontal(mut self) -> Self {
+        self.flip[0] = !self.flip[0];
+        self
+    }
+
+    pub fn flip_vertical(mut self) -> Self {
+        self.flip[1] = !self.flip[1];
+        self
+    }
+
+    pub fn skew(mut self, x: f32, y: f32) -> Self {
+        self.skew = Some((x, y));
+        self
+    }
+
+    pub fn transform(mut self, matrix: Transform) -> Self {
+        self.matrix = Some(matrix);
+        self
+    }
+
     pub fn set_orign(mut self, origin: TransformLineOrigin) -> Self {
         self.transform_origin = origin;
         self
@@ -80,8 +116,17 @@ impl Serialize for Line {
             return result;
         }
 
-        if self.do_rotate || self.do_scale {
+        let transform = self.do_rotate
+            || self.do_scale
+            || self.flip[0]
+            || self.flip[1]
+            || self.skew.is_some()
+            || self.matrix.is_some();
+        if transform {
             result.push_str("gsave ");
+            if let Some(matrix) = &self.matrix {
+                write!(&mut result, "{} concat ", matrix).unwrap();
+            }
             let origin = match self.transform_origin {
                 TransformLineOrigin::Left => (self.x, self.y + (self.stroke_width / 2.0)),
                 TransformLineOrigin::Center => (
@@ -98,21 +143,34 @@ impl Serialize for Line {
                 write!(&mut result, "{} rotate ", self.rotate).unwrap();
             }
 
-            if self.do_scale {
-                write!(&mut result, "{} {} scale ", self.scale[0], self.scale[1]).unwrap();
+            if let Some((x, y)) = self.skew {
+                write_skew(&mut result, x, y);
+            }
+
+            if self.do_scale || self.flip[0] || self.flip[1] {
+                let x = if self.flip[0] {
+                    -self.scale[0]
+                } else {
+                    self.scale[0]
+                };
+                let y = if self.flip[1] {
+                    -self.scale[1]
+                } else {
+                    self.scale[1]
+                };
+                write!(&mut result, "{} {} scale ", x, y).unwrap();
             }
 
             write!(&mut result, "-{} -{} translate ", origin.0, origin.1).unwrap();
         }
 
-        write!(
-            &mut result,
-            "{} 0 {} {} line ",
-            self.length, self.x, self.y,
-        )
-        .unwrap();
+        write!(&mut result, "{} 0 {} {} line ", self.length, self.x, self.y,).unwrap();
 
         if self.stroke_width > 0.0 {
+            if self.line_cap.is_some() {
+                result.push_str("gsave ");
+                write_stroke_state(&mut result, self.line_cap.as_ref(), None, None);
+            }
             match self.color_mode {
                 ColorMode::RGB => {
                     write!(
@@ -138,12 +196,17 @@ impl Serialize for Line {
                     .unwrap();
                 }
             }
+            if self.line_cap.is_some() {
+                result.push_str("grestore ");
+            }
         }
 
-        if self.do_rotate || self.do_scale {
+        if transform {
             result.push_str("grestore ");
         }
 
         result
     }
 }
+
+impl GroupItem for Line {}
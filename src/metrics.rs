@@ -0,0 +1,955 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+use crate::{
+    encoding::{base_letter, glyph_name},
+    font::is_standard_latin,
+    Font, TrueTypeFont,
+};
+
+// Advance widths (1/1000 em) from the Adobe AFM files for character codes 32 to 126 in
+// StandardEncoding, so codes 39 and 96 are quoteright and quoteleft, followed by the widths of
+// the rest of the standard character set in the order of `EXTRA_CHARACTERS`. Oblique styles share
+// the widths of the upright style, and accented letters have the width of their base letter.
+#[rustfmt::skip]
+const HELVETICA: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 222, 333, 333, 389, 584, 278, 333, 278, 278,
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556,
+    1015, 667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778,
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469, 556,
+    222, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556,
+    556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+#[rustfmt::skip]
+const HELVETICA_BOLD: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 278, 333, 333, 389, 584, 278, 333, 278, 278,
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611,
+    975, 722, 722, 722, 722, 667, 611, 778, 722, 278, 556, 722, 611, 833, 722, 778,
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 333, 278, 333, 584, 556,
+    278, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556, 278, 889, 611, 611,
+    611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+];
+
+#[rustfmt::skip]
+const TIMES_ROMAN: [u16; 95] = [
+    250, 333, 408, 500, 500, 833, 778, 333, 333, 333, 500, 564, 250, 333, 250, 278,
+    500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 278, 278, 564, 564, 564, 444,
+    921, 722, 667, 667, 722, 611, 556, 722, 722, 333, 389, 722, 611, 889, 722, 722,
+    556, 722, 667, 556, 611, 722, 722, 944, 722, 722, 611, 333, 278, 333, 469, 500,
+    333, 444, 500, 444, 500, 444, 333, 500, 500, 278, 278, 500, 278, 778, 500, 500,
+    500, 500, 333, 389, 278, 500, 500, 722, 500, 500, 444, 480, 200, 480, 541,
+];
+
+#[rustfmt::skip]
+const TIMES_BOLD: [u16; 95] = [
+    250, 333, 555, 500, 500, 1000, 833, 333, 333, 333, 500, 570, 250, 333, 250, 278,
+    500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 333, 333, 570, 570, 570, 500,
+    930, 722, 667, 722, 722, 667, 611, 778, 778, 389, 500, 778, 667, 944, 722, 778,
+    611, 778, 722, 556, 667, 722, 722, 1000, 722, 722, 667, 333, 278, 333, 581, 500,
+    333, 500, 556, 444, 556, 444, 333, 500, 556, 278, 333, 556, 278, 833, 556, 500,
+    556, 556, 444, 389, 333, 556, 500, 722, 500, 500, 444, 394, 220, 394, 520,
+];
+
+#[rustfmt::skip]
+const TIMES_ITALIC: [u16; 95] = [
+    250, 333, 420, 500, 500, 833, 778, 333, 333, 333, 500, 675, 250, 333, 250, 278,
+    500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 333, 333, 675, 675, 675, 500,
+    920, 611, 611, 667, 722, 611, 611, 722, 722, 333, 444, 667, 556, 833, 667, 722,
+    611, 722, 611, 500, 556, 722, 611, 833, 611, 556, 556, 389, 278, 389, 422, 500,
+    333, 500, 500, 444, 500, 444, 278, 500, 500, 278, 278, 444, 278, 722, 500, 500,
+    500, 500, 389, 389, 278, 500, 444, 667, 444, 444, 389, 400, 275, 400, 541,
+];
+
+#[rustfmt::skip]
+const TIMES_BOLD_ITALIC: [u16; 95] = [
+    250, 389, 555, 500, 500, 833, 778, 333, 333, 333, 500, 570, 250, 333, 250, 278,
+    500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 333, 333, 570, 570, 570, 500,
+    832, 667, 667, 667, 722, 667, 667, 722, 778, 389, 500, 667, 611, 889, 722, 722,
+    611, 722, 667, 556, 611, 722, 667, 889, 667, 611, 611, 333, 278, 333, 570, 500,
+    333, 500, 500, 444, 500, 444, 333, 500, 556, 278, 278, 500, 278, 778, 556, 500,
+    500, 500, 389, 389, 278, 556, 444, 667, 500, 444, 389, 348, 220, 348, 570,
+];
+
+#[rustfmt::skip]
+const EXTRA_CHARACTERS: [char; 71] = [
+    '¡', '¢', '£', '¤', '¥', '¦', '§', '¨', '©', 'ª', '«', '¬',
+    '\u{00AD}', '®', '¯', '°', '±', '²', '³', '´', 'µ', '¶', '·', '¸',
+    '¹', 'º', '»', '¼', '½', '¾', '¿', 'Æ', 'Ð', '×', 'Ø', 'Þ',
+    'ß', 'æ', 'ð', '÷', 'ø', 'þ', 'ı', 'Ł', 'ł', 'Œ', 'œ', 'ƒ',
+    'ˆ', 'ˇ', '˜', '–', '—', '‘', '’', '‚', '“', '”', '„', '†',
+    '‡', '•', '…', '‰', '‹', '›', '⁄', '™', '−', 'ﬁ', 'ﬂ',
+];
+
+#[rustfmt::skip]
+const HELVETICA_EXTRA: [u16; 71] = [
+    333, 556, 556, 556, 556, 260, 556, 333, 737, 370, 556, 584, 333, 737, 333, 400,
+    584, 333, 333, 333, 556, 537, 278, 333, 333, 365, 556, 834, 834, 834, 611, 1000,
+    722, 584, 778, 667, 611, 889, 556, 584, 611, 556, 278, 556, 222, 1000, 944, 556,
+    333, 333, 333, 556, 1000, 222, 222, 222, 333, 333, 333, 556, 556, 350, 1000, 1000,
+    333, 333, 167, 1000, 584, 500, 500,
+];
+
+#[rustfmt::skip]
+const HELVETICA_BOLD_EXTRA: [u16; 71] = [
+    333, 556, 556, 556, 556, 280, 556, 333, 737, 370, 556, 584, 333, 737, 333, 400,
+    584, 333, 333, 333, 611, 556, 278, 333, 333, 365, 556, 834, 834, 834, 611, 1000,
+    722, 584, 778, 667, 611, 889, 611, 584, 611, 611, 278, 611, 278, 1000, 944, 556,
+    333, 333, 333, 556, 1000, 278, 278, 278, 500, 500, 500, 556, 556, 350, 1000, 1000,
+    333, 333, 167, 1000, 584, 611, 611,
+];
+
+#[rustfmt::skip]
+const TIMES_ROMAN_EXTRA: [u16; 71] = [
+    333, 500, 500, 500, 500, 200, 500, 333, 760, 276, 500, 564, 333, 760, 333, 400,
+    564, 300, 300, 333, 500, 453, 250, 333, 300, 310, 500, 750, 750, 750, 444, 889,
+    722, 564, 722, 556, 500, 667, 500, 564, 500, 500, 278, 611, 278, 889, 722, 500,
+    333, 333, 333, 500, 1000, 333, 333, 333, 444, 444, 444, 500, 500, 350, 1000, 1000,
+    333, 333, 167, 980, 564, 556, 556,
+];
+
+#[rustfmt::skip]
+const TIMES_BOLD_EXTRA: [u16; 71] = [
+    333, 500, 500, 500, 500, 220, 500, 333, 747, 300, 500, 570, 333, 747, 333, 400,
+    570, 300, 300, 333, 556, 540, 250, 333, 300, 330, 500, 750, 750, 750, 500, 1000,
+    722, 570, 778, 611, 556, 722, 500, 570, 500, 556, 278, 667, 278, 1000, 722, 500,
+    333, 333, 333, 500, 1000, 333, 333, 333, 500, 500, 500, 500, 500, 350, 1000, 1000,
+    333, 333, 167, 1000, 570, 556, 556,
+];
+
+#[rustfmt::skip]
+const TIMES_ITALIC_EXTRA: [u16; 71] = [
+    389, 500, 500, 500, 500, 275, 500, 333, 760, 276, 500, 675, 333, 760, 333, 400,
+    675, 300, 300, 333, 500, 523, 250, 333, 300, 310, 500, 750, 750, 750, 500, 889,
+    722, 675, 722, 611, 500, 667, 500, 675, 500, 500, 278, 556, 278, 944, 667, 500,
+    333, 333, 333, 500, 889, 333, 333, 333, 556, 556, 556, 500, 500, 350, 889, 1000,
+    333, 333, 167, 980, 675, 500, 500,
+];
+
+#[rustfmt::skip]
+const TIMES_BOLD_ITALIC_EXTRA: [u16; 71] = [
+    389, 500, 500, 500, 500, 220, 500, 333, 747, 266, 500, 606, 333, 747, 333, 400,
+    570, 300, 300, 333, 576, 500, 250, 333, 300, 300, 500, 750, 750, 750, 500, 944,
+    722, 570, 722, 611, 500, 722, 500, 570, 500, 500, 278, 611, 278, 944, 722, 500,
+    333, 333, 333, 500, 1000, 333, 333, 333, 500, 500, 500, 500, 500, 350, 1000, 1000,
+    333, 333, 167, 1000, 606, 556, 556,
+];
+
+#[rustfmt::skip]
+const AVANT_GARDE_BOOK: [u16; 95] = [
+    277, 295, 309, 554, 554, 775, 757, 351, 369, 369, 425, 606, 277, 332, 277, 437,
+    554, 554, 554, 554, 554, 554, 554, 554, 554, 554, 277, 277, 606, 606, 606, 591,
+    867, 740, 574, 813, 744, 536, 485, 872, 683, 226, 482, 591, 462, 919, 740, 869,
+    592, 871, 607, 498, 426, 655, 702, 960, 609, 592, 480, 351, 605, 351, 606, 500,
+    351, 683, 682, 647, 685, 650, 314, 673, 610, 200, 203, 502, 200, 938, 610, 655,
+    682, 682, 301, 388, 339, 608, 554, 831, 480, 536, 425, 351, 672, 351, 606,
+];
+
+#[rustfmt::skip]
+const AVANT_GARDE_BOOK_EXTRA: [u16; 71] = [
+    295, 554, 554, 554, 554, 672, 615, 369, 747, 369, 425, 606, 332, 747, 485, 400,
+    606, 332, 332, 375, 608, 564, 277, 307, 332, 369, 425, 831, 831, 831, 591, 992,
+    790, 606, 868, 592, 555, 1157, 655, 606, 653, 682, 200, 517, 300, 1194, 1137, 554,
+    502, 502, 440, 500, 1000, 351, 351, 354, 502, 484, 502, 553, 553, 606, 1000, 1174,
+    255, 255, 166, 1000, 606, 487, 485,
+];
+
+#[rustfmt::skip]
+const AVANT_GARDE_DEMI: [u16; 95] = [
+    280, 280, 360, 560, 560, 860, 680, 280, 380, 380, 440, 600, 280, 420, 280, 460,
+    560, 560, 560, 560, 560, 560, 560, 560, 560, 560, 280, 280, 600, 600, 600, 560,
+    740, 740, 580, 780, 700, 520, 480, 840, 680, 280, 480, 620, 440, 900, 740, 840,
+    560, 840, 580, 520, 420, 640, 700, 900, 680, 620, 500, 320, 640, 320, 600, 500,
+    280, 660, 660, 640, 660, 640, 280, 660, 600, 240, 260, 580, 240, 940, 600, 640,
+    660, 660, 320, 440, 300, 600, 560, 800, 560, 580, 460, 340, 600, 340, 600,
+];
+
+#[rustfmt::skip]
+const AVANT_GARDE_DEMI_EXTRA: [u16; 71] = [
+    280, 560, 560, 560, 560, 600, 560, 500, 740, 360, 460, 600, 420, 740, 420, 400,
+    600, 336, 336, 420, 576, 600, 280, 340, 336, 360, 460, 840, 840, 840, 560, 900,
+    742, 600, 840, 560, 600, 1080, 640, 600, 660, 660, 240, 480, 320, 1060, 1080, 560,
+    540, 540, 480, 500, 1000, 280, 280, 280, 480, 480, 480, 560, 560, 600, 1000, 1280,
+    240, 240, 160, 1000, 600, 520, 520,
+];
+
+#[rustfmt::skip]
+const BOOKMAN_LIGHT: [u16; 95] = [
+    320, 300, 380, 620, 620, 900, 800, 220, 300, 300, 440, 600, 320, 400, 320, 600,
+    620, 620, 620, 620, 620, 620, 620, 620, 620, 620, 320, 320, 600, 600, 600, 540,
+    820, 680, 740, 740, 800, 720, 640, 800, 800, 340, 600, 720, 600, 920, 740, 800,
+    620, 820, 720, 660, 620, 780, 700, 960, 720, 640, 640, 300, 600, 300, 600, 500,
+    220, 580, 620, 520, 620, 520, 320, 540, 660, 300, 300, 620, 300, 940, 660, 560,
+    620, 580, 440, 520, 380, 680, 520, 780, 560, 540, 480, 280, 600, 280, 600,
+];
+
+#[rustfmt::skip]
+const BOOKMAN_LIGHT_EXTRA: [u16; 71] = [
+    300, 620, 620, 620, 620, 600, 520, 420, 740, 420, 360, 600, 400, 740, 440, 400,
+    600, 372, 372, 340, 680, 600, 320, 320, 372, 420, 380, 930, 930, 930, 540, 1260,
+    800, 600, 800, 620, 660, 860, 560, 600, 560, 620, 300, 600, 320, 1240, 900, 620,
+    420, 420, 440, 500, 1000, 220, 220, 220, 400, 400, 400, 540, 540, 460, 1000, 1280,
+    240, 240, 140, 980, 600, 620, 620,
+];
+
+#[rustfmt::skip]
+const BOOKMAN_LIGHT_ITALIC: [u16; 95] = [
+    300, 320, 360, 600, 620, 800, 820, 280, 280, 280, 440, 600, 300, 320, 300, 600,
+    620, 620, 620, 620, 620, 620, 620, 620, 620, 620, 300, 300, 600, 600, 600, 540,
+    780, 700, 720, 720, 740, 680, 620, 760, 800, 320, 560, 720, 580, 860, 720, 760,
+    600, 780, 700, 640, 600, 720, 680, 960, 700, 660, 580, 260, 600, 260, 600, 500,
+    280, 620, 600, 480, 640, 540, 340, 560, 620, 280, 280, 600, 280, 880, 620, 540,
+    600, 560, 400, 540, 340, 620, 540, 880, 540, 600, 520, 360, 600, 380, 600,
+];
+
+#[rustfmt::skip]
+const BOOKMAN_LIGHT_ITALIC_EXTRA: [u16; 71] = [
+    320, 620, 620, 620, 620, 600, 620, 360, 780, 440, 300, 600, 320, 780, 440, 400,
+    600, 372, 372, 320, 620, 620, 300, 300, 372, 400, 300, 930, 930, 930, 540, 1220,
+    740, 600, 760, 600, 620, 880, 540, 600, 540, 600, 280, 580, 340, 1180, 900, 620,
+    340, 340, 440, 500, 1000, 280, 280, 320, 440, 440, 480, 620, 620, 460, 1000, 1180,
+    180, 180, 20, 980, 600, 640, 660,
+];
+
+#[rustfmt::skip]
+const BOOKMAN_DEMI: [u16; 95] = [
+    340, 360, 420, 660, 660, 940, 800, 320, 320, 320, 460, 600, 340, 360, 340, 600,
+    660, 660, 660, 660, 660, 660, 660, 660, 660, 660, 340, 340, 600, 600, 600, 660,
+    820, 720, 720, 740, 780, 720, 680, 780, 820, 400, 640, 800, 640, 940, 740, 800,
+    660, 800, 780, 660, 700, 740, 720, 940, 780, 700, 640, 300, 600, 300, 600, 500,
+    320, 580, 600, 580, 640, 580, 380, 580, 680, 360, 340, 660, 340, 1000, 680, 620,
+    640, 620, 460, 520, 460, 660, 600, 800, 600, 620, 560, 320, 600, 320, 600,
+];
+
+#[rustfmt::skip]
+const BOOKMAN_DEMI_EXTRA: [u16; 71] = [
+    360, 660, 660, 660, 660, 600, 600, 500, 740, 400, 400, 600, 360, 740, 460, 400,
+    600, 396, 396, 400, 660, 800, 340, 360, 396, 400, 400, 990, 990, 990, 660, 1140,
+    780, 600, 800, 660, 660, 880, 620, 600, 620, 640, 360, 640, 340, 1220, 940, 660,
+    500, 500, 480, 500, 1000, 320, 320, 320, 540, 540, 540, 440, 380, 460, 1000, 1360,
+    220, 220, 120, 980, 600, 740, 740,
+];
+
+#[rustfmt::skip]
+const BOOKMAN_DEMI_ITALIC: [u16; 95] = [
+    340, 320, 380, 680, 680, 880, 980, 320, 260, 260, 460, 600, 340, 280, 340, 360,
+    680, 680, 680, 680, 680, 680, 680, 680, 680, 680, 340, 340, 620, 600, 620, 620,
+    780, 720, 720, 700, 760, 720, 660, 760, 800, 380, 620, 780, 640, 860, 740, 760,
+    640, 760, 740, 700, 700, 740, 660, 1000, 740, 660, 680, 260, 580, 260, 620, 500,
+    320, 680, 600, 560, 680, 560, 420, 620, 700, 380, 320, 700, 380, 960, 680, 600,
+    660, 620, 500, 540, 440, 680, 540, 860, 620, 600, 560, 300, 620, 300, 620,
+];
+
+#[rustfmt::skip]
+const BOOKMAN_DEMI_ITALIC_EXTRA: [u16; 71] = [
+    320, 680, 680, 680, 680, 620, 620, 520, 780, 440, 380, 620, 280, 780, 480, 400,
+    600, 408, 408, 340, 680, 680, 340, 360, 408, 440, 380, 1020, 1020, 1020, 620, 1140,
+    760, 600, 760, 640, 660, 880, 600, 600, 600, 660, 380, 640, 380, 1180, 920, 680,
+    480, 480, 480, 500, 1000, 320, 320, 300, 520, 520, 520, 420, 420, 360, 1000, 1360,
+    220, 220, 120, 940, 600, 820, 820,
+];
+
+#[rustfmt::skip]
+const NEW_CENTURY_ROMAN: [u16; 95] = [
+    278, 296, 389, 556, 556, 833, 815, 204, 333, 333, 500, 606, 278, 333, 278, 278,
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 606, 606, 606, 444,
+    737, 722, 722, 722, 778, 722, 667, 778, 833, 407, 556, 778, 667, 944, 815, 778,
+    667, 778, 722, 630, 667, 815, 722, 981, 704, 704, 611, 333, 606, 333, 606, 500,
+    204, 556, 556, 444, 574, 500, 333, 537, 611, 315, 296, 593, 315, 889, 611, 500,
+    574, 556, 444, 463, 389, 611, 537, 778, 537, 537, 481, 333, 606, 333, 606,
+];
+
+#[rustfmt::skip]
+const NEW_CENTURY_ROMAN_EXTRA: [u16; 71] = [
+    296, 556, 556, 556, 556, 606, 500, 333, 737, 334, 426, 606, 333, 737, 333, 400,
+    606, 333, 333, 333, 611, 606, 278, 333, 333, 300, 426, 834, 834, 834, 444, 1000,
+    778, 606, 778, 667, 574, 796, 500, 606, 500, 574, 315, 667, 315, 1000, 833, 556,
+    333, 333, 333, 556, 1000, 204, 204, 204, 389, 389, 389, 500, 500, 606, 1000, 1000,
+    259, 259, 167, 1000, 606, 611, 611,
+];
+
+#[rustfmt::skip]
+const NEW_CENTURY_ITALIC: [u16; 95] = [
+    278, 333, 400, 556, 556, 833, 852, 204, 333, 333, 500, 606, 278, 333, 278, 606,
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 606, 606, 606, 444,
+    747, 704, 722, 722, 778, 722, 667, 778, 833, 407, 611, 741, 667, 944, 815, 778,
+    667, 778, 741, 667, 611, 815, 704, 926, 704, 685, 667, 333, 606, 333, 606, 500,
+    204, 574, 556, 444, 611, 444, 333, 537, 611, 333, 315, 556, 333, 889, 611, 500,
+    574, 556, 444, 444, 352, 611, 519, 778, 500, 500, 463, 333, 606, 333, 606,
+];
+
+#[rustfmt::skip]
+const NEW_CENTURY_ITALIC_EXTRA: [u16; 71] = [
+    333, 556, 556, 556, 556, 606, 500, 333, 747, 422, 426, 606, 333, 747, 333, 400,
+    606, 333, 333, 333, 611, 650, 278, 333, 333, 372, 426, 834, 834, 834, 444, 870,
+    778, 606, 778, 667, 556, 722, 500, 606, 500, 574, 333, 667, 333, 981, 778, 556,
+    333, 333, 333, 500, 1000, 204, 204, 204, 389, 389, 389, 500, 500, 606, 1000, 1000,
+    333, 333, 167, 950, 606, 611, 611,
+];
+
+#[rustfmt::skip]
+const NEW_CENTURY_BOLD: [u16; 95] = [
+    287, 296, 333, 574, 574, 833, 852, 241, 389, 389, 500, 606, 278, 333, 278, 278,
+    574, 574, 574, 574, 574, 574, 574, 574, 574, 574, 278, 278, 606, 606, 606, 500,
+    747, 759, 778, 778, 833, 759, 722, 833, 870, 444, 648, 815, 722, 981, 833, 833,
+    759, 833, 815, 667, 722, 833, 759, 981, 722, 722, 667, 389, 606, 389, 606, 500,
+    241, 611, 648, 556, 667, 574, 389, 611, 685, 370, 352, 667, 352, 963, 685, 611,
+    667, 648, 519, 500, 426, 685, 611, 889, 611, 611, 537, 389, 606, 389, 606,
+];
+
+#[rustfmt::skip]
+const NEW_CENTURY_BOLD_EXTRA: [u16; 71] = [
+    296, 574, 574, 574, 574, 606, 500, 333, 747, 367, 500, 606, 333, 747, 333, 400,
+    606, 344, 344, 333, 685, 747, 278, 333, 344, 367, 500, 861, 861, 861, 500, 981,
+    833, 606, 833, 759, 611, 870, 611, 606, 611, 667, 370, 722, 352, 1000, 907, 574,
+    333, 333, 333, 500, 1000, 241, 241, 241, 481, 481, 481, 500, 500, 606, 1000, 1000,
+    333, 333, 167, 1000, 606, 685, 685,
+];
+
+#[rustfmt::skip]
+const NEW_CENTURY_BOLD_ITALIC: [u16; 95] = [
+    287, 333, 400, 574, 574, 889, 889, 259, 407, 407, 500, 606, 287, 333, 287, 278,
+    574, 574, 574, 574, 574, 574, 574, 574, 574, 574, 287, 287, 606, 606, 606, 481,
+    747, 741, 759, 759, 833, 741, 704, 815, 870, 444, 667, 778, 704, 944, 852, 833,
+    741, 833, 796, 685, 722, 833, 741, 944, 741, 704, 704, 407, 606, 407, 606, 500,
+    259, 667, 611, 537, 667, 519, 389, 611, 685, 389, 370, 648, 389, 944, 685, 574,
+    648, 630, 519, 481, 407, 685, 556, 833, 574, 519, 519, 407, 606, 407, 606,
+];
+
+#[rustfmt::skip]
+const NEW_CENTURY_BOLD_ITALIC_EXTRA: [u16; 71] = [
+    333, 574, 574, 574, 574, 606, 500, 333, 747, 412, 481, 606, 333, 747, 333, 400,
+    606, 344, 344, 333, 685, 650, 287, 333, 344, 356, 481, 861, 861, 861, 481, 889,
+    833, 606, 833, 741, 574, 815, 574, 606, 574, 648, 389, 704, 389, 1000, 815, 574,
+    333, 333, 333, 500, 1000, 259, 259, 259, 481, 481, 481, 500, 500, 606, 1000, 1167,
+    278, 278, 167, 950, 606, 685, 685,
+];
+
+#[rustfmt::skip]
+const PALATINO_ROMAN: [u16; 95] = [
+    250, 278, 371, 500, 500, 840, 778, 278, 333, 333, 389, 606, 250, 333, 250, 606,
+    500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 250, 250, 606, 606, 606, 444,
+    747, 778, 611, 709, 774, 611, 556, 763, 832, 337, 333, 726, 611, 946, 831, 786,
+    604, 786, 668, 525, 613, 778, 722, 1000, 667, 667, 667, 333, 606, 333, 606, 500,
+    278, 500, 553, 444, 611, 479, 333, 556, 582, 291, 234, 556, 291, 883, 582, 546,
+    601, 560, 395, 424, 326, 603, 565, 834, 516, 556, 500, 333, 606, 333, 606,
+];
+
+#[rustfmt::skip]
+const PALATINO_ROMAN_EXTRA: [u16; 71] = [
+    278, 500, 500, 500, 500, 606, 500, 333, 747, 333, 500, 606, 333, 747, 333, 400,
+    606, 300, 300, 333, 603, 628, 250, 333, 300, 333, 500, 750, 750, 750, 444, 944,
+    774, 606, 833, 604, 556, 758, 546, 606, 556, 601, 287, 611, 291, 998, 827, 500,
+    333, 333, 333, 500, 1000, 278, 278, 278, 500, 500, 500, 500, 500, 606, 1000, 1144,
+    331, 331, 167, 979, 606, 605, 608,
+];
+
+#[rustfmt::skip]
+const PALATINO_ITALIC: [u16; 95] = [
+    250, 333, 500, 500, 500, 889, 778, 278, 333, 333, 389, 606, 250, 333, 250, 296,
+    500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 250, 250, 606, 606, 606, 500,
+    747, 722, 611, 667, 778, 611, 556, 722, 778, 333, 333, 667, 556, 944, 778, 778,
+    611, 778, 667, 556, 611, 778, 722, 944, 722, 667, 667, 333, 606, 333, 606, 500,
+    278, 444, 463, 407, 500, 389, 278, 500, 500, 278, 278, 444, 278, 778, 556, 444,
+    500, 463, 389, 389, 333, 556, 500, 722, 500, 500, 444, 333, 606, 333, 606,
+];
+
+#[rustfmt::skip]
+const PALATINO_ITALIC_EXTRA: [u16; 71] = [
+    333, 500, 500, 500, 500, 606, 500, 333, 747, 333, 500, 606, 333, 747, 333, 400,
+    606, 300, 300, 333, 556, 500, 250, 333, 300, 333, 500, 750, 750, 750, 500, 941,
+    778, 606, 778, 611, 500, 638, 444, 606, 444, 500, 278, 556, 278, 1028, 669, 500,
+    333, 333, 333, 500, 1000, 278, 278, 278, 500, 500, 500, 500, 500, 500, 1000, 1000,
+    333, 333, 167, 1000, 606, 528, 545,
+];
+
+#[rustfmt::skip]
+const PALATINO_BOLD: [u16; 95] = [
+    250, 278, 402, 500, 500, 889, 833, 278, 333, 333, 444, 606, 250, 333, 250, 296,
+    500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 250, 250, 606, 606, 606, 444,
+    747, 778, 667, 722, 833, 611, 556, 833, 833, 389, 389, 778, 611, 1000, 833, 833,
+    611, 833, 722, 611, 667, 778, 778, 1000, 667, 667, 667, 333, 606, 333, 606, 500,
+    278, 500, 611, 444, 611, 500, 389, 556, 611, 333, 333, 611, 333, 889, 611, 556,
+    611, 611, 389, 444, 333, 611, 556, 833, 500, 556, 500, 310, 606, 310, 606,
+];
+
+#[rustfmt::skip]
+const PALATINO_BOLD_EXTRA: [u16; 71] = [
+    278, 500, 500, 500, 500, 606, 500, 333, 747, 438, 500, 606, 333, 747, 333, 400,
+    606, 300, 300, 333, 611, 641, 250, 333, 300, 488, 500, 750, 750, 750, 444, 1000,
+    833, 606, 833, 611, 611, 778, 556, 606, 556, 611, 333, 611, 333, 1000, 833, 500,
+    333, 333, 333, 500, 1000, 278, 278, 333, 500, 500, 500, 500, 500, 606, 1000, 1000,
+    389, 389, 167, 998, 606, 611, 611,
+];
+
+#[rustfmt::skip]
+const PALATINO_BOLD_ITALIC: [u16; 95] = [
+    250, 333, 500, 500, 500, 889, 833, 278, 333, 333, 444, 606, 250, 389, 250, 315,
+    500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 250, 250, 606, 606, 606, 444,
+    833, 722, 667, 685, 778, 611, 556, 778, 778, 389, 389, 722, 611, 944, 778, 833,
+    667, 833, 722, 556, 611, 778, 667, 1000, 722, 611, 667, 333, 606, 333, 606, 500,
+    278, 556, 537, 444, 556, 444, 333, 500, 556, 333, 333, 556, 333, 833, 556, 556,
+    556, 537, 389, 444, 389, 556, 556, 833, 500, 556, 500, 333, 606, 333, 606,
+];
+
+#[rustfmt::skip]
+const PALATINO_BOLD_ITALIC_EXTRA: [u16; 71] = [
+    333, 500, 500, 500, 500, 606, 556, 333, 747, 333, 500, 606, 389, 747, 333, 400,
+    606, 300, 300, 333, 556, 556, 250, 333, 300, 333, 500, 750, 750, 750, 444, 944,
+    778, 606, 833, 667, 556, 738, 556, 606, 556, 537, 333, 611, 333, 944, 778, 500,
+    333, 333, 333, 500, 1000, 278, 278, 250, 500, 500, 500, 556, 556, 606, 1000, 1000,
+    389, 389, 167, 1000, 606, 611, 611,
+];
+
+#[rustfmt::skip]
+const ZAPF_CHANCERY: [u16; 95] = [
+    220, 280, 220, 440, 440, 680, 780, 240, 260, 220, 420, 520, 220, 280, 220, 340,
+    440, 440, 440, 440, 440, 440, 440, 440, 440, 440, 260, 240, 520, 520, 520, 380,
+    700, 620, 600, 520, 700, 620, 580, 620, 680, 380, 400, 660, 580, 840, 700, 600,
+    540, 600, 600, 460, 500, 740, 640, 880, 560, 560, 620, 240, 480, 320, 520, 500,
+    240, 420, 420, 340, 440, 340, 320, 400, 440, 240, 220, 440, 240, 620, 460, 400,
+    440, 400, 300, 320, 320, 460, 440, 680, 420, 400, 440, 240, 520, 240, 520,
+];
+
+#[rustfmt::skip]
+const ZAPF_CHANCERY_EXTRA: [u16; 71] = [
+    280, 440, 440, 440, 440, 520, 420, 220, 740, 260, 340, 520, 280, 740, 440, 400,
+    520, 264, 264, 300, 460, 500, 220, 300, 264, 260, 380, 660, 660, 660, 400, 740,
+    700, 520, 660, 540, 420, 540, 400, 520, 440, 440, 240, 580, 300, 820, 560, 440,
+    340, 340, 440, 500, 1000, 240, 240, 180, 340, 360, 280, 460, 480, 600, 1000, 960,
+    240, 260, 60, 1000, 520, 520, 520,
+];
+
+// Symbol and ZapfDingbats have their own encodings, eg: code 97 is alpha in Symbol.
+#[rustfmt::skip]
+const SYMBOL: [u16; 95] = [
+    250, 333, 713, 500, 549, 833, 778, 439, 333, 333, 500, 549, 250, 549, 250, 278,
+    500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 278, 278, 549, 549, 549, 444,
+    549, 722, 667, 722, 612, 611, 763, 603, 722, 333, 631, 722, 686, 889, 722, 722,
+    768, 741, 556, 592, 611, 690, 439, 768, 645, 795, 611, 333, 863, 333, 658, 500,
+    500, 631, 549, 549, 494, 439, 521, 411, 603, 329, 603, 549, 549, 576, 521, 549,
+    549, 521, 549, 603, 439, 576, 713, 686, 493, 686, 494, 480, 200, 480, 549,
+];
+
+#[rustfmt::skip]
+const ZAPF_DINGBATS: [u16; 95] = [
+    278, 974, 961, 974, 980, 719, 789, 790, 791, 690, 960, 939, 549, 855, 911, 933,
+    911, 945, 974, 755, 846, 762, 761, 571, 677, 763, 760, 759, 754, 494, 552, 537,
+    577, 692, 786, 788, 788, 790, 793, 794, 816, 823, 789, 841, 823, 833, 816, 831,
+    923, 744, 723, 749, 790, 792, 695, 776, 768, 792, 759, 707, 708, 682, 701, 826,
+    815, 789, 789, 707, 687, 696, 689, 786, 787, 713, 791, 785, 791, 873, 761, 762,
+    762, 759, 759, 892, 892, 788, 784, 438, 138, 277, 415, 392, 392, 668, 668,
+];
+
+// Kerning pairs (1/1000 em) from the Adobe AFM files. Only Helvetica (and its oblique and narrow
+// styles) and Times-Roman are kerned. Quotes are paired under both their typographic and ASCII
+// characters, since ASCII quotes are shown as quoteright and quoteleft.
+#[rustfmt::skip]
+const HELVETICA_KERNING: [(char, char, i16); 237] = [
+    ('A', 'y', -40), ('A', 'w', -40), ('A', 'v', -40), ('A', 'u', -30), ('A', 'Y', -100),
+    ('A', 'W', -50), ('A', 'V', -70), ('A', 'U', -50), ('A', 'T', -120), ('A', 'Q', -30),
+    ('A', 'O', -30), ('A', 'G', -30), ('A', 'C', -30), ('B', '.', -20), ('B', ',', -20),
+    ('B', 'U', -10), ('C', '.', -30), ('C', ',', -30), ('D', '.', -70), ('D', ',', -70),
+    ('D', 'Y', -90), ('D', 'W', -40), ('D', 'V', -70), ('D', 'A', -40), ('F', 'r', -45),
+    ('F', '.', -150), ('F', 'o', -30), ('F', 'e', -30), ('F', ',', -150), ('F', 'a', -50),
+    ('F', 'A', -80), ('J', 'u', -20), ('J', '.', -30), ('J', ',', -30), ('J', 'a', -20),
+    ('J', 'A', -20), ('K', 'y', -50), ('K', 'u', -30), ('K', 'o', -40), ('K', 'e', -40),
+    ('K', 'O', -50), ('L', 'y', -30), ('L', '\u{2019}', -160), ('L', '\'', -160),
+    ('L', '\u{201D}', -140), ('L', 'Y', -140), ('L', 'W', -70), ('L', 'V', -110), ('L', 'T', -110),
+    ('O', '.', -40), ('O', ',', -40), ('O', 'Y', -70), ('O', 'X', -60), ('O', 'W', -30),
+    ('O', 'V', -50), ('O', 'T', -40), ('O', 'A', -20), ('P', '.', -180), ('P', 'o', -50),
+    ('P', 'e', -50), ('P', ',', -180), ('P', 'a', -40), ('P', 'A', -120), ('Q', 'U', -10),
+    ('R', 'Y', -50), ('R', 'W', -30), ('R', 'V', -50), ('R', 'U', -40), ('R', 'T', -30),
+    ('R', 'O', -20), ('S', '.', -20), ('S', ',', -20), ('T', 'y', -120), ('T', 'w', -120),
+    ('T', 'u', -120), ('T', ';', -20), ('T', 'r', -120), ('T', '.', -120), ('T', 'o', -120),
+    ('T', '-', -140), ('T', 'e', -120), ('T', ',', -120), ('T', ':', -20), ('T', 'a', -120),
+    ('T', 'O', -40), ('T', 'A', -120), ('U', '.', -40), ('U', ',', -40), ('U', 'A', -40),
+    ('V', 'u', -70), ('V', ';', -40), ('V', '.', -125), ('V', 'o', -80), ('V', '-', -80),
+    ('V', 'e', -80), ('V', ',', -125), ('V', ':', -40), ('V', 'a', -70), ('V', 'O', -40),
+    ('V', 'G', -40), ('V', 'A', -80), ('W', 'y', -20), ('W', 'u', -30), ('W', '.', -80),
+    ('W', 'o', -30), ('W', '-', -40), ('W', 'e', -30), ('W', ',', -80), ('W', 'a', -40),
+    ('W', 'O', -20), ('W', 'A', -50), ('Y', 'u', -110), ('Y', ';', -60), ('Y', '.', -140),
+    ('Y', 'o', -140), ('Y', 'i', -20), ('Y', '-', -140), ('Y', 'e', -140), ('Y', ',', -140),
+    ('Y', ':', -60), ('Y', 'a', -140), ('Y', 'O', -85), ('Y', 'A', -110), ('a', 'y', -30),
+    ('a', 'w', -20), ('a', 'v', -20), ('b', 'y', -20), ('b', 'v', -20), ('b', 'u', -20),
+    ('b', '.', -40), ('b', 'l', -20), ('b', ',', -40), ('b', 'b', -10), ('c', 'k', -20),
+    ('c', ',', -15), (':', ' ', -50), (',', '\u{2019}', -100), (',', '\'', -100),
+    (',', '\u{201D}', -100), ('e', 'y', -20), ('e', 'x', -30), ('e', 'w', -20), ('e', 'v', -30),
+    ('e', '.', -15), ('e', ',', -15), ('f', '\u{2019}', 50), ('f', '\'', 50), ('f', '\u{201D}', 60),
+    ('f', '.', -30), ('f', 'o', -30), ('f', 'e', -30), ('f', '\u{0131}', -28), ('f', ',', -30),
+    ('f', 'a', -30), ('g', 'r', -10), ('h', 'y', -30), ('k', 'o', -20), ('k', 'e', -20),
+    ('m', 'y', -15), ('m', 'u', -10), ('n', 'y', -15), ('n', 'v', -20), ('n', 'u', -10),
+    ('o', 'y', -30), ('o', 'x', -30), ('o', 'w', -15), ('o', 'v', -15), ('o', '.', -40),
+    ('o', ',', -40), ('p', 'y', -30), ('p', '.', -35), ('p', ',', -35), ('.', ' ', -60),
+    ('.', '\u{2019}', -100), ('.', '\'', -100), ('.', '\u{201D}', -100), ('\u{201D}', ' ', -40),
+    ('\u{2018}', '\u{2018}', -57), ('\u{2018}', '`', -57), ('`', '\u{2018}', -57), ('`', '`', -57),
+    ('\u{2019}', ' ', -70), ('\'', ' ', -70), ('\u{2019}', 's', -50), ('\'', 's', -50),
+    ('\u{2019}', 'r', -50), ('\'', 'r', -50), ('\u{2019}', '\u{2019}', -57),
+    ('\u{2019}', '\'', -57), ('\'', '\u{2019}', -57), ('\'', '\'', -57), ('\u{2019}', 'd', -50),
+    ('\'', 'd', -50), ('r', 'y', 30), ('r', 'v', 30), ('r', 'u', 15), ('r', 't', 40),
+    ('r', ';', 30), ('r', '.', -50), ('r', 'p', 30), ('r', 'n', 25), ('r', 'm', 25), ('r', 'l', 15),
+    ('r', 'k', 15), ('r', 'i', 15), ('r', ',', -50), ('r', ':', 30), ('r', 'a', -10),
+    ('s', 'w', -30), ('s', '.', -15), ('s', ',', -15), (';', ' ', -50), (' ', '\u{2018}', -60),
+    (' ', '`', -60), (' ', '\u{201C}', -30), (' ', 'Y', -90), (' ', 'W', -40), (' ', 'V', -50),
+    (' ', 'T', -50), ('v', '.', -80), ('v', 'o', -25), ('v', 'e', -25), ('v', ',', -80),
+    ('v', 'a', -25), ('w', '.', -60), ('w', 'o', -10), ('w', 'e', -10), ('w', ',', -60),
+    ('w', 'a', -15), ('x', 'e', -30), ('y', '.', -100), ('y', 'o', -20), ('y', 'e', -20),
+    ('y', ',', -100), ('y', 'a', -20), ('z', 'o', -15), ('z', 'e', -15),
+];
+
+#[rustfmt::skip]
+const TIMES_ROMAN_KERNING: [(char, char, i16); 192] = [
+    ('A', 'C', -40), ('A', 'G', -40), ('A', 'O', -55), ('A', 'Q', -55), ('A', 'T', -111),
+    ('A', 'U', -55), ('A', 'V', -135), ('A', 'W', -90), ('A', 'Y', -105), ('A', '\u{2019}', -111),
+    ('A', '\'', -111), ('A', 'v', -74), ('A', 'w', -92), ('A', 'y', -92), ('B', 'A', -35),
+    ('B', 'U', -10), ('D', 'A', -40), ('D', 'V', -40), ('D', 'W', -30), ('D', 'Y', -55),
+    ('F', 'A', -74), ('F', 'a', -15), ('F', ',', -80), ('F', 'o', -15), ('F', '.', -80),
+    ('J', 'A', -60), ('K', 'O', -30), ('K', 'e', -25), ('K', 'o', -35), ('K', 'u', -15),
+    ('K', 'y', -25), ('L', 'T', -92), ('L', 'V', -100), ('L', 'W', -74), ('L', 'Y', -100),
+    ('L', '\u{2019}', -92), ('L', '\'', -92), ('L', 'y', -55), ('N', 'A', -35), ('O', 'A', -35),
+    ('O', 'T', -40), ('O', 'V', -50), ('O', 'W', -35), ('O', 'X', -40), ('O', 'Y', -50),
+    ('P', 'A', -92), ('P', 'a', -15), ('P', ',', -111), ('P', '.', -111), ('Q', 'U', -10),
+    ('R', 'O', -40), ('R', 'T', -60), ('R', 'U', -40), ('R', 'V', -80), ('R', 'W', -55),
+    ('R', 'Y', -65), ('T', 'A', -93), ('T', 'O', -18), ('T', 'a', -80), ('T', ':', -50),
+    ('T', ',', -74), ('T', 'e', -70), ('T', '-', -92), ('T', 'i', -35), ('T', 'o', -80),
+    ('T', '.', -74), ('T', 'r', -35), ('T', ';', -55), ('T', 'u', -45), ('T', 'w', -80),
+    ('T', 'y', -80), ('U', 'A', -40), ('V', 'A', -135), ('V', 'G', -15), ('V', 'O', -40),
+    ('V', 'a', -111), ('V', ':', -74), ('V', ',', -129), ('V', 'e', -111), ('V', '-', -100),
+    ('V', 'i', -60), ('V', 'o', -129), ('V', '.', -129), ('V', ';', -74), ('V', 'u', -75),
+    ('W', 'A', -120), ('W', 'O', -10), ('W', 'a', -80), ('W', ':', -37), ('W', ',', -92),
+    ('W', 'e', -80), ('W', '-', -65), ('W', 'i', -40), ('W', 'o', -80), ('W', '.', -92),
+    ('W', ';', -37), ('W', 'u', -50), ('W', 'y', -73), ('Y', 'A', -120), ('Y', 'O', -30),
+    ('Y', 'a', -100), ('Y', ':', -92), ('Y', ',', -129), ('Y', 'e', -100), ('Y', '-', -111),
+    ('Y', 'i', -55), ('Y', 'o', -110), ('Y', '.', -129), ('Y', ';', -92), ('Y', 'u', -111),
+    ('a', 'v', -20), ('a', 'w', -15), ('b', '.', -40), ('b', 'u', -20), ('b', 'v', -15),
+    ('c', 'y', -15), (',', '\u{201D}', -70), (',', '\u{2019}', -70), (',', '\'', -70),
+    ('e', 'g', -15), ('e', 'v', -25), ('e', 'w', -25), ('e', 'x', -15), ('e', 'y', -15),
+    ('f', 'a', -10), ('f', '\u{0131}', -50), ('f', 'f', -25), ('f', 'i', -20),
+    ('f', '\u{2019}', 55), ('f', '\'', 55), ('g', 'a', -5), ('h', 'y', -5), ('i', 'v', -25),
+    ('k', 'e', -10), ('k', 'o', -10), ('k', 'y', -15), ('l', 'w', -10), ('n', 'v', -40),
+    ('n', 'y', -15), ('o', 'v', -15), ('o', 'w', -25), ('o', 'y', -10), ('p', 'y', -10),
+    ('.', '\u{201D}', -70), ('.', '\u{2019}', -70), ('.', '\'', -70), ('\u{201C}', 'A', -80),
+    ('\u{2018}', 'A', -80), ('`', 'A', -80), ('\u{2018}', '\u{2018}', -74), ('\u{2018}', '`', -74),
+    ('`', '\u{2018}', -74), ('`', '`', -74), ('\u{2019}', 'd', -50), ('\'', 'd', -50),
+    ('\u{2019}', 'l', -10), ('\'', 'l', -10), ('\u{2019}', '\u{2019}', -74),
+    ('\u{2019}', '\'', -74), ('\'', '\u{2019}', -74), ('\'', '\'', -74), ('\u{2019}', 'r', -50),
+    ('\'', 'r', -50), ('\u{2019}', 's', -55), ('\'', 's', -55), ('\u{2019}', ' ', -74),
+    ('\'', ' ', -74), ('\u{2019}', 't', -18), ('\'', 't', -18), ('\u{2019}', 'v', -50),
+    ('\'', 'v', -50), ('r', ',', -40), ('r', 'g', -18), ('r', '-', -20), ('r', '.', -55),
+    (' ', 'A', -55), (' ', 'T', -18), (' ', 'V', -50), (' ', 'W', -30), (' ', 'Y', -90),
+    ('v', 'a', -25), ('v', ',', -65), ('v', 'e', -15), ('v', 'o', -20), ('v', '.', -65),
+    ('w', 'a', -10), ('w', ',', -65), ('w', 'o', -10), ('w', '.', -65), ('x', 'e', -15),
+    ('y', ',', -65), ('y', '.', -65),
+];
+
+// Helvetica Narrow is Helvetica condensed to 82% of its width.
+const NARROW: f32 = 0.82;
+
+// The standard fonts all have an UnderlinePosition of -100 and an UnderlineThickness of 50. They
+// have no strikeout metrics so the strikeout is centered at half the XHeight.
+const STANDARD_DECORATIONS: Decorations = Decorations {
+    underline_position: -100.0,
+    underline_thickness: 50.0,
+    strikeout_position: 261.5,
+    strikeout_thickness: 50.0,
+};
+
+// The positions of the underline and strikeout from the baseline (negative below) to the center of
+// the line, and their thicknesses, in 1/1000 em.
+#[derive(Clone, Copy)]
+pub(crate) struct Decorations {
+    pub(crate) underline_position: f32,
+    pub(crate) underline_thickness: f32,
+    pub(crate) strikeout_position: f32,
+    pub(crate) strikeout_thickness: f32,
+}
+
+impl Default for Decorations {
+    fn default() -> Self {
+        STANDARD_DECORATIONS
+    }
+}
+
+impl Decorations {
+    // Returns the position and thickness of each line in points.
+    pub(crate) fn lines(&self, size: f32, underline: bool, strikethrough: bool) -> Vec<(f32, f32)> {
+        let mut lines = Vec::new();
+        if underline {
+            lines.push((self.underline_position, self.underline_thickness));
+        }
+        if strikethrough {
+            lines.push((self.strikeout_position, self.strikeout_thickness));
+        }
+        lines
+            .into_iter()
+            .map(|(position, thickness)| (position * size / 1000.0, thickness * size / 1000.0))
+            .collect()
+    }
+}
+
+// XHeights from the AFM files. Fonts without metrics use Helvetica's.
+fn x_height(font: &str) -> f32 {
+    match font {
+        "Courier" | "Courier-Oblique" => 426.0,
+        "Courier-Bold" | "Courier-BoldOblique" => 439.0,
+        "Helvetica-Bold" | "Helvetica-BoldOblique" => 532.0,
+        "Helvetica-Narrow-Bold" | "Helvetica-Narrow-BoldOblique" => 532.0,
+        "Times-Roman" => 448.0,
+        "Times-Bold" => 461.0,
+        "Times-Italic" => 441.0,
+        "Times-BoldItalic" => 462.0,
+        _ => 523.0,
+    }
+}
+
+// Widths in 1/1000 em by character, the width used for characters the font doesn't have, the
+// kerning adjustment for pairs of characters and the font's decoration lines. CID-keyed fonts
+// also have the glyph index of each character, which is the character code text is shown with.
+#[derive(Clone)]
+pub(crate) struct Widths {
+    widths: HashMap<char, f32>,
+    missing: f32,
+    kerning: HashMap<(char, char), f32>,
+    decorations: Decorations,
+    glyphs: Option<HashMap<char, u16>>,
+}
+
+impl Widths {
+    pub(crate) fn new(
+        widths: HashMap<char, f32>,
+        missing: f32,
+        kerning: HashMap<(char, char), f32>,
+        decorations: Decorations,
+    ) -> Self {
+        Widths {
+            widths,
+            missing,
+            kerning,
+            decorations,
+            glyphs: None,
+        }
+    }
+
+    pub(crate) fn set_glyphs(&mut self, glyphs: Option<HashMap<char, u16>>) {
+        self.glyphs = glyphs;
+    }
+
+    pub(crate) fn advance(&self, c: char) -> f32 {
+        match self.widths.get(&c) {
+            Some(width) => *width,
+            None => *self.widths.get(&base_letter(c)).unwrap_or(&self.missing),
+        }
+    }
+
+    pub(crate) fn kerning(&self, left: char, right: char) -> f32 {
+        self.kerning.get(&(left, right)).copied().unwrap_or(0.0)
+    }
+
+    fn measure(&self, text: &str) -> f32 {
+        let widths: f32 = text.chars().map(|c| self.advance(c)).sum();
+        let kerning: f32 = text
+            .chars()
+            .zip(text.chars().skip(1))
+            .map(|(left, right)| self.kerning(left, right))
+            .sum();
+        widths + kerning
+    }
+}
+
+#[derive(Clone)]
+pub struct FontMetrics {
+    embedded: HashMap<String, Widths>,
+    fallbacks: HashMap<String, Vec<String>>,
+    standard_kerning: bool,
+}
+
+impl Default for FontMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FontMetrics {
+    pub fn new() -> Self {
+        FontMetrics {
+            embedded: HashMap::new(),
+            fallbacks: HashMap::new(),
+            standard_kerning: true,
+        }
+    }
+
+    // Text without metrics isn't kerned, so it's measured without the standard fonts' kerning.
+    fn unkerned() -> Self {
+        FontMetrics {
+            standard_kerning: false,
+            ..Self::new()
+        }
+    }
+
+    pub fn add_font(&mut self, font: &TrueTypeFont) {
+        self.embedded
+            .insert(font.name().to_string(), font.widths().clone());
+    }
+
+    // Characters missing from the font are set in the first of the fallback fonts that has them,
+    // eg: an embedded CJK font and Symbol after a Latin font. Fallbacks of fallbacks aren't used.
+    pub fn set_fallbacks<F: Into<Font>>(&mut self, font: F, fallbacks: Vec<Font>) {
+        let fallbacks: Vec<String> = fallbacks
+            .into_iter()
+            .map(|fallback| fallback.name().to_string())
+            .collect();
+        let font = font.into().name().to_string();
+        if fallbacks.is_empty() {
+            self.fallbacks.remove(&font);
+        } else {
+            self.fallbacks.insert(font, fallbacks);
+        }
+    }
+
+    // Embedded fonts have the characters in their cmap and the standard Latin fonts have Latin-1
+    // and the other characters in the standard Latin character set. Other fonts are assumed to
+    // have every character.
+    fn has_glyph(&self, font: &str, c: char) -> bool {
+        if let Some(widths) = self.embedded.get(font) {
+            return widths.widths.contains_key(&c);
+        }
+        if !is_standard_latin(font) || (c as u32) <= 0xFF {
+            return true;
+        }
+        !('\u{0391}'..='\u{03C9}').contains(&c) && !glyph_name(c).starts_with("uni")
+    }
+
+    // Splits the text into runs of the font or the fallback it is set in. Whitespace stays in the
+    // run before it.
+    pub(crate) fn runs<'a>(&'a self, font: &'a str, text: &str) -> Vec<(&'a str, String)> {
+        let Some(fallbacks) = self.fallbacks.get(font) else {
+            return vec![(font, text.to_string())];
+        };
+        let mut runs: Vec<(&str, String)> = Vec::new();
+        for c in text.chars() {
+            let chosen = match runs.last() {
+                Some((current, _)) if c.is_whitespace() => *current,
+                _ => std::iter::once(font)
+                    .chain(fallbacks.iter().map(String::as_str))
+                    .find(|candidate| self.has_glyph(candidate, c))
+                    .unwrap_or(font),
+            };
+            match runs.last_mut() {
+                Some((current, run)) if *current == chosen => run.push(c),
+                _ => runs.push((chosen, c.to_string())),
+            }
+        }
+        runs
+    }
+
+    // Kerning pairs (1/1000 em) are available for embedded fonts with a kern table and the
+    // standard fonts that have pairs bundled.
+    pub(crate) fn kerning(&self, font: &str) -> Option<&HashMap<(char, char), f32>> {
+        match self.embedded.get(font) {
+            Some(widths) => Some(&widths.kerning).filter(|kerning| !kerning.is_empty()),
+            None if self.standard_kerning => standard_kerning(font),
+            None => None,
+        }
+    }
+
+    // Only CID-keyed fonts have glyph indexes.
+    pub(crate) fn glyphs(&self, font: &str) -> Option<&HashMap<char, u16>> {
+        self.embedded
+            .get(font)
+            .and_then(|widths| widths.glyphs.as_ref())
+    }
+
+    pub(crate) fn decorations(&self, font: &str) -> Decorations {
+        match self.embedded.get(font) {
+            Some(widths) => widths.decorations,
+            None => Decorations {
+                strikeout_position: x_height(font) / 2.0,
+                ..STANDARD_DECORATIONS
+            },
+        }
+    }
+
+    // Returns the width of the text in points, or None when there are no metrics for the font.
+    // Kerning pairs are included, and runs set in a fallback font are measured
+    // with its metrics (or as Helvetica when it has none).
+    pub fn measure<F: Into<Font>>(&self, text: &str, font: F, size: f32) -> Option<f32> {
+        let font = font.into();
+        if !self.fallbacks.contains_key(font.name()) {
+            return self.measure_font(text, font.name(), size);
+        }
+        let mut total = 0.0;
+        for (run_font, run) in self.runs(font.name(), text) {
+            total += match self.measure_font(&run, run_font, size) {
+                Some(width) => width,
+                None if run_font == font.name() => return None,
+                None => self.measure_font(&run, "Helvetica", size).unwrap_or(0.0),
+            };
+        }
+        Some(total)
+    }
+
+    fn measure_font(&self, text: &str, font: &str, size: f32) -> Option<f32> {
+        if let Some(widths) = self.embedded.get(font) {
+            return Some(widths.measure(text) * size / 1000.0);
+        }
+        let (table, extra, scale) = match font {
+            "Courier" | "Courier-Bold" | "Courier-Oblique" | "Courier-BoldOblique" => {
+                let width = text.chars().count() as f32 * 600.0;
+                return Some(width * size / 1000.0);
+            }
+            "Helvetica" | "Helvetica-Oblique" => (&HELVETICA, Some(&HELVETICA_EXTRA), 1.0),
+            "Helvetica-Bold" | "Helvetica-BoldOblique" => {
+                (&HELVETICA_BOLD, Some(&HELVETICA_BOLD_EXTRA), 1.0)
+            }
+            "Helvetica-Narrow" | "Helvetica-Narrow-Oblique" => {
+                (&HELVETICA, Some(&HELVETICA_EXTRA), NARROW)
+            }
+            "Helvetica-Narrow-Bold" | "Helvetica-Narrow-BoldOblique" => {
+                (&HELVETICA_BOLD, Some(&HELVETICA_BOLD_EXTRA), NARROW)
+            }
+            "Times-Roman" => (&TIMES_ROMAN, Some(&TIMES_ROMAN_EXTRA), 1.0),
+            "Times-Bold" => (&TIMES_BOLD, Some(&TIMES_BOLD_EXTRA), 1.0),
+            "Times-Italic" => (&TIMES_ITALIC, Some(&TIMES_ITALIC_EXTRA), 1.0),
+            "Times-BoldItalic" => (&TIMES_BOLD_ITALIC, Some(&TIMES_BOLD_ITALIC_EXTRA), 1.0),
+            "AvantGarde-Book" | "AvantGarde-BookOblique" => {
+                (&AVANT_GARDE_BOOK, Some(&AVANT_GARDE_BOOK_EXTRA), 1.0)
+            }
+            "AvantGarde-Demi" | "AvantGarde-DemiOblique" => {
+                (&AVANT_GARDE_DEMI, Some(&AVANT_GARDE_DEMI_EXTRA), 1.0)
+            }
+            "Bookman-Light" => (&BOOKMAN_LIGHT, Some(&BOOKMAN_LIGHT_EXTRA), 1.0),
+            "Bookman-LightItalic" => (
+                &BOOKMAN_LIGHT_ITALIC,
+                Some(&BOOKMAN_LIGHT_ITALIC_EXTRA),
+                1.0,
+            ),
+            "Bookman-Demi" => (&BOOKMAN_DEMI, Some(&BOOKMAN_DEMI_EXTRA), 1.0),
+            "Bookman-DemiItalic" => (&BOOKMAN_DEMI_ITALIC, Some(&BOOKMAN_DEMI_ITALIC_EXTRA), 1.0),
+            "NewCenturySchlbk-Roman" => (&NEW_CENTURY_ROMAN, Some(&NEW_CENTURY_ROMAN_EXTRA), 1.0),
+            "NewCenturySchlbk-Italic" => {
+                (&NEW_CENTURY_ITALIC, Some(&NEW_CENTURY_ITALIC_EXTRA), 1.0)
+            }
+            "NewCenturySchlbk-Bold" => (&NEW_CENTURY_BOLD, Some(&NEW_CENTURY_BOLD_EXTRA), 1.0),
+            "NewCenturySchlbk-BoldItalic" => (
+                &NEW_CENTURY_BOLD_ITALIC,
+                Some(&NEW_CENTURY_BOLD_ITALIC_EXTRA),
+                1.0,
+            ),
+            "Palatino-Roman" => (&PALATINO_ROMAN, Some(&PALATINO_ROMAN_EXTRA), 1.0),
+            "Palatino-Italic" => (&PALATINO_ITALIC, Some(&PALATINO_ITALIC_EXTRA), 1.0),
+            "Palatino-Bold" => (&PALATINO_BOLD, Some(&PALATINO_BOLD_EXTRA), 1.0),
+            "Palatino-BoldItalic" => (
+                &PALATINO_BOLD_ITALIC,
+                Some(&PALATINO_BOLD_ITALIC_EXTRA),
+                1.0,
+            ),
+            "ZapfChancery-MediumItalic" => (&ZAPF_CHANCERY, Some(&ZAPF_CHANCERY_EXTRA), 1.0),
+            "Symbol" => (&SYMBOL, None, 1.0),
+            "ZapfDingbats" => (&ZAPF_DINGBATS, None, 1.0),
+            _ => return None,
+        };
+        let width = |c: char| {
+            let c = match c {
+                '\u{A0}' => ' ',
+                '\u{AD}' => '-',
+                'ì'..='ï' => 'ı',
+                'Š' => 'S',
+                'š' => 's',
+                'Ž' => 'Z',
+                'ž' => 'z',
+                'Ÿ' => 'Y',
+                _ => c,
+            };
+            let extra = extra.and_then(|extra| {
+                let index = EXTRA_CHARACTERS.iter().position(|e| *e == c)?;
+                Some(extra[index])
+            });
+            let width = match (extra, base_letter(c) as u32) {
+                (Some(width), _) => width,
+                (None, code @ 32..=126) => table[code as usize - 32],
+                (None, _) => table[0],
+            };
+            (width as f32 * scale).round()
+        };
+        let mut total: f32 = text.chars().map(width).sum();
+        if let Some(kerning) = self.kerning(font) {
+            total += text
+                .chars()
+                .zip(text.chars().skip(1))
+                .filter_map(|pair| kerning.get(&pair))
+                .sum::<f32>();
+        }
+        Some(total * size / 1000.0)
+    }
+}
+
+type KerningPairs = HashMap<(char, char), f32>;
+
+fn standard_kerning(font: &str) -> Option<&'static KerningPairs> {
+    static KERNING: OnceLock<HashMap<&str, KerningPairs>> = OnceLock::new();
+    let tables = KERNING.get_or_init(|| {
+        let table = |pairs: &[(char, char, i16)], scale: f32| -> KerningPairs {
+            pairs
+                .iter()
+                .map(|(left, right, kern)| ((*left, *right), (*kern as f32 * scale).round()))
+                .collect()
+        };
+        HashMap::from([
+            ("Helvetica", table(&HELVETICA_KERNING, 1.0)),
+            ("Helvetica-Oblique", table(&HELVETICA_KERNING, 1.0)),
+            ("Helvetica-Narrow", table(&HELVETICA_KERNING, NARROW)),
+            (
+                "Helvetica-Narrow-Oblique",
+                table(&HELVETICA_KERNING, NARROW),
+            ),
+            ("Times-Roman", table(&TIMES_ROMAN_KERNING, 1.0)),
+        ])
+    });
+    tables.get(font)
+}
+
+pub(crate) fn decorations(metrics: Option<&FontMetrics>, font: &str) -> Decorations {
+    metrics
+        .map(|metrics| metrics.decorations(font))
+        .unwrap_or_else(|| FontMetrics::new().decorations(font))
+}
+
+// Describes the text shown in a fallback font and the characters no font has, which are shown as
+// .notdef (usually nothing).
+pub(crate) fn glyph_warnings(metrics: Option<&FontMetrics>, text: &str, font: &str) -> Vec<String> {
+    let default = FontMetrics::new();
+    let metrics = metrics.unwrap_or(&default);
+    let mut warnings = Vec::new();
+    for (run_font, run) in metrics.runs(font, text) {
+        if run_font != font {
+            warnings.push(format!(
+                "{} is substituted for {} to show \"{}\".",
+                run_font,
+                font,
+                run.trim()
+            ));
+        }
+        let missing: String = run
+            .chars()
+            .filter(|c| !c.is_whitespace() && !metrics.has_glyph(run_font, *c))
+            .collect();
+        if !missing.is_empty() {
+            warnings.push(format!("{} has no glyphs for \"{}\".", run_font, missing));
+        }
+    }
+    warnings
+}
+
+// Measures text for layout. Fonts without metrics are measured as Helvetica.
+pub(crate) fn measure_text(
+    metrics: Option<&FontMetrics>,
+    text: &str,
+    font: &str,
+    size: f32,
+) -> f32 {
+    let default = FontMetrics::unkerned();
+    let metrics = metrics.unwrap_or(&default);
+    metrics
+        .measure(text, font, size)
+        .or_else(|| metrics.measure(text, Font::Helvetica, size))
+        .unwrap_or(0.0)
+}
@@ -0,0 +1,141 @@
+pub enum CheckDigit {
+    None, // Default
+    Luhn,
+    Mod10, // GS1 weights (3, 1)
+    Mod7,
+}
+
+impl CheckDigit {
+    fn compute(&self, number: u64) -> Option<u64> {
+        let digits: Vec<u64> = number
+            .to_string()
+            .bytes()
+            .map(|b| (b - b'0') as u64)
+            .collect();
+        match self {
+            CheckDigit::None => None,
+            CheckDigit::Luhn => {
+                let sum: u64 = digits
+                    .iter()
+                    .rev()
+                    .enumerate()
+                    .map(|(i, d)| {
+                        if i % 2 == 0 {
+                            let doubled = d * 2;
+                            if doubled > 9 {
+                                doubled - 9
+                            } else {
+                                doubled
+                            }
+                        } else {
+                            *d
+                        }
+                    })
+                    .sum();
+                Some((10 - sum % 10) % 10)
+            }
+            CheckDigit::Mod10 => {
+                let sum: u64 = digits
+                    .iter()
+                    .rev()
+                    .enumerate()
+                    .map(|(i, d)| if i % 2 == 0 { d * 3 } else { *d })
+                    .sum();
+                Some((10 - sum % 10) % 10)
+            }
+            CheckDigit::Mod7 => Some(number % 7),
+        }
+    }
+}
+
+// Generates serial numbers, eg: for ticket stock, and records the range that was issued.
+pub struct Numbering {
+    next: u64,
+    step: u64,
+    prefix: String,
+    suffix: String,
+    padding: usize,
+    check_digit: CheckDigit,
+    issued: Option<(u64, u64)>,
+    count: usize,
+}
+
+impl Numbering {
+    pub fn new(start: u64) -> Self {
+        Numbering {
+            next: start,
+            step: 1,
+            prefix: String::new(),
+            suffix: String::new(),
+            padding: 0,
+            check_digit: CheckDigit::None,
+            issued: None,
+            count: 0,
+        }
+    }
+
+    pub fn step(mut self, step: u64) -> Self {
+        self.step = step.max(1);
+        self
+    }
+
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    pub fn suffix(mut self, suffix: &str) -> Self {
+        self.suffix = suffix.to_string();
+        self
+    }
+
+    // Pads the number (not including the check digit) with leading zeros.
+    pub fn padding(mut self, width: usize) -> Self {
+        self.padding = width;
+        self
+    }
+
+    pub fn check_digit(mut self, check_digit: CheckDigit) -> Self {
+        self.check_digit = check_digit;
+        self
+    }
+
+    pub fn format(&self, number: u64) -> String {
+        let check = match self.check_digit.compute(number) {
+            Some(digit) => digit.to_string(),
+            None => String::new(),
+        };
+        format!(
+            "{}{:0width$}{}{}",
+            self.prefix,
+            number,
+            check,
+            self.suffix,
+            width = self.padding
+        )
+    }
+
+    // The first and last numbers issued so far.
+    pub fn issued(&self) -> Option<(u64, u64)> {
+        self.issued
+    }
+
+    pub fn issued_count(&self) -> usize {
+        self.count
+    }
+}
+
+impl Iterator for Numbering {
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let number = self.next;
+        self.next = number.checked_add(self.step)?;
+        self.issued = Some(match self.issued {
+            Some((first, _)) => (first, number),
+            None => (number, number),
+        });
+        self.count += 1;
+        Some(self.format(number))
+    }
+}
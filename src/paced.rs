@@ -0,0 +1,122 @@
+use std::{
+    io::{Error, ErrorKind, Write},
+    thread,
+    time::{Duration, Instant},
+};
+
+// Writes to a slow sink, eg: a socket to a network printer with a small input buffer, in chunks
+// at a limited rate. Writes the sink can't accept yet are retried after a delay.
+pub struct PacedWriter<W: Write> {
+    sink: W,
+    chunk_size: usize,
+    rate: usize,
+    attempts: u32,
+    delay: Duration,
+    started: Option<Instant>,
+    sent: usize,
+    pending: Option<Error>,
+}
+
+impl<W: Write> PacedWriter<W> {
+    pub fn new(sink: W) -> Self {
+        PacedWriter {
+            sink,
+            chunk_size: 4096,
+            rate: 0,
+            attempts: 50,
+            delay: Duration::from_millis(100),
+            started: None,
+            sent: 0,
+            pending: None,
+        }
+    }
+
+    // The most bytes written to the sink at once, 4096 by default.
+    pub fn chunk_size(mut self, bytes: usize) -> Self {
+        self.chunk_size = bytes.max(1);
+        self
+    }
+
+    // Limits the average rate data is written at. Unlimited by default.
+    pub fn rate(mut self, bytes_per_second: usize) -> Self {
+        self.rate = bytes_per_second;
+        self
+    }
+
+    // How often a write that would block or timed out is retried, and the delay before each
+    // retry. 50 attempts 100ms apart by default.
+    pub fn retry(mut self, attempts: u32, delay: Duration) -> Self {
+        self.attempts = attempts;
+        self.delay = delay;
+        self
+    }
+
+    pub fn into_inner(self) -> W {
+        self.sink
+    }
+
+    // Sleeps until the data sent so far is within the rate.
+    fn pace(&mut self) {
+        let Some(started) = self.started.filter(|_| self.rate > 0) else {
+            return;
+        };
+        let due = Duration::from_secs_f64(self.sent as f64 / self.rate as f64);
+        if let Some(wait) = due.checked_sub(started.elapsed()) {
+            thread::sleep(wait);
+        }
+    }
+}
+
+// When the sink fails after part of the buffer was written, the part written is reported and the
+// error is kept and returned by the next write or flush.
+impl<W: Write> Write for PacedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        if let Some(error) = self.pending.take() {
+            return Err(error);
+        }
+        self.started.get_or_insert_with(Instant::now);
+        let mut written = 0;
+        let mut retries = 0;
+        while written < buf.len() {
+            let end = (written + self.chunk_size).min(buf.len());
+            let error = match self.sink.write(&buf[written..end]) {
+                Ok(0) => Error::new(ErrorKind::WriteZero, "The sink stopped accepting data."),
+                Ok(count) => {
+                    written += count;
+                    self.sent += count;
+                    retries = 0;
+                    match self.sink.flush() {
+                        Ok(()) => {
+                            self.pace();
+                            continue;
+                        }
+                        Err(error) => error,
+                    }
+                }
+                Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                Err(error)
+                    if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut)
+                        && retries < self.attempts =>
+                {
+                    retries += 1;
+                    thread::sleep(self.delay);
+                    continue;
+                }
+                Err(error) => error,
+            };
+            if written == 0 {
+                return Err(error);
+            }
+            self.pending = Some(error);
+            return Ok(written);
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        if let Some(error) = self.pending.take() {
+            return Err(error);
+        }
+        self.sink.flush()
+    }
+}
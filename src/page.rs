@@ -1,11 +1,33 @@
-use std::io::{BufWriter, Error, Write};
+use std::{
+    collections::{HashMap, HashSet},
+    io::{BufWriter, Error, ErrorKind, Write},
+};
 
-use crate::{DocumentType, Fabricate, Serialize};
+use crate::{
+    encoding, ChangeBar, ColorPatches, Condition, DocumentType, Fabricate, FinishingMarks,
+    Orientation, Procedure, RevisionCloud, RevisionMark, Serialize, SlugLine,
+};
+
+const PAGE_SAVE_THRESHOLD: usize = 65536;
+
+struct ConditionalBlock {
+    start: usize,
+    end: usize,
+    condition: Condition,
+}
 
 pub struct Page {
     width: i32,
     height: i32,
     buffer: Vec<u8>,
+    // Each with the conditional block it was found in, if any.
+    warnings: Vec<(Option<usize>, String)>,
+    conditional: Vec<ConditionalBlock>,
+    regions: HashMap<String, (f32, f32, f32, f32)>,
+    background: Option<String>,
+    setup: Vec<String>,
+    orientation: Orientation,
+    trim_box: Option<(f32, f32, f32, f32)>,
 }
 
 impl Page {
@@ -14,12 +36,197 @@ impl Page {
             width: width.max(1),
             height: height.max(1),
             buffer: Vec::new(),
+            warnings: Vec::new(),
+            conditional: Vec::new(),
+            regions: HashMap::new(),
+            background: None,
+            setup: Vec::new(),
+            orientation: Orientation::Portrait,
+            trim_box: None,
         }
     }
 
+    pub(crate) fn size(&self) -> (i32, i32) {
+        (self.width, self.height)
+    }
+
     pub fn add<T: Serialize>(&mut self, item: &T) -> Result<(), Error> {
         self.buffer
             .write_all(item.to_postscript_string().as_bytes())?;
+        self.warnings
+            .extend(item.warnings().into_iter().map(|warning| (None, warning)));
+        Ok(())
+    }
+
+    // The item's warnings are only reported for pages it is rendered on.
+    pub fn add_if<T: Serialize>(&mut self, condition: Condition, item: &T) -> Result<(), Error> {
+        let start = self.buffer.len();
+        self.buffer
+            .write_all(item.to_postscript_string().as_bytes())?;
+        let block = self.conditional.len();
+        self.warnings.extend(
+            item.warnings()
+                .into_iter()
+                .map(|warning| (Some(block), warning)),
+        );
+        self.conditional.push(ConditionalBlock {
+            start,
+            end: self.buffer.len(),
+            condition,
+        });
+        Ok(())
+    }
+
+    // The name is cleaned up the same way as by Form::new so it matches the form's definition.
+    pub fn set_background(&mut self, form_name: &str) -> Result<(), Error> {
+        let form_name = encoding::name_text(form_name);
+        if form_name.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Background form name can't be empty.",
+            ));
+        }
+        self.background = Some(form_name);
+        Ok(())
+    }
+
+    // A landscape page keeps the portrait media size so it feeds and binds like the rest of the
+    // document. Its content is drawn with the width and height swapped and rotated onto the page.
+    pub fn set_orientation(&mut self, orientation: Orientation) {
+        self.orientation = orientation;
+    }
+
+    // The finished size of the page once it's trimmed, in the same coordinates as the page content.
+    // The rest of the page is the slug area.
+    pub fn set_trim_box(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.trim_box = Some((x, y, width.max(0.0), height.max(0.0)));
+    }
+
+    // Places the patches in the slug area below the trim box, aligned with its left edge.
+    pub fn add_color_patches(&mut self, patches: &ColorPatches) -> Result<(), Error> {
+        let (x, y, _, _) = self.trim_box.ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                "Color patches require the page to have a trim box.",
+            )
+        })?;
+        let (width, _) = self.content_size();
+        if patches.size() > y || patches.width() > width - x {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Color patches don't fit in the slug area below the trim box.",
+            ));
+        }
+        if !self.buffer.is_empty() && !self.buffer.ends_with(b"\n") {
+            self.buffer.push(b'\n');
+        }
+        self.buffer
+            .write_all(patches.place(x, (y - patches.size()) / 2.0).as_bytes())?;
+        Ok(())
+    }
+
+    // Places the slug line above or below the trim box, aligned with its left edge.
+    pub fn add_slug_line(&mut self, slug: &SlugLine) -> Result<(), Error> {
+        let (x, y, _, trim_height) = self.trim_box.ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                "A slug line requires the page to have a trim box.",
+            )
+        })?;
+        let (_, height) = self.content_size();
+        let (bottom, slug_height) = if slug.is_top() {
+            (y + trim_height, height - y - trim_height)
+        } else {
+            (0.0, y)
+        };
+        if slug.size() > slug_height {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "The slug line doesn't fit in the slug area.",
+            ));
+        }
+        self.add(&slug.text(x, bottom + (slug_height - slug.size()) / 2.0))
+    }
+
+    pub fn add_finishing_marks(&mut self, marks: &FinishingMarks) -> Result<(), Error> {
+        let (x, y, trim_width, trim_height) = self.trim_box.ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                "Finishing marks require the page to have a trim box.",
+            )
+        })?;
+        let (width, height) = self.content_size();
+        let slug = marks.slug();
+        if !marks.fits(trim_width, trim_height)
+            || slug > x.min(y)
+            || slug > width - x - trim_width
+            || slug > height - y - trim_height
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "The finishing marks don't fit the trim box and slug area.",
+            ));
+        }
+        self.buffer
+            .write_all(marks.place(x, y, trim_width, trim_height).as_bytes())?;
+        Ok(())
+    }
+
+    // The page size in the coordinates of its content, which are rotated on landscape pages.
+    fn content_size(&self) -> (f32, f32) {
+        match self.orientation {
+            Orientation::Portrait => (self.width as f32, self.height as f32),
+            Orientation::Landscape => (self.height as f32, self.width as f32),
+        }
+    }
+
+    pub fn add_procedure(&mut self, procedure: Procedure) {
+        self.setup.push(procedure.body);
+    }
+
+    pub fn add_setup(&mut self, code: &str) {
+        self.setup.push(code.to_string());
+    }
+
+    pub fn tag_region(&mut self, tag: &str, x: f32, y: f32, width: f32, height: f32) {
+        self.regions.insert(tag.to_string(), (x, y, width, height));
+    }
+
+    pub fn mark_revision(&mut self, tag: &str, mark: RevisionMark) -> Result<(), Error> {
+        let (x, y, width, height) = *self.regions.get(tag).ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                format!("Region '{}' has not been tagged.", tag),
+            )
+        })?;
+        match mark {
+            RevisionMark::Cloud => self.add(&RevisionCloud::new(x, y, width, height)),
+            // Change bars sit in the left margin, halfway between the page edge and the region.
+            RevisionMark::ChangeBar => self.add(&ChangeBar::new(x / 2.0, y, height)),
+        }
+    }
+
+    fn write_buffer<W: Write>(
+        &self,
+        flags: &HashSet<String>,
+        writer: &mut BufWriter<W>,
+    ) -> Result<(), Error> {
+        let landscape = matches!(self.orientation, Orientation::Landscape);
+        if landscape {
+            writeln!(writer, "gsave {} 0 translate 90 rotate", self.width)?;
+        }
+        let mut cursor = 0;
+        for block in &self.conditional {
+            writer.write_all(&self.buffer[cursor..block.start])?;
+            if block.condition.is_met(flags) {
+                writer.write_all(&self.buffer[block.start..block.end])?;
+            }
+            cursor = block.end;
+        }
+        writer.write_all(&self.buffer[cursor..])?;
+        if landscape {
+            writer.write_all("grestore\n".as_bytes())?;
+        }
         Ok(())
     }
 }
@@ -28,24 +235,80 @@ impl Fabricate for Page {
     fn fabricate<W: Write>(
         &self,
         doc_type: &DocumentType,
+        flags: &HashSet<String>,
         writer: &mut BufWriter<W>,
     ) -> Result<(), Error> {
         match doc_type {
             DocumentType::PS => {
-                write!(
+                writeln!(
                     writer,
-                    r#"%%PageBoundingBox: 0 0 {} {}
-<< /PageSize [{} {}] >> setpagedevice
-"#,
-                    self.width, self.height, self.width, self.height
+                    "%%PageBoundingBox: 0 0 {} {}",
+                    self.width, self.height
                 )?;
-                writer.write_all(&self.buffer)?;
+                if let Orientation::Landscape = self.orientation {
+                    writer.write_all("%%PageOrientation: Landscape\n".as_bytes())?;
+                }
+                // Procedures and setup code that only this page needs are discarded once the page
+                // has been shown.
+                let setup = !self.setup.is_empty();
+                if setup || self.background.is_some() {
+                    write!(
+                        writer,
+                        "%%BeginPageSetup\n<< /PageSize [{} {}] >> setpagedevice\n",
+                        self.width, self.height
+                    )?;
+                    if setup {
+                        writer.write_all("/pslib_pagesetup save def\n".as_bytes())?;
+                        for code in &self.setup {
+                            writeln!(writer, "{}", code)?;
+                        }
+                    }
+                    if let Some(form_name) = &self.background {
+                        writeln!(writer, "{} execform", form_name)?;
+                    }
+                    writer.write_all("%%EndPageSetup\n".as_bytes())?;
+                } else {
+                    writeln!(
+                        writer,
+                        "<< /PageSize [{} {}] >> setpagedevice",
+                        self.width, self.height
+                    )?;
+                }
+                // Large pages are wrapped in save/restore so the VM they use is reclaimed before the
+                // next page rather than accumulating on Level 1 interpreters.
+                let wrap = encoding::estimate_vm(&self.buffer) > PAGE_SAVE_THRESHOLD;
+                if wrap {
+                    writer.write_all("/pslib_pagesave save def\n".as_bytes())?;
+                }
+                self.write_buffer(flags, writer)?;
+                if wrap {
+                    writer.write_all("pslib_pagesave restore\n".as_bytes())?;
+                }
                 writer.write_all("showpage\n".as_bytes())?;
+                if setup {
+                    writer.write_all("pslib_pagesetup restore\n".as_bytes())?;
+                }
             }
             _ => {
-                writer.write_all(&self.buffer)?;
+                for code in &self.setup {
+                    writeln!(writer, "{}", code)?;
+                }
+                if let Some(form_name) = &self.background {
+                    writeln!(writer, "{} execform", form_name)?;
+                }
+                self.write_buffer(flags, writer)?;
             }
         }
         Ok(())
     }
+
+    fn warnings(&self, flags: &HashSet<String>) -> Vec<String> {
+        self.warnings
+            .iter()
+            .filter(|(block, _)| {
+                block.is_none_or(|block| self.conditional[block].condition.is_met(flags))
+            })
+            .map(|(_, warning)| warning.clone())
+            .collect()
+    }
 }
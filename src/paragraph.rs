@@ -0,0 +1,482 @@
+use crate::{
+    metrics::{decorations, glyph_warnings, measure_text},
+    text::{fit_size, font_name, visual_order, write_decorated, write_font, Spacing, TextColor},
+    Font, FontMetrics, GroupItem, Serialize,
+};
+use std::fmt::Write;
+
+pub enum TabAlignment {
+    Left,
+    Right,
+    Center,
+    // Aligns the first decimal point in the text on the stop, or its end when it has none.
+    Decimal,
+}
+
+pub struct TabStop {
+    position: f32,
+    alignment: TabAlignment,
+    leader: Option<char>,
+}
+
+impl TabStop {
+    // The position is measured from the left edge of the column.
+    pub fn new(position: f32, alignment: TabAlignment) -> Self {
+        TabStop {
+            position,
+            alignment,
+            leader: None,
+        }
+    }
+
+    // Fills the space before the text with a repeated character, eg: dots in a table of contents.
+    pub fn leader(mut self, leader: char) -> Self {
+        self.leader = Some(leader);
+        self
+    }
+}
+
+// Finds where words may be broken across lines, eg: from hyphenation patterns for a language.
+// Closures taking a word and returning its break points can be used directly.
+pub trait Hyphenator {
+    // The byte offsets in the word where it may be broken with a hyphen.
+    fn hyphenate(&self, word: &str) -> Vec<usize>;
+}
+
+impl<F: Fn(&str) -> Vec<usize>> Hyphenator for F {
+    fn hyphenate(&self, word: &str) -> Vec<usize> {
+        self(word)
+    }
+}
+
+pub struct Paragraph {
+    text: String,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    font: String,
+    size: f32,
+    leading: Option<f32>,
+    color: TextColor,
+    metrics: Option<FontMetrics>,
+    tracking: f32,
+    word_spacing: f32,
+    underline: bool,
+    strikethrough: bool,
+    columns: usize,
+    gutter: f32,
+    column_rule: f32,
+    shrink_to_fit: bool,
+    min_size: f32,
+    tab_stops: Vec<TabStop>,
+    hyphenator: Option<Box<dyn Hyphenator>>,
+}
+
+impl Paragraph {
+    // The box is positioned by its bottom left corner and the first line is set at the top.
+    pub fn new(text: &str, x: f32, y: f32, width: f32, height: f32) -> Self {
+        Paragraph {
+            text: text.to_string(),
+            x,
+            y,
+            width: width.max(0.0),
+            height: height.max(0.0),
+            font: "Helvetica".to_string(),
+            size: 12.0,
+            leading: None,
+            color: TextColor::Gray(0.0),
+            metrics: None,
+            tracking: 0.0,
+            word_spacing: 0.0,
+            underline: false,
+            strikethrough: false,
+            columns: 1,
+            gutter: 0.0,
+            column_rule: 0.0,
+            shrink_to_fit: false,
+            min_size: 6.0,
+            tab_stops: Vec::new(),
+            hyphenator: None,
+        }
+    }
+
+    pub fn font<F: Into<Font>>(mut self, font: F, size: f32) -> Self {
+        self.font = font_name(font);
+        self.size = size.max(0.0);
+        self
+    }
+
+    // The distance between baselines, 120% of the font size by default.
+    pub fn leading(mut self, leading: f32) -> Self {
+        self.leading = Some(leading.max(0.0));
+        self
+    }
+
+    pub fn tracking(mut self, tracking: f32) -> Self {
+        self.tracking = tracking;
+        self
+    }
+
+    pub fn word_spacing(mut self, word_spacing: f32) -> Self {
+        self.word_spacing = word_spacing;
+        self
+    }
+
+    // Required to wrap text set in an embedded font, and to apply its kerning.
+    pub fn metrics(mut self, metrics: &FontMetrics) -> Self {
+        self.metrics = Some(metrics.clone());
+        self
+    }
+
+    // Splits the box into columns separated by the gutter. Text fills each column from top to
+    // bottom before flowing into the next.
+    pub fn columns(mut self, count: usize, gutter: f32) -> Self {
+        self.columns = count.max(1);
+        self.gutter = gutter.max(0.0);
+        self
+    }
+
+    // Draws a line of the given width down the middle of each gutter.
+    pub fn column_rule(mut self, width: f32) -> Self {
+        self.column_rule = width.max(0.0);
+        self
+    }
+
+    fn column_width(&self) -> f32 {
+        let gutters = self.gutter * (self.columns - 1) as f32;
+        ((self.width - gutters) / self.columns as f32).max(0.0)
+    }
+
+    // Reduces the font size, in steps of half a point down to the minimum size, until all of the
+    // text fits in the box.
+    pub fn shrink_to_fit(mut self) -> Self {
+        self.shrink_to_fit = true;
+        self
+    }
+
+    // The smallest size text is shrunk to, 6pt by default.
+    pub fn min_size(mut self, size: f32) -> Self {
+        self.min_size = size.max(0.5);
+        self
+    }
+
+    // Text after a tab is set at the next stop past the end of the text before it, or a space
+    // after it when there are no more stops. Lines with tabs are not wrapped. Without stops tabs
+    // are treated as spaces.
+    pub fn tab_stop(mut self, stop: TabStop) -> Self {
+        self.tab_stops.push(stop);
+        self.tab_stops
+            .sort_by(|a, b| a.position.total_cmp(&b.position));
+        self
+    }
+
+    // Words that don't fit at the end of a line are broken at the last break point that fits,
+    // with a hyphen added, instead of being moved to the next line whole.
+    pub fn hyphenator<H: Hyphenator + 'static>(mut self, hyphenator: H) -> Self {
+        self.hyphenator = Some(Box::new(hyphenator));
+        self
+    }
+
+    pub fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    pub fn strikethrough(mut self) -> Self {
+        self.strikethrough = true;
+        self
+    }
+
+    pub fn fill_gray(mut self, gray: f32) -> Self {
+        self.color = TextColor::Gray(gray.clamp(0.0, 1.0));
+        self
+    }
+
+    pub fn fill_rgb(mut self, r: f32, g: f32, b: f32) -> Self {
+        self.color = TextColor::Rgb([r.clamp(0.0, 1.0), g.clamp(0.0, 1.0), b.clamp(0.0, 1.0)]);
+        self
+    }
+
+    pub fn fill_cmyk(mut self, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.color = TextColor::Cmyk([
+            c.clamp(0.0, 1.0),
+            m.clamp(0.0, 1.0),
+            y.clamp(0.0, 1.0),
+            k.clamp(0.0, 1.0),
+        ]);
+        self
+    }
+
+    fn spacing(&self, size: f32) -> Spacing<'_> {
+        Spacing {
+            size,
+            tracking: self.tracking,
+            word_spacing: self.word_spacing,
+            font: &self.font,
+            metrics: self.metrics.as_ref(),
+        }
+    }
+
+    fn measure(&self, text: &str, size: f32) -> f32 {
+        measure_text(self.metrics.as_ref(), text, &self.font, size) + self.spacing(size).width(text)
+    }
+
+    // A leading that was set is scaled with the font when the text is shrunk to fit.
+    fn leading_for(&self, size: f32) -> f32 {
+        match self.leading {
+            Some(leading) => leading * size / self.size,
+            None => size * 1.2,
+        }
+    }
+
+    fn fitted_size(&self) -> f32 {
+        if self.shrink_to_fit {
+            fit_size(self.size, self.min_size, |size| self.fits(size))
+        } else {
+            self.size
+        }
+    }
+
+    fn lines_per_column(&self, size: f32) -> usize {
+        let leading = self.leading_for(size);
+        let top = self.y + self.height - size;
+        if top < self.y {
+            0
+        } else if leading > 0.0 {
+            ((top - self.y) / leading) as usize + 1
+        } else {
+            usize::MAX
+        }
+    }
+
+    fn fits(&self, size: f32) -> bool {
+        let lines = self.wrap(size);
+        lines.len() <= self.lines_per_column(size).saturating_mul(self.columns)
+            && lines.iter().all(|line| {
+                self.segments(line, size)
+                    .last()
+                    .map_or(0.0, |(x, text, _)| x + self.measure(text, size))
+                    <= self.column_width()
+            })
+    }
+
+    // Splits a line at its tabs into pieces of text and leaders, each with its offset from the
+    // left edge of the column.
+    fn segments(&self, line: &str, size: f32) -> Vec<(f32, String, bool)> {
+        let mut parts = line.split('\t');
+        let first = parts.next().unwrap_or_default();
+        let mut pen = self.measure(first, size);
+        let mut segments = vec![(0.0, first.to_string(), false)];
+        for part in parts {
+            let width = self.measure(part, size);
+            let Some(stop) = self.tab_stops.iter().find(|stop| stop.position > pen) else {
+                let start = pen + self.measure(" ", size);
+                segments.push((start, part.to_string(), false));
+                pen = start + width;
+                continue;
+            };
+            let before = match stop.alignment {
+                TabAlignment::Left => 0.0,
+                TabAlignment::Right => width,
+                TabAlignment::Center => width / 2.0,
+                TabAlignment::Decimal => part
+                    .find('.')
+                    .map_or(width, |i| self.measure(&part[..i], size)),
+            };
+            let start = (stop.position - before).max(pen);
+            if let Some(leader) = stop.leader {
+                // Whole leader characters only, ending where the text starts.
+                let leader = leader.to_string();
+                let leader_width = self.measure(&leader, size);
+                let count = if leader_width > 0.0 {
+                    ((start - pen) / leader_width) as usize
+                } else {
+                    0
+                };
+                if count > 0 {
+                    let x = start - count as f32 * leader_width;
+                    segments.push((x, leader.repeat(count), true));
+                }
+            }
+            segments.push((start, part.to_string(), false));
+            pen = start + width;
+        }
+        segments
+    }
+
+    // Breaks the text into lines at spaces and newlines, and within words at the hyphenator's
+    // break points. Words wider than the box are broken between characters.
+    fn wrap(&self, size: f32) -> Vec<String> {
+        let join = |line: &str, word: &str| {
+            if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", line, word)
+            }
+        };
+        let mut lines = Vec::new();
+        for paragraph in self.text.lines() {
+            if !self.tab_stops.is_empty() && paragraph.contains('\t') {
+                lines.push(paragraph.to_string());
+                continue;
+            }
+            let mut line = String::new();
+            for word in paragraph.split_whitespace() {
+                let points = self.break_points(word);
+                let mut start = 0;
+                loop {
+                    let candidate = join(&line, &word[start..]);
+                    if self.measure(&candidate, size) <= self.column_width() {
+                        line = candidate;
+                        break;
+                    }
+                    // The last break point where the start of the word and a hyphen still fit.
+                    let split = points
+                        .iter()
+                        .rev()
+                        .take_while(|point| **point > start)
+                        .find(|point| {
+                            let candidate = join(&line, &format!("{}-", &word[start..**point]));
+                            self.measure(&candidate, size) <= self.column_width()
+                        });
+                    if let Some(split) = split {
+                        lines.push(join(&line, &format!("{}-", &word[start..*split])));
+                        line = String::new();
+                        start = *split;
+                        continue;
+                    }
+                    if !line.is_empty() {
+                        lines.push(line);
+                        line = String::new();
+                        continue;
+                    }
+                    for c in word[start..].chars() {
+                        let candidate = format!("{}{}", line, c);
+                        if !line.is_empty() && self.measure(&candidate, size) > self.column_width()
+                        {
+                            lines.push(line);
+                            line = c.to_string();
+                        } else {
+                            line = candidate;
+                        }
+                    }
+                    break;
+                }
+            }
+            lines.push(line);
+        }
+        lines
+    }
+
+    fn break_points(&self, word: &str) -> Vec<usize> {
+        let Some(hyphenator) = &self.hyphenator else {
+            return Vec::new();
+        };
+        let mut points = hyphenator.hyphenate(word);
+        points.retain(|point| *point > 0 && *point < word.len() && word.is_char_boundary(*point));
+        points.sort_unstable();
+        points
+    }
+}
+
+impl Serialize for Paragraph {
+    fn to_postscript_string(&self) -> String {
+        let mut result = String::new();
+
+        if self.text.is_empty() || self.size == 0.0 || self.font.is_empty() {
+            return result;
+        }
+
+        let size = self.fitted_size();
+
+        // Lines that don't fit in the last column are not shown.
+        let leading = self.leading_for(size);
+        let top = self.y + self.height - size;
+        let per_column = self.lines_per_column(size);
+        let column_width = self.column_width();
+        let lines: Vec<(f32, f32, String)> = self
+            .wrap(size)
+            .into_iter()
+            .enumerate()
+            .take(per_column.saturating_mul(self.columns))
+            .map(|(i, line)| {
+                let column = (i / per_column) as f32;
+                let x = self.x + column * (column_width + self.gutter);
+                (x, top - (i % per_column) as f32 * leading, line)
+            })
+            .filter(|(_, _, line)| !line.is_empty())
+            .collect();
+        if lines.is_empty() {
+            return result;
+        }
+
+        result.push_str("gsave ");
+        self.color.write(&mut result);
+        let spacing = self.spacing(size);
+        write_font(
+            &mut result,
+            &self.font,
+            size,
+            &self.text,
+            spacing.glyphs(&self.font).is_some(),
+        );
+        let decorations = decorations(self.metrics.as_ref(), &self.font).lines(
+            size,
+            self.underline,
+            self.strikethrough,
+        );
+        for (x, baseline, line) in lines {
+            for (offset, text, leader) in self.segments(&line, size) {
+                if text.is_empty() {
+                    continue;
+                }
+                write!(&mut result, "{} {} moveto ", x + offset, baseline).unwrap();
+                write_decorated(
+                    &mut result,
+                    &visual_order(&text),
+                    &spacing,
+                    baseline,
+                    if leader { &[] } else { &decorations },
+                );
+            }
+        }
+        if self.column_rule > 0.0 {
+            for column in 1..self.columns {
+                let x = self.x + column as f32 * (column_width + self.gutter) - self.gutter / 2.0;
+                write!(
+                    &mut result,
+                    "newpath {} {} moveto 0 {} rlineto {} setlinewidth stroke ",
+                    x, self.y, self.height, self.column_rule
+                )
+                .unwrap();
+            }
+        }
+        result.push_str("grestore ");
+
+        result
+    }
+
+    fn warnings(&self) -> Vec<String> {
+        if self.text.is_empty() || self.size == 0.0 || self.font.is_empty() {
+            return Vec::new();
+        }
+        let mut warnings = glyph_warnings(self.metrics.as_ref(), &self.text, &self.font);
+        let size = self.fitted_size();
+        let hidden = self
+            .wrap(size)
+            .into_iter()
+            .skip(self.lines_per_column(size).saturating_mul(self.columns))
+            .filter(|line| !line.is_empty())
+            .count();
+        if hidden > 0 {
+            warnings.push(format!(
+                "Paragraph overflows its columns; {} lines are not shown.",
+                hidden
+            ));
+        }
+        warnings
+    }
+}
+
+impl GroupItem for Paragraph {}
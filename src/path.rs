@@ -0,0 +1,379 @@
+use crate::{
+    style::{
+        bounds, write_corner, write_paint, FillStyle, HatchStyle, ShadowStyle, StrokeStyle,
+        TransformStyle,
+    },
+    svg_path, FillRule, Group, GroupItem, HatchPattern, LineCap, LineJoin, PaintOrder, Serialize,
+    Transform, TransformOrigin,
+};
+use std::{fmt::Write, io::Error};
+
+pub(crate) enum PathSegment {
+    MoveTo(f32, f32),
+    LineTo(f32, f32),
+    CurveTo(f32, f32, f32, f32, f32, f32),
+    QuadTo(f32, f32, f32, f32),
+    ArcTo(f32, f32, f32, f32, f32),
+    Close,
+}
+
+pub struct Path {
+    segments: Vec<PathSegment>,
+    fill: FillStyle,
+    hatch: HatchStyle,
+    stroke: StrokeStyle,
+    shadow: ShadowStyle,
+    transform: TransformStyle,
+    corner_radius: f32,
+}
+
+impl Default for Path {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Path {
+    pub fn new() -> Self {
+        Path {
+            segments: Vec::new(),
+            fill: FillStyle::new(),
+            hatch: HatchStyle::new(),
+            stroke: StrokeStyle::new(0.0),
+            shadow: ShadowStyle::new(),
+            transform: TransformStyle::new(),
+            corner_radius: 0.0,
+        }
+    }
+
+    pub fn from_svg_data(data: &str) -> Result<Self, Error> {
+        let mut path = Self::new();
+        path.segments = svg_path::parse(data)?;
+        Ok(path)
+    }
+
+    pub fn move_to(mut self, x: f32, y: f32) -> Self {
+        self.segments.push(PathSegment::MoveTo(x, y));
+        self
+    }
+
+    pub fn line_to(mut self, x: f32, y: f32) -> Self {
+        // A path can't start with lineto as there's no current point yet
+        if self.segments.is_empty() {
+            self.segments.push(PathSegment::MoveTo(x, y));
+        } else {
+            self.segments.push(PathSegment::LineTo(x, y));
+        }
+        self
+    }
+
+    // Curves, arcs and close need a current point, so they're ignored until the path has one
+    pub fn curve_to(mut self, x1: f32, y1: f32, x2: f32, y2: f32, x3: f32, y3: f32) -> Self {
+        if self.segments.is_empty() {
+            return self;
+        }
+        self.segments
+            .push(PathSegment::CurveTo(x1, y1, x2, y2, x3, y3));
+        self
+    }
+
+    pub fn quad_to(mut self, x1: f32, y1: f32, x2: f32, y2: f32) -> Self {
+        if self.segments.is_empty() {
+            return self;
+        }
+        self.segments.push(PathSegment::QuadTo(x1, y1, x2, y2));
+        self
+    }
+
+    pub fn arc_to(mut self, x1: f32, y1: f32, x2: f32, y2: f32, radius: f32) -> Self {
+        if self.segments.is_empty() {
+            return self;
+        }
+        self.segments
+            .push(PathSegment::ArcTo(x1, y1, x2, y2, radius.max(0.0)));
+        self
+    }
+
+    pub fn close(mut self) -> Self {
+        if self.segments.is_empty() {
+            return self;
+        }
+        self.segments.push(PathSegment::Close);
+        self
+    }
+
+    pub fn round_corners(mut self, radius: f32) -> Self {
+        self.corner_radius = radius.max(0.0);
+        self
+    }
+
+    pub fn fill_rgb(mut self, r: f32, g: f32, b: f32) -> Self {
+        self.fill.rgb(r, g, b);
+        self
+    }
+
+    pub fn fill_cmyk(mut self, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.fill.cmyk(c, m, y, k);
+        self
+    }
+
+    pub fn fill_rule(mut self, rule: FillRule) -> Self {
+        self.fill.rule(&rule);
+        self.shadow.rule(&rule);
+        self.hatch.rule(&rule);
+        self
+    }
+
+    pub fn opacity(mut self, opacity: f32) -> Self {
+        self.fill.opacity(opacity);
+        self
+    }
+
+    pub fn backdrop_rgb(mut self, r: f32, g: f32, b: f32) -> Self {
+        self.fill.backdrop_rgb(r, g, b);
+        self
+    }
+
+    pub fn backdrop_cmyk(mut self, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.fill.backdrop_cmyk(c, m, y, k);
+        self
+    }
+
+    pub fn hatch(mut self, pattern: HatchPattern, spacing: f32, width: f32) -> Self {
+        self.hatch.set(pattern, spacing, width);
+        self
+    }
+
+    pub fn hatch_rgb(mut self, r: f32, g: f32, b: f32) -> Self {
+        self.hatch.rgb(r, g, b);
+        self
+    }
+
+    pub fn hatch_cmyk(mut self, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.hatch.cmyk(c, m, y, k);
+        self
+    }
+
+    pub fn stroke_rgb(mut self, width: f32, r: f32, g: f32, b: f32) -> Self {
+        self.stroke.rgb(width, r, g, b);
+        self
+    }
+
+    pub fn stroke_cmyk(mut self, width: f32, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.stroke.cmyk(width, c, m, y, k);
+        self
+    }
+
+    pub fn paint_order(mut self, order: PaintOrder) -> Self {
+        self.stroke.paint_order(order);
+        self
+    }
+
+    pub fn line_cap(mut self, cap: LineCap) -> Self {
+        self.stroke.cap(cap);
+        self
+    }
+
+    pub fn line_join(mut self, join: LineJoin) -> Self {
+        self.stroke.join(join);
+        self
+    }
+
+    pub fn miter_limit(mut self, limit: f32) -> Self {
+        self.stroke.miter_limit(limit);
+        self
+    }
+
+    pub fn shadow_rgb(mut self, dx: f32, dy: f32, r: f32, g: f32, b: f32) -> Self {
+        self.shadow.rgb(dx, dy, r, g, b);
+        self
+    }
+
+    pub fn shadow_cmyk(mut self, dx: f32, dy: f32, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.shadow.cmyk(dx, dy, c, m, y, k);
+        self
+    }
+
+    pub fn scale(mut self, x: f32, y: f32) -> Self {
+        self.transform.scale(x, y);
+        self
+    }
+
+    pub fn flip_horizontal(mut self) -> Self {
+        self.transform.flip_horizontal();
+        self
+    }
+
+    pub fn flip_vertical(mut self) -> Self {
+        self.transform.flip_vertical();
+        self
+    }
+
+    pub fn skew(mut self, x: f32, y: f32) -> Self {
+        self.transform.skew(x, y);
+        self
+    }
+
+    pub fn transform(mut self, matrix: Transform) -> Self {
+        self.transform.matrix(matrix);
+        self
+    }
+
+    pub fn set_orign(mut self, origin: TransformOrigin) -> Self {
+        self.transform.set_origin(origin);
+        self
+    }
+
+    pub fn rotate(mut self, angle: f32) -> Self {
+        self.transform.rotate(angle);
+        self
+    }
+
+    // Checks whether the subpath that starts at `index` is closed.
+    fn is_closed(&self, index: usize) -> bool {
+        for segment in &self.segments[index + 1..] {
+            match segment {
+                PathSegment::MoveTo(..) => return false,
+                PathSegment::Close => return true,
+                _ => {}
+            }
+        }
+        false
+    }
+
+    fn points(&self) -> Vec<(f32, f32)> {
+        let mut points = Vec::new();
+        for segment in &self.segments {
+            match *segment {
+                PathSegment::MoveTo(x, y) | PathSegment::LineTo(x, y) => points.push((x, y)),
+                PathSegment::CurveTo(x1, y1, x2, y2, x3, y3) => {
+                    points.extend_from_slice(&[(x1, y1), (x2, y2), (x3, y3)])
+                }
+                PathSegment::QuadTo(x1, y1, x2, y2) | PathSegment::ArcTo(x1, y1, x2, y2, _) => {
+                    points.extend_from_slice(&[(x1, y1), (x2, y2)])
+                }
+                PathSegment::Close => {}
+            }
+        }
+        points
+    }
+
+    pub(crate) fn write_outline(&self, path: &mut String) {
+        // With rounded corners every vertex between two straight segments becomes an arcto. Closed
+        // subpaths start halfway along their first edge so the starting vertex is rounded too.
+        let round = self.corner_radius > 0.0;
+        let mut start = (0.0, 0.0);
+        let mut start_rounded = false;
+        let mut current = (0.0, 0.0);
+        path.push_str("newpath ");
+        for (i, segment) in self.segments.iter().enumerate() {
+            let next = self.segments.get(i + 1);
+            let previous = current;
+            current = match *segment {
+                PathSegment::MoveTo(x, y) | PathSegment::LineTo(x, y) => (x, y),
+                PathSegment::CurveTo(_, _, _, _, x, y) => (x, y),
+                PathSegment::QuadTo(_, _, x, y) | PathSegment::ArcTo(_, _, x, y, _) => (x, y),
+                PathSegment::Close => start,
+            };
+            match segment {
+                PathSegment::MoveTo(x, y) => {
+                    start = (*x, *y);
+                    start_rounded = false;
+                    if let (true, Some(PathSegment::LineTo(nx, ny))) = (round, next) {
+                        if self.is_closed(i) {
+                            start_rounded = true;
+                            write!(path, "{} {} moveto ", (x + nx) / 2.0, (y + ny) / 2.0).unwrap();
+                            continue;
+                        }
+                    }
+                    write!(path, "{} {} moveto ", x, y).unwrap()
+                }
+                PathSegment::LineTo(x, y) => match (round, next) {
+                    (true, Some(PathSegment::LineTo(nx, ny))) => {
+                        write_corner(path, previous, (*x, *y), (*nx, *ny), self.corner_radius)
+                    }
+                    (true, Some(PathSegment::Close)) => {
+                        write_corner(path, previous, (*x, *y), start, self.corner_radius)
+                    }
+                    _ => write!(path, "{} {} lineto ", x, y).unwrap(),
+                },
+                PathSegment::CurveTo(x1, y1, x2, y2, x3, y3) => {
+                    write!(path, "{} {} {} {} {} {} curveto ", x1, y1, x2, y2, x3, y3).unwrap()
+                }
+                PathSegment::QuadTo(x1, y1, x2, y2) => {
+                    write!(path, "{} {} {} {} quadto ", x1, y1, x2, y2).unwrap()
+                }
+                PathSegment::ArcTo(x1, y1, x2, y2, radius) => write!(
+                    path,
+                    "{} {} {} {} {} arcto 4 {{ pop }} repeat ",
+                    x1, y1, x2, y2, radius
+                )
+                .unwrap(),
+                PathSegment::Close => {
+                    if start_rounded {
+                        let first = self.segments[..i]
+                            .iter()
+                            .rev()
+                            .take_while(|segment| !matches!(segment, PathSegment::MoveTo(..)))
+                            .last();
+                        if let Some(PathSegment::LineTo(nx, ny)) = first {
+                            write_corner(path, previous, start, (*nx, *ny), self.corner_radius);
+                        }
+                    }
+                    path.push_str("closepath ")
+                }
+            }
+        }
+    }
+
+    // Writes the outline within the path's transform followed by the paint operators, ignoring
+    // the path's own fill and stroke.
+    pub(crate) fn write_painted(&self, result: &mut String, paint: &str) {
+        if self.segments.is_empty() {
+            return;
+        }
+        let (x, y, width, height) = bounds(&self.points());
+        self.transform.begin(result, x, y, width, height);
+        self.write_outline(result);
+        result.push_str(paint);
+        self.transform.end(result);
+    }
+
+    fn serialize(&self, fill: &FillStyle, stroke: &StrokeStyle) -> String {
+        let mut result = String::new();
+
+        if self.segments.is_empty()
+            || (!fill.is_enabled() && !self.hatch.is_enabled() && !stroke.is_enabled())
+        {
+            return result;
+        }
+
+        let (x, y, width, height) = bounds(&self.points());
+        self.transform.begin(&mut result, x, y, width, height);
+
+        let mut path = String::new();
+        self.write_outline(&mut path);
+        self.shadow.write(&mut result, &path);
+        result.push_str(&path);
+
+        write_paint(&mut result, stroke, &self.stroke, |result| {
+            fill.write_as(result, &self.fill);
+            self.hatch.write(result, x, y, width, height);
+        });
+        self.transform.end(&mut result);
+
+        result
+    }
+}
+
+impl Serialize for Path {
+    fn to_postscript_string(&self) -> String {
+        self.serialize(&self.fill, &self.stroke)
+    }
+}
+
+impl GroupItem for Path {
+    fn to_postscript_string_in(&self, group: &Group) -> String {
+        self.serialize(group.fill_for(&self.fill), group.stroke_for(&self.stroke))
+    }
+}
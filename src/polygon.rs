@@ -0,0 +1,208 @@
+use crate::{
+    style::{
+        bounds, write_paint, write_rounded_vertices, write_vertices, FillStyle, HatchStyle,
+        ShadowStyle, StrokeStyle, TransformStyle,
+    },
+    FillRule, Group, GroupItem, HatchPattern, LanguageLevel, LineCap, LineJoin, PaintOrder,
+    Serialize, Transform, TransformOrigin,
+};
+
+pub struct Polygon {
+    points: Vec<(f32, f32)>,
+    fill: FillStyle,
+    hatch: HatchStyle,
+    stroke: StrokeStyle,
+    shadow: ShadowStyle,
+    transform: TransformStyle,
+    operand_limit: usize,
+    corner_radius: f32,
+}
+
+impl Polygon {
+    pub fn new(points: Vec<(f32, f32)>) -> Self {
+        Polygon {
+            points,
+            fill: FillStyle::new(),
+            hatch: HatchStyle::new(),
+            stroke: StrokeStyle::new(0.0),
+            shadow: ShadowStyle::new(),
+            transform: TransformStyle::new(),
+            operand_limit: LanguageLevel::Level1.operand_stack_limit(),
+            corner_radius: 0.0,
+        }
+    }
+
+    pub fn fill_rgb(mut self, r: f32, g: f32, b: f32) -> Self {
+        self.fill.rgb(r, g, b);
+        self
+    }
+
+    pub fn fill_cmyk(mut self, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.fill.cmyk(c, m, y, k);
+        self
+    }
+
+    pub fn fill_rule(mut self, rule: FillRule) -> Self {
+        self.fill.rule(&rule);
+        self.shadow.rule(&rule);
+        self.hatch.rule(&rule);
+        self
+    }
+
+    pub fn opacity(mut self, opacity: f32) -> Self {
+        self.fill.opacity(opacity);
+        self
+    }
+
+    pub fn backdrop_rgb(mut self, r: f32, g: f32, b: f32) -> Self {
+        self.fill.backdrop_rgb(r, g, b);
+        self
+    }
+
+    pub fn backdrop_cmyk(mut self, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.fill.backdrop_cmyk(c, m, y, k);
+        self
+    }
+
+    pub fn hatch(mut self, pattern: HatchPattern, spacing: f32, width: f32) -> Self {
+        self.hatch.set(pattern, spacing, width);
+        self
+    }
+
+    pub fn hatch_rgb(mut self, r: f32, g: f32, b: f32) -> Self {
+        self.hatch.rgb(r, g, b);
+        self
+    }
+
+    pub fn hatch_cmyk(mut self, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.hatch.cmyk(c, m, y, k);
+        self
+    }
+
+    pub fn stroke_rgb(mut self, width: f32, r: f32, g: f32, b: f32) -> Self {
+        self.stroke.rgb(width, r, g, b);
+        self
+    }
+
+    pub fn stroke_cmyk(mut self, width: f32, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.stroke.cmyk(width, c, m, y, k);
+        self
+    }
+
+    pub fn paint_order(mut self, order: PaintOrder) -> Self {
+        self.stroke.paint_order(order);
+        self
+    }
+
+    pub fn line_cap(mut self, cap: LineCap) -> Self {
+        self.stroke.cap(cap);
+        self
+    }
+
+    pub fn line_join(mut self, join: LineJoin) -> Self {
+        self.stroke.join(join);
+        self
+    }
+
+    pub fn miter_limit(mut self, limit: f32) -> Self {
+        self.stroke.miter_limit(limit);
+        self
+    }
+
+    pub fn shadow_rgb(mut self, dx: f32, dy: f32, r: f32, g: f32, b: f32) -> Self {
+        self.shadow.rgb(dx, dy, r, g, b);
+        self
+    }
+
+    pub fn shadow_cmyk(mut self, dx: f32, dy: f32, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.shadow.cmyk(dx, dy, c, m, y, k);
+        self
+    }
+
+    pub fn round_corners(mut self, radius: f32) -> Self {
+        self.corner_radius = radius.max(0.0);
+        self
+    }
+
+    pub fn language_level(mut self, level: LanguageLevel) -> Self {
+        self.operand_limit = level.operand_stack_limit();
+        self
+    }
+
+    pub fn scale(mut self, x: f32, y: f32) -> Self {
+        self.transform.scale(x, y);
+        self
+    }
+
+    pub fn flip_horizontal(mut self) -> Self {
+        self.transform.flip_horizontal();
+        self
+    }
+
+    pub fn flip_vertical(mut self) -> Self {
+        self.transform.flip_vertical();
+        self
+    }
+
+    pub fn skew(mut self, x: f32, y: f32) -> Self {
+        self.transform.skew(x, y);
+        self
+    }
+
+    pub fn transform(mut self, matrix: Transform) -> Self {
+        self.transform.matrix(matrix);
+        self
+    }
+
+    pub fn set_orign(mut self, origin: TransformOrigin) -> Self {
+        self.transform.set_origin(origin);
+        self
+    }
+
+    pub fn rotate(mut self, angle: f32) -> Self {
+        self.transform.rotate(angle);
+        self
+    }
+
+    fn serialize(&self, fill: &FillStyle, stroke: &StrokeStyle) -> String {
+        let mut result = String::new();
+
+        if self.points.len() < 2
+            || (!fill.is_enabled() && !self.hatch.is_enabled() && !stroke.is_enabled())
+        {
+            return result;
+        }
+
+        let (x, y, width, height) = bounds(&self.points);
+        self.transform.begin(&mut result, x, y, width, height);
+
+        let mut path = String::new();
+        if self.corner_radius > 0.0 && self.points.len() > 2 {
+            write_rounded_vertices(&mut path, &self.points, self.corner_radius);
+        } else {
+            write_vertices(&mut path, &self.points, "poly", self.operand_limit);
+        }
+        self.shadow.write(&mut result, &path);
+        result.push_str(&path);
+
+        write_paint(&mut result, stroke, &self.stroke, |result| {
+            fill.write_as(result, &self.fill);
+            self.hatch.write(result, x, y, width, height);
+        });
+        self.transform.end(&mut result);
+
+        result
+    }
+}
+
+impl Serialize for Polygon {
+    fn to_postscript_string(&self) -> String {
+        self.serialize(&self.fill, &self.stroke)
+    }
+}
+
+impl GroupItem for Polygon {
+    fn to_postscript_string_in(&self, group: &Group) -> String {
+        self.serialize(group.fill_for(&self.fill), group.stroke_for(&self.stroke))
+    }
+}
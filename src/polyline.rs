@@ -0,0 +1,188 @@
+use crate::{
+    style::{bounds, write_vertices, StrokeStyle, TransformStyle},
+    Group, GroupItem, LanguageLevel, LineCap, LineJoin, Serialize, Transform, TransformOrigin,
+};
+use std::collections::HashMap;
+
+pub struct Polyline {
+    points: Vec<(f32, f32)>,
+    stroke: StrokeStyle,
+    segment_strokes: HashMap<usize, StrokeStyle>,
+    transform: TransformStyle,
+    operand_limit: usize,
+}
+
+impl Polyline {
+    pub fn new(points: Vec<(f32, f32)>) -> Self {
+        Polyline {
+            points,
+            stroke: StrokeStyle::new(1.0),
+            segment_strokes: HashMap::new(),
+            transform: TransformStyle::new(),
+            operand_limit: LanguageLevel::Level1.operand_stack_limit(),
+        }
+    }
+
+    pub fn stroke_rgb(mut self, width: f32, r: f32, g: f32, b: f32) -> Self {
+        self.stroke.rgb(width, r, g, b);
+        self
+    }
+
+    pub fn stroke_cmyk(mut self, width: f32, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.stroke.cmyk(width, c, m, y, k);
+        self
+    }
+
+    pub fn line_cap(mut self, cap: LineCap) -> Self {
+        self.stroke.cap(cap);
+        self
+    }
+
+    pub fn line_join(mut self, join: LineJoin) -> Self {
+        self.stroke.join(join);
+        self
+    }
+
+    pub fn miter_limit(mut self, limit: f32) -> Self {
+        self.stroke.miter_limit(limit);
+        self
+    }
+
+    // Segment `index` runs from point `index` to point `index + 1`.
+    pub fn segment_rgb(mut self, index: usize, width: f32, r: f32, g: f32, b: f32) -> Self {
+        let mut stroke = StrokeStyle::new(0.0);
+        stroke.rgb(width, r, g, b);
+        self.segment_strokes.insert(index, stroke);
+        self
+    }
+
+    pub fn segment_cmyk(
+        mut self,
+        index: usize,
+        width: f32,
+        c: f32,
+        m: f32,
+        y: f32,
+        k: f32,
+    ) -> Self {
+        let mut stroke = StrokeStyle::new(0.0);
+        stroke.cmyk(width, c, m, y, k);
+        self.segment_strokes.insert(index, stroke);
+        self
+    }
+
+    pub fn language_level(mut self, level: LanguageLevel) -> Self {
+        self.operand_limit = level.operand_stack_limit();
+        self
+    }
+
+    pub fn scale(mut self, x: f32, y: f32) -> Self {
+        self.transform.scale(x, y);
+        self
+    }
+
+    pub fn flip_horizontal(mut self) -> Self {
+        self.transform.flip_horizontal();
+        self
+    }
+
+    pub fn flip_vertical(mut self) -> Self {
+        self.transform.flip_vertical();
+        self
+    }
+
+    pub fn skew(mut self, x: f32, y: f32) -> Self {
+        self.transform.skew(x, y);
+        self
+    }
+
+    pub fn transform(mut self, matrix: Transform) -> Self {
+        self.transform.matrix(matrix);
+        self
+    }
+
+    pub fn set_orign(mut self, origin: TransformOrigin) -> Self {
+        self.transform.set_origin(origin);
+        self
+    }
+
+    pub fn rotate(mut self, angle: f32) -> Self {
+        self.transform.rotate(angle);
+        self
+    }
+
+    fn serialize(&self, stroke: &StrokeStyle) -> String {
+        let mut result = String::new();
+
+        if self.points.len() < 2 {
+            return result;
+        }
+
+        let (x, y, width, height) = bounds(&self.points);
+        self.transform.begin(&mut result, x, y, width, height);
+
+        // Consecutive segments without their own styling are stroked as a single path so the
+        // joins between them are rendered correctly.
+        let mut run_start = 0;
+        for index in 0..self.points.len() - 1 {
+            if let Some(segment) = self.segment_strokes.get(&index) {
+                if run_start < index {
+                    write_run(
+                        &mut result,
+                        &self.points[run_start..=index],
+                        stroke,
+                        &self.stroke,
+                        self.operand_limit,
+                    );
+                }
+                write_run(
+                    &mut result,
+                    &self.points[index..=index + 1],
+                    segment,
+                    &self.stroke,
+                    self.operand_limit,
+                );
+                run_start = index + 1;
+            }
+        }
+        if run_start < self.points.len() - 1 {
+            write_run(
+                &mut result,
+                &self.points[run_start..],
+                stroke,
+                &self.stroke,
+                self.operand_limit,
+            );
+        }
+
+        self.transform.end(&mut result);
+
+        result
+    }
+}
+
+fn write_run(
+    result: &mut String,
+    points: &[(f32, f32)],
+    stroke: &StrokeStyle,
+    state: &StrokeStyle,
+    operand_limit: usize,
+) {
+    if !stroke.is_enabled() {
+        return;
+    }
+    write_vertices(result, points, "pline", operand_limit);
+    stroke.write_with_state(result, state);
+}
+
+impl Serialize for Polyline {
+    fn to_postscript_string(&self) -> String {
+        self.serialize(&self.stroke)
+    }
+}
+
+impl GroupItem for Polyline {
+    fn to_postscript_string_in(&self, group: &Group) -> String {
+        self.serialize(group.stroke_for(&self.stroke))
+    }
+}
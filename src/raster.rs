@@ -0,0 +1,235 @@
+use std::io::{Error, ErrorKind};
+
+fn invalid(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message.to_string())
+}
+
+// Image samples, 8 bits per component with rows from top to bottom. Samples that are still
+// compressed are decoded by the interpreter with the filter.
+pub(crate) struct Raster {
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pub(crate) components: usize,
+    pub(crate) samples: Vec<u8>,
+    pub(crate) filter: Option<&'static str>,
+    pub(crate) inverted: bool,
+}
+
+impl Raster {
+    pub(crate) fn decode(data: &[u8]) -> Result<Self, Error> {
+        if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+            return decode_png(data);
+        }
+        if data.starts_with(&[0xFF, 0xD8]) {
+            return read_jpeg(data);
+        }
+        match data.get(0..2) {
+            Some(b"P2" | b"P3" | b"P5" | b"P6") => decode_pnm(data),
+            _ => Err(Error::new(
+                ErrorKind::Unsupported,
+                "Unsupported image format.",
+            )),
+        }
+    }
+}
+
+// Palettes and bit depths other than 8 are expanded to 8 bit grey or RGB samples, and interlaced
+// images are deinterlaced. Transparent pixels are composited over white.
+#[cfg(feature = "png")]
+fn decode_png(data: &[u8]) -> Result<Raster, Error> {
+    let png_error = |error: png::DecodingError| Error::new(ErrorKind::InvalidData, error);
+    let mut decoder = png::Decoder::new(data);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(png_error)?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).map_err(png_error)?;
+    buffer.truncate(info.buffer_size());
+
+    let (components, alpha) = match info.color_type {
+        png::ColorType::Grayscale => (1, false),
+        png::ColorType::GrayscaleAlpha => (1, true),
+        png::ColorType::Rgb => (3, false),
+        png::ColorType::Rgba => (3, true),
+        png::ColorType::Indexed => return Err(invalid("Unexpected indexed PNG samples.")),
+    };
+    let samples = if alpha {
+        buffer
+            .chunks(components + 1)
+            .flat_map(|pixel| {
+                let alpha = pixel[components] as u32;
+                pixel[..components]
+                    .iter()
+                    .map(move |value| ((*value as u32 * alpha + 255 * (255 - alpha)) / 255) as u8)
+            })
+            .collect()
+    } else {
+        buffer
+    };
+    Ok(Raster {
+        width: info.width as usize,
+        height: info.height as usize,
+        components,
+        samples,
+        filter: None,
+        inverted: false,
+    })
+}
+
+#[cfg(not(feature = "png"))]
+fn decode_png(_data: &[u8]) -> Result<Raster, Error> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        "PNG images require the png feature.",
+    ))
+}
+
+// JPEG data is passed through as it is, to be decoded with DCTDecode; only the frame header is
+// read for the size and components. CMYK images written by Adobe applications store inverted
+// samples.
+fn read_jpeg(data: &[u8]) -> Result<Raster, Error> {
+    let truncated = || invalid("Unexpected end of JPEG data.");
+    let mut inverted = false;
+    let mut offset = 2;
+    loop {
+        let marker = *data.get(offset + 1).ok_or_else(truncated)?;
+        if data[offset] != 0xFF {
+            return Err(invalid("Invalid JPEG marker."));
+        }
+        // Markers may be preceded by any number of fill bytes.
+        if marker == 0xFF {
+            offset += 1;
+            continue;
+        }
+        let segment = data.get(offset + 2..offset + 4).ok_or_else(truncated)?;
+        let length = u16::from_be_bytes([segment[0], segment[1]]) as usize;
+        let segment = data
+            .get(offset + 4..offset + 2 + length)
+            .ok_or_else(truncated)?;
+        match marker {
+            0xEE if segment.starts_with(b"Adobe") => inverted = true,
+            0xC0..=0xCF if !matches!(marker, 0xC4 | 0xC8 | 0xCC) => {
+                if segment.len() < 6 || segment[0] != 8 {
+                    return Err(Error::new(
+                        ErrorKind::Unsupported,
+                        "Only 8 bit JPEG images are supported.",
+                    ));
+                }
+                let components = segment[5] as usize;
+                if !matches!(components, 1 | 3 | 4) {
+                    return Err(invalid("Invalid JPEG components."));
+                }
+                let width = u16::from_be_bytes([segment[3], segment[4]]) as usize;
+                let height = u16::from_be_bytes([segment[1], segment[2]]) as usize;
+                // A height of 0 is defined by a DNL marker after the first scan.
+                if height == 0 {
+                    return Err(Error::new(
+                        ErrorKind::Unsupported,
+                        "JPEG images without a height in the frame header are not supported.",
+                    ));
+                }
+                if width == 0 {
+                    return Err(invalid("Invalid JPEG width."));
+                }
+                return Ok(Raster {
+                    width,
+                    height,
+                    components,
+                    samples: data.to_vec(),
+                    filter: Some("DCTDecode"),
+                    inverted: inverted && components == 4,
+                });
+            }
+            0xD9 | 0xDA => return Err(invalid("JPEG frame header not found.")),
+            _ => {}
+        }
+        offset += 2 + length;
+    }
+}
+
+// Reads the next whitespace separated header field, skipping comments.
+fn pnm_field<'a>(data: &'a [u8], offset: &mut usize) -> Result<&'a [u8], Error> {
+    loop {
+        match data.get(*offset) {
+            Some(b'#') => {
+                while data
+                    .get(*offset)
+                    .is_some_and(|b| *b != b'\n' && *b != b'\r')
+                {
+                    *offset += 1;
+                }
+            }
+            Some(b) if b.is_ascii_whitespace() => *offset += 1,
+            Some(_) => break,
+            None => return Err(invalid("Unexpected end of image data.")),
+        }
+    }
+    let start = *offset;
+    while data.get(*offset).is_some_and(|b| !b.is_ascii_whitespace()) {
+        *offset += 1;
+    }
+    Ok(&data[start..*offset])
+}
+
+fn pnm_number(data: &[u8], offset: &mut usize) -> Result<usize, Error> {
+    std::str::from_utf8(pnm_field(data, offset)?)
+        .ok()
+        .and_then(|field| field.parse().ok())
+        .ok_or_else(|| invalid("Invalid image header."))
+}
+
+// Netpbm greymaps and pixmaps, in plain (P2, P3) or raw (P5, P6) form. Samples with a maximum
+// other than 255 are scaled to 8 bits.
+fn decode_pnm(data: &[u8]) -> Result<Raster, Error> {
+    let components = if matches!(&data[0..2], b"P3" | b"P6") {
+        3
+    } else {
+        1
+    };
+    let mut offset = 2;
+    let width = pnm_number(data, &mut offset)?;
+    let height = pnm_number(data, &mut offset)?;
+    let max = pnm_number(data, &mut offset)?;
+    if width == 0 || height == 0 || max == 0 || max > 65535 {
+        return Err(invalid("Invalid image header."));
+    }
+    let count = width
+        .checked_mul(height)
+        .and_then(|pixels| pixels.checked_mul(components))
+        .ok_or_else(|| invalid("Invalid image header."))?;
+    // Every sample takes at least one byte, so a header claiming more samples than there is data
+    // is rejected before anything is allocated for them.
+    if count > data.len() - offset {
+        return Err(invalid("Unexpected end of image data."));
+    }
+
+    let mut values = Vec::with_capacity(count);
+    if matches!(&data[0..2], b"P5" | b"P6") {
+        // A single whitespace character separates the header from the samples.
+        let start = offset + 1;
+        let size = if max > 255 { 2 } else { 1 };
+        let samples = count
+            .checked_mul(size)
+            .and_then(|length| length.checked_add(start))
+            .and_then(|end| data.get(start..end))
+            .ok_or_else(|| invalid("Unexpected end of image data."))?;
+        for sample in samples.chunks(size) {
+            values.push(sample.iter().fold(0, |value, b| value << 8 | *b as usize));
+        }
+    } else {
+        for _ in 0..count {
+            values.push(pnm_number(data, &mut offset)?);
+        }
+    }
+
+    Ok(Raster {
+        width,
+        height,
+        components,
+        samples: values
+            .into_iter()
+            .map(|value| (value.min(max) * 255 / max) as u8)
+            .collect(),
+        filter: None,
+        inverted: false,
+    })
+}
@@ -1,4 +1,8 @@
-use crate::{ColorMode, Serialize, TransformOrigin};
+use crate::{
+    style::{write_paint, FillStyle, HatchStyle, ShadowStyle, StrokeStyle, TransformStyle},
+    FillRule, Group, GroupItem, HatchPattern, LineCap, LineJoin, PaintOrder, Serialize, Transform,
+    TransformOrigin,
+};
 use std::fmt::Write;
 
 pub struct Rect {
@@ -6,196 +10,209 @@ pub struct Rect {
     y: f32,
     width: f32,
     height: f32,
-    stroke_width: f32,
-    stroke_color_rgb: [f32; 3],
-    stroke_color_cmyk: [f32; 4],
-    fill_color_rgb: [f32; 3],
-    fill_color_cmyk: [f32; 4],
-    do_fill: bool,
-    rotate: f32,
-    scale: [f32; 2],
-    do_scale: bool,
-    do_rotate: bool,
-    transform_origin: TransformOrigin,
-    fill_color_mode: ColorMode,
-    stroke_color_mode: ColorMode,
+    corner_radius: f32,
+    fill: FillStyle,
+    hatch: HatchStyle,
+    stroke: StrokeStyle,
+    shadow: ShadowStyle,
+    transform: TransformStyle,
 }
 
 impl Rect {
     pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
         Rect {
-            fill_color_mode: ColorMode::RGB,
-            stroke_color_mode: ColorMode::RGB,
             x: x.max(0.0),
             y: y.max(0.0),
             width: width.max(0.0),
             height: height.max(0.0),
-            stroke_width: 0.0,
-            stroke_color_rgb: [0.0, 0.0, 0.0],
-            fill_color_rgb: [0.0, 0.0, 0.0],
-            stroke_color_cmyk: [0.0, 0.0, 0.0, 0.0],
-            fill_color_cmyk: [0.0, 0.0, 0.0, 0.0],
-            do_fill: false,
-            do_rotate: false,
-            rotate: 0.0,
-            scale: [1.0, 1.0],
-            do_scale: false,
-            transform_origin: TransformOrigin::Center,
+            corner_radius: 0.0,
+            fill: FillStyle::new(),
+            hatch: HatchStyle::new(),
+            stroke: StrokeStyle::new(0.0),
+            shadow: ShadowStyle::new(),
+            transform: TransformStyle::new(),
         }
     }
 
     pub fn fill_rgb(mut self, r: f32, g: f32, b: f32) -> Self {
-        self.fill_color_rgb[0] = r.clamp(0.0, 1.0);
-        self.fill_color_rgb[1] = g.clamp(0.0, 1.0);
-        self.fill_color_rgb[2] = b.clamp(0.0, 1.0);
-        self.do_fill = true;
-        self.fill_color_mode = ColorMode::RGB;
+        self.fill.rgb(r, g, b);
         self
     }
 
     pub fn fill_cmyk(mut self, c: f32, m: f32, y: f32, k: f32) -> Self {
-        self.fill_color_cmyk[0] = c.clamp(0.0, 1.0);
-        self.fill_color_cmyk[1] = m.clamp(0.0, 1.0);
-        self.fill_color_cmyk[2] = y.clamp(0.0, 1.0);
-        self.fill_color_cmyk[3] = k.clamp(0.0, 1.0);
-        self.do_fill = true;
-        self.fill_color_mode = ColorMode::CMYK;
+        self.fill.cmyk(c, m, y, k);
+        self
+    }
+
+    pub fn fill_rule(mut self, rule: FillRule) -> Self {
+        self.fill.rule(&rule);
+        self.shadow.rule(&rule);
+        self.hatch.rule(&rule);
+        self
+    }
+
+    pub fn opacity(mut self, opacity: f32) -> Self {
+        self.fill.opacity(opacity);
+        self
+    }
+
+    pub fn backdrop_rgb(mut self, r: f32, g: f32, b: f32) -> Self {
+        self.fill.backdrop_rgb(r, g, b);
+        self
+    }
+
+    pub fn backdrop_cmyk(mut self, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.fill.backdrop_cmyk(c, m, y, k);
+        self
+    }
+
+    pub fn hatch(mut self, pattern: HatchPattern, spacing: f32, width: f32) -> Self {
+        self.hatch.set(pattern, spacing, width);
+        self
+    }
+
+    pub fn hatch_rgb(mut self, r: f32, g: f32, b: f32) -> Self {
+        self.hatch.rgb(r, g, b);
+        self
+    }
+
+    pub fn hatch_cmyk(mut self, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.hatch.cmyk(c, m, y, k);
         self
     }
 
     pub fn stroke_rgb(mut self, width: f32, r: f32, g: f32, b: f32) -> Self {
-        self.stroke_width = width.max(0.0);
-        self.stroke_color_rgb[0] = r.clamp(0.0, 1.0);
-        self.stroke_color_rgb[1] = g.clamp(0.0, 1.0);
-        self.stroke_color_rgb[2] = b.clamp(0.0, 1.0);
-        self.stroke_color_mode = ColorMode::RGB;
+        self.stroke.rgb(width, r, g, b);
         self
     }
 
     pub fn stroke_cmyk(mut self, width: f32, c: f32, m: f32, y: f32, k: f32) -> Self {
-        self.stroke_width = width.max(0.0);
-        self.stroke_color_cmyk[0] = c.clamp(0.0, 1.0);
-        self.stroke_color_cmyk[1] = m.clamp(0.0, 1.0);
-        self.stroke_color_cmyk[2] = y.clamp(0.0, 1.0);
-        self.stroke_color_cmyk[3] = k.clamp(0.0, 1.0);
-        self.stroke_color_mode = ColorMode::CMYK;
+        self.stroke.cmyk(width, c, m, y, k);
+        self
+    }
+
+    pub fn paint_order(mut self, order: PaintOrder) -> Self {
+        self.stroke.paint_order(order);
+        self
+    }
+
+    pub fn line_cap(mut self, cap: LineCap) -> Self {
+        self.stroke.cap(cap);
+        self
+    }
+
+    pub fn line_join(mut self, join: LineJoin) -> Self {
+        self.stroke.join(join);
+        self
+    }
+
+    pub fn miter_limit(mut self, limit: f32) -> Self {
+        self.stroke.miter_limit(limit);
+        self
+    }
+
+    pub fn shadow_rgb(mut self, dx: f32, dy: f32, r: f32, g: f32, b: f32) -> Self {
+        self.shadow.rgb(dx, dy, r, g, b);
+        self
+    }
+
+    pub fn shadow_cmyk(mut self, dx: f32, dy: f32, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.shadow.cmyk(dx, dy, c, m, y, k);
+        self
+    }
+
+    pub fn corner_radius(mut self, radius: f32) -> Self {
+        self.corner_radius = radius.max(0.0);
         self
     }
 
     pub fn scale(mut self, x: f32, y: f32) -> Self {
-        self.scale[0] = x;
-        self.scale[1] = y;
-        self.do_scale = true;
+        self.transform.scale(x, y);
+        self
+    }
+
+    pub fn flip_horizontal(mut self) -> Self {
+        self.transform.flip_horizontal();
+        self
+    }
+
+    pub fn flip_vertical(mut self) -> Self {
+        self.transform.flip_vertical();
+        self
+    }
+
+    pub fn skew(mut self, x: f32, y: f32) -> Self {
+        self.transform.skew(x, y);
+        self
+    }
+
+    pub fn transform(mut self, matrix: Transform) -> Self {
+        self.transform.matrix(matrix);
         self
     }
 
     pub fn set_orign(mut self, origin: TransformOrigin) -> Self {
-        self.transform_origin = origin;
+        self.transform.set_origin(origin);
         self
     }
 
     pub fn rotate(mut self, angle: f32) -> Self {
-        self.rotate = angle.clamp(-360.0, 360.0);
-        self.do_rotate = true;
+        self.transform.rotate(angle);
         self
     }
-}
 
-impl Serialize for Rect {
-    fn to_postscript_string(&self) -> String {
+    fn serialize(&self, fill: &FillStyle, stroke: &StrokeStyle) -> String {
         let mut result = String::new();
 
-        if !self.do_fill && self.stroke_width == 0.0 {
+        if !fill.is_enabled() && !self.hatch.is_enabled() && !stroke.is_enabled() {
             return result;
         }
 
-        if self.do_rotate || self.do_scale {
-            result.push_str("gsave ");
-            let origin = match self.transform_origin {
-                TransformOrigin::TopLeft => (self.x, self.y + self.height),
-                TransformOrigin::TopRight => (self.x + self.width, self.y + self.height),
-                TransformOrigin::BottomLeft => (self.x, self.y),
-                TransformOrigin::BottomRight => (self.x + self.width, self.y),
-                TransformOrigin::Center => {
-                    (self.x + (self.width / 2.0), self.y + (self.height / 2.0))
-                }
-            };
-            write!(&mut result, "{} {} translate ", origin.0, origin.1).unwrap();
-
-            if self.do_rotate {
-                write!(&mut result, "{} rotate ", self.rotate).unwrap();
-            }
-
-            if self.do_scale {
-                write!(&mut result, "{} {} scale ", self.scale[0], self.scale[1]).unwrap();
-            }
-
-            write!(&mut result, "-{} -{} translate ", origin.0, origin.1).unwrap();
+        self.transform
+            .begin(&mut result, self.x, self.y, self.width, self.height);
+
+        let mut path = String::new();
+        let radius = self
+            .corner_radius
+            .min(self.width / 2.0)
+            .min(self.height / 2.0);
+        if radius > 0.0 {
+            write!(
+                &mut path,
+                "{} {} {} {} {} rrect ",
+                radius, self.width, self.height, self.x, self.y
+            )
+            .unwrap();
+        } else {
+            write!(
+                &mut path,
+                "-{} 0 0 -{} {} 0 0 {} {} {} rect ",
+                self.width, self.height, self.width, self.height, self.x, self.y
+            )
+            .unwrap();
         }
+        self.shadow.write(&mut result, &path);
+        result.push_str(&path);
 
-        write!(
-            &mut result,
-            "-{} 0 0 -{} {} 0 0 {} {} {} rect ",
-            self.width, self.height, self.width, self.height, self.x, self.y
-        )
-        .unwrap();
-
-        if self.do_fill {
-            match self.fill_color_mode {
-                ColorMode::RGB => {
-                    write!(
-                        &mut result,
-                        "{} {} {} fillrgb ",
-                        self.fill_color_rgb[0], self.fill_color_rgb[1], self.fill_color_rgb[2]
-                    )
-                    .unwrap();
-                }
-                ColorMode::CMYK => {
-                    write!(
-                        &mut result,
-                        "{} {} {} {} fillcmyk ",
-                        self.fill_color_cmyk[0],
-                        self.fill_color_cmyk[1],
-                        self.fill_color_cmyk[2],
-                        self.fill_color_cmyk[3],
-                    )
-                    .unwrap();
-                }
-            }
-        }
+        write_paint(&mut result, stroke, &self.stroke, |result| {
+            fill.write_as(result, &self.fill);
+            self.hatch
+                .write(result, self.x, self.y, self.width, self.height);
+        });
+        self.transform.end(&mut result);
 
-        if self.stroke_width > 0.0 {
-            match self.stroke_color_mode {
-                ColorMode::RGB => {
-                    write!(
-                        &mut result,
-                        "{} {} {} {} strokergb ",
-                        self.stroke_color_rgb[0],
-                        self.stroke_color_rgb[1],
-                        self.stroke_color_rgb[2],
-                        self.stroke_width,
-                    )
-                    .unwrap();
-                }
-                ColorMode::CMYK => {
-                    write!(
-                        &mut result,
-                        "{} {} {} {} {} strokecmyk ",
-                        self.stroke_color_cmyk[0],
-                        self.stroke_color_cmyk[1],
-                        self.stroke_color_cmyk[2],
-                        self.stroke_color_cmyk[3],
-                        self.stroke_width,
-                    )
-                    .unwrap();
-                }
-            }
-        }
+        result
+    }
+}
 
-        if self.do_rotate || self.do_scale {
-            result.push_str("grestore ");
-        }
+impl Serialize for Rect {
+    fn to_postscript_string(&self) -> String {
+        self.serialize(&self.fill, &self.stroke)
+    }
+}
 
-        result
+impl GroupItem for Rect {
+    fn to_postscript_string_in(&self, group: &Group) -> String {
+        self.serialize(group.fill_for(&self.fill), group.stroke_for(&self.stroke))
     }
 }
@@ -0,0 +1,38 @@
+// A summary of the finished document returned by Document::close().
+pub struct Report {
+    pub(crate) page_count: u32,
+    pub(crate) bookmarks: Vec<(String, u32, u32)>,
+    pub(crate) warnings: Vec<String>,
+    pub(crate) resources: Vec<String>,
+    pub(crate) bytes_written: usize,
+    pub(crate) digest: Option<String>,
+}
+
+impl Report {
+    pub fn page_count(&self) -> u32 {
+        self.page_count
+    }
+
+    // The title of each bookmark with the first and last page it covers.
+    pub fn bookmarks(&self) -> &[(String, u32, u32)] {
+        &self.bookmarks
+    }
+
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    // The names of the procedures, fonts and forms defined in the document, in the order they
+    // were first written.
+    pub fn resources(&self) -> &[String] {
+        &self.resources
+    }
+
+    pub fn bytes_written(&self) -> usize {
+        self.bytes_written
+    }
+
+    pub fn digest(&self) -> Option<&str> {
+        self.digest.as_deref()
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{style::StrokeStyle, GroupItem, Serialize};
+use std::fmt::Write;
+
+pub enum RevisionMark {
+    Cloud,
+    ChangeBar,
+}
+
+pub struct RevisionCloud {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    arc_length: f32,
+    stroke: StrokeStyle,
+}
+
+impl RevisionCloud {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        RevisionCloud {
+            x,
+            y,
+            width: width.max(0.0),
+            height: height.max(0.0),
+            arc_length: 12.0,
+            stroke: StrokeStyle::new(1.0),
+        }
+    }
+
+    pub fn arc_length(mut self, length: f32) -> Self {
+        self.arc_length = length.max(1.0);
+        self
+    }
+
+    pub fn stroke_rgb(mut self, width: f32, r: f32, g: f32, b: f32) -> Self {
+        self.stroke.rgb(width, r, g, b);
+        self
+    }
+
+    pub fn stroke_cmyk(mut self, width: f32, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.stroke.cmyk(width, c, m, y, k);
+        self
+    }
+}
+
+impl Serialize for RevisionCloud {
+    fn to_postscript_string(&self) -> String {
+        let mut result = String::new();
+
+        if !self.stroke.is_enabled() || self.width == 0.0 || self.height == 0.0 {
+            return result;
+        }
+
+        // Walk the sides counterclockwise, bulging every segment outwards.
+        let sides = [
+            ((self.x, self.y), (1.0, 0.0), self.width, 180.0),
+            (
+                (self.x + self.width, self.y),
+                (0.0, 1.0),
+                self.height,
+                270.0,
+            ),
+            (
+                (self.x + self.width, self.y + self.height),
+                (-1.0, 0.0),
+                self.width,
+                0.0,
+            ),
+            (
+                (self.x, self.y + self.height),
+                (0.0, -1.0),
+                self.height,
+                90.0,
+            ),
+        ];
+
+        result.push_str("newpath ");
+        for (start, direction, length, angle) in sides {
+            let count = (length / self.arc_length).ceil().max(1.0);
+            let segment = length / count;
+            for i in 0..count as u32 {
+                let offset = segment * (i as f32 + 0.5);
+                write!(
+                    &mut result,
+                    "{} {} {} {} {} arc ",
+                    start.0 + direction.0 * offset,
+                    start.1 + direction.1 * offset,
+                    segment / 2.0,
+                    angle,
+                    angle + 180.0
+                )
+                .unwrap();
+            }
+        }
+        result.push_str("closepath ");
+
+        self.stroke.write(&mut result);
+
+        result
+    }
+}
+
+pub struct ChangeBar {
+    x: f32,
+    y: f32,
+    height: f32,
+    stroke: StrokeStyle,
+}
+
+impl ChangeBar {
+    pub fn new(x: f32, y: f32, height: f32) -> Self {
+        ChangeBar {
+            x,
+            y,
+            height: height.max(0.0),
+            stroke: StrokeStyle::new(2.0),
+        }
+    }
+
+    pub fn stroke_rgb(mut self, width: f32, r: f32, g: f32, b: f32) -> Self {
+        self.stroke.rgb(width, r, g, b);
+        self
+    }
+
+    pub fn stroke_cmyk(mut self, width: f32, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.stroke.cmyk(width, c, m, y, k);
+        self
+    }
+}
+
+impl Serialize for ChangeBar {
+    fn to_postscript_string(&self) -> String {
+        let mut result = String::new();
+
+        if !self.stroke.is_enabled() || self.height == 0.0 {
+            return result;
+        }
+
+        write!(&mut result, "0 {} {} {} line ", self.height, self.x, self.y).unwrap();
+
+        self.stroke.write(&mut result);
+
+        result
+    }
+}
+
+impl GroupItem for RevisionCloud {}
+
+impl GroupItem for ChangeBar {}
@@ -0,0 +1,346 @@
+use crate::{
+    font::bold_name,
+    metrics::{decorations, glyph_warnings, measure_text},
+    text::{font_name, write_decorated, write_font, Spacing, TextColor},
+    Font, FontMetrics, GroupItem, Serialize,
+};
+use std::fmt::Write;
+
+pub struct Span {
+    text: String,
+    font: String,
+    size: f32,
+    color: TextColor,
+    tracking: f32,
+    word_spacing: f32,
+    underline: bool,
+    strikethrough: bool,
+}
+
+impl Span {
+    pub fn new(text: &str) -> Self {
+        Span {
+            text: text.to_string(),
+            font: "Helvetica".to_string(),
+            size: 12.0,
+            color: TextColor::Gray(0.0),
+            tracking: 0.0,
+            word_spacing: 0.0,
+            underline: false,
+            strikethrough: false,
+        }
+    }
+
+    pub fn font<F: Into<Font>>(mut self, font: F, size: f32) -> Self {
+        self.font = font_name(font);
+        self.size = size.max(0.0);
+        self
+    }
+
+    // Switches a standard font to its bold face, eg: Times-Italic to Times-BoldItalic. Call after
+    // font(); other fonts are unchanged.
+    pub fn bold(mut self) -> Self {
+        if let Some(bold) = bold_name(&self.font) {
+            self.font = bold.to_string();
+        }
+        self
+    }
+
+    pub fn tracking(mut self, tracking: f32) -> Self {
+        self.tracking = tracking;
+        self
+    }
+
+    pub fn word_spacing(mut self, word_spacing: f32) -> Self {
+        self.word_spacing = word_spacing;
+        self
+    }
+
+    pub fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    pub fn strikethrough(mut self) -> Self {
+        self.strikethrough = true;
+        self
+    }
+
+    pub fn fill_gray(mut self, gray: f32) -> Self {
+        self.color = TextColor::Gray(gray.clamp(0.0, 1.0));
+        self
+    }
+
+    pub fn fill_rgb(mut self, r: f32, g: f32, b: f32) -> Self {
+        self.color = TextColor::Rgb([r.clamp(0.0, 1.0), g.clamp(0.0, 1.0), b.clamp(0.0, 1.0)]);
+        self
+    }
+
+    pub fn fill_cmyk(mut self, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.color = TextColor::Cmyk([
+            c.clamp(0.0, 1.0),
+            m.clamp(0.0, 1.0),
+            y.clamp(0.0, 1.0),
+            k.clamp(0.0, 1.0),
+        ]);
+        self
+    }
+}
+
+// A word made of the pieces of one or more spans, with the span of the space before it.
+struct Word {
+    pieces: Vec<(usize, String)>,
+    space: Option<usize>,
+    newline: bool,
+}
+
+pub struct RichText {
+    spans: Vec<Span>,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    leading: Option<f32>,
+    metrics: Option<FontMetrics>,
+}
+
+impl RichText {
+    // The box is positioned by its bottom left corner and the first line is set at the top.
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        RichText {
+            spans: Vec::new(),
+            x,
+            y,
+            width: width.max(0.0),
+            height: height.max(0.0),
+            leading: None,
+            metrics: None,
+        }
+    }
+
+    pub fn span(mut self, span: Span) -> Self {
+        self.spans.push(span);
+        self
+    }
+
+    // The distance between baselines, 120% of the largest font size on each line by default.
+    pub fn leading(mut self, leading: f32) -> Self {
+        self.leading = Some(leading.max(0.0));
+        self
+    }
+
+    // Required to wrap spans set in an embedded font, and to apply its kerning.
+    pub fn metrics(mut self, metrics: &FontMetrics) -> Self {
+        self.metrics = Some(metrics.clone());
+        self
+    }
+
+    fn spacing(&self, span: usize) -> Spacing<'_> {
+        let span = &self.spans[span];
+        Spacing {
+            size: span.size,
+            tracking: span.tracking,
+            word_spacing: span.word_spacing,
+            font: &span.font,
+            metrics: self.metrics.as_ref(),
+        }
+    }
+
+    fn measure(&self, span: usize, text: &str) -> f32 {
+        let style = &self.spans[span];
+        measure_text(self.metrics.as_ref(), text, &style.font, style.size)
+            + self.spacing(span).width(text)
+    }
+
+    fn word_width(&self, word: &Word, line_start: bool) -> f32 {
+        let space = match word.space {
+            Some(span) if !line_start => self.measure(span, " "),
+            _ => 0.0,
+        };
+        space
+            + word
+                .pieces
+                .iter()
+                .map(|(span, text)| self.measure(*span, text))
+                .sum::<f32>()
+    }
+
+    // Splits the spans into words. Text in neighbouring spans without whitespace between them
+    // belongs to the same word so it is never broken.
+    fn words(&self) -> Vec<Word> {
+        let mut words = Vec::new();
+        let mut current = Word {
+            pieces: Vec::new(),
+            space: None,
+            newline: false,
+        };
+        let mut space = None;
+        let mut newline = false;
+        for (index, span) in self.spans.iter().enumerate() {
+            if span.size == 0.0 || span.font.is_empty() {
+                continue;
+            }
+            for c in span.text.chars() {
+                if c.is_whitespace() {
+                    if !current.pieces.is_empty() {
+                        words.push(current);
+                        current = Word {
+                            pieces: Vec::new(),
+                            space: None,
+                            newline: false,
+                        };
+                    }
+                    if c == '\n' {
+                        newline = true;
+                        space = None;
+                    } else if !newline {
+                        space = Some(index);
+                    }
+                    continue;
+                }
+                if current.pieces.is_empty() {
+                    current.space = space.take();
+                    current.newline = newline;
+                    newline = false;
+                }
+                match current.pieces.last_mut() {
+                    Some((last, text)) if *last == index => text.push(c),
+                    _ => current.pieces.push((index, c.to_string())),
+                }
+            }
+        }
+        if !current.pieces.is_empty() {
+            words.push(current);
+        }
+        words
+    }
+
+    // Words are moved to the next line when they don't fit. A word wider than the box is set on a
+    // line of its own.
+    fn wrap(&self) -> Vec<Vec<Word>> {
+        let mut lines: Vec<Vec<Word>> = Vec::new();
+        let mut line: Vec<Word> = Vec::new();
+        let mut width = 0.0;
+        for word in self.words() {
+            let fits = width + self.word_width(&word, line.is_empty()) <= self.width;
+            if !line.is_empty() && (word.newline || !fits) {
+                lines.push(line);
+                line = Vec::new();
+                width = 0.0;
+            }
+            width += self.word_width(&word, line.is_empty());
+            line.push(word);
+        }
+        if !line.is_empty() {
+            lines.push(line);
+        }
+        lines
+    }
+
+    // Every span on a line shares its baseline, which is set below the largest font. Lines that
+    // don't fit in the box are not shown; the number of them is returned with the lines shown.
+    fn layout(&self) -> (Vec<(f32, Vec<Word>)>, usize) {
+        let lines = self.wrap();
+        let total = lines.len();
+        let mut lines_shown = Vec::new();
+        let mut baseline = self.y + self.height;
+        for (i, line) in lines.into_iter().enumerate() {
+            let size = line
+                .iter()
+                .flat_map(|word| word.pieces.iter())
+                .map(|(span, _)| self.spans[*span].size)
+                .fold(0.0, f32::max);
+            baseline -= if i == 0 {
+                size
+            } else {
+                self.leading.unwrap_or(size * 1.2)
+            };
+            if baseline < self.y {
+                return (lines_shown, total - i);
+            }
+            lines_shown.push((baseline, line));
+        }
+        (lines_shown, 0)
+    }
+}
+
+impl Serialize for RichText {
+    fn to_postscript_string(&self) -> String {
+        let mut result = String::new();
+
+        let (lines_shown, _) = self.layout();
+        if lines_shown.is_empty() {
+            return result;
+        }
+
+        result.push_str("gsave ");
+        let mut color = String::new();
+        let mut font = String::new();
+        for (baseline, line) in lines_shown {
+            let mut runs: Vec<(usize, String)> = Vec::new();
+            for (i, word) in line.iter().enumerate() {
+                let space = word
+                    .space
+                    .filter(|_| i > 0)
+                    .map(|span| (span, " ".to_string()));
+                for (span, text) in space.into_iter().chain(word.pieces.iter().cloned()) {
+                    match runs.last_mut() {
+                        Some((last, run)) if *last == span => run.push_str(&text),
+                        _ => runs.push((span, text)),
+                    }
+                }
+            }
+
+            write!(&mut result, "{} {} moveto ", self.x, baseline).unwrap();
+            for (index, text) in runs {
+                let span = &self.spans[index];
+                let mut next_color = String::new();
+                span.color.write(&mut next_color);
+                if next_color != color {
+                    result.push_str(&next_color);
+                    color = next_color;
+                }
+                let mut next_font = String::new();
+                write_font(
+                    &mut next_font,
+                    &span.font,
+                    span.size,
+                    &text,
+                    self.spacing(index).glyphs(&span.font).is_some(),
+                );
+                if next_font != font {
+                    result.push_str(&next_font);
+                    font = next_font;
+                }
+                let lines = decorations(self.metrics.as_ref(), &span.font).lines(
+                    span.size,
+                    span.underline,
+                    span.strikethrough,
+                );
+                write_decorated(&mut result, &text, &self.spacing(index), baseline, &lines);
+            }
+        }
+        result.push_str("grestore ");
+
+        result
+    }
+
+    fn warnings(&self) -> Vec<String> {
+        let mut warnings: Vec<String> = self
+            .spans
+            .iter()
+            .filter(|span| !span.text.is_empty() && span.size > 0.0)
+            .flat_map(|span| glyph_warnings(self.metrics.as_ref(), &span.text, &span.font))
+            .collect();
+        let (_, hidden) = self.layout();
+        if hidden > 0 {
+            warnings.push(format!(
+                "Rich text overflows its box; {} lines are not shown.",
+                hidden
+            ));
+        }
+        warnings
+    }
+}
+
+impl GroupItem for RichText {}
@@ -0,0 +1,107 @@
+use crate::{encoding::escape_string, GroupItem, Serialize};
+use std::fmt::Write;
+
+// A Separation color space for the named spot color. Devices that don't produce the separation
+// use the alternate CMYK color scaled by the tint.
+pub(crate) fn separation_space(name: &str, alternate: [f32; 4]) -> String {
+    let [c, m, y, k] = alternate;
+    format!(
+        "[/Separation ({}) /DeviceCMYK {{ dup {} mul exch dup {} mul exch dup {} mul exch {} mul }}] setcolorspace ",
+        escape_string(name),
+        c,
+        m,
+        y,
+        k
+    )
+}
+
+// Content printed on a technical plate, eg: white ink under artwork on clear film or a spot UV
+// varnish. Whatever color the items are painted in, their fills, strokes and text are painted in
+// the spot color instead.
+pub struct TechnicalSeparation {
+    name: String,
+    alternate: [f32; 4],
+    tint: f32,
+    overprint: bool,
+    buffer: String,
+    warnings: Vec<String>,
+}
+
+impl TechnicalSeparation {
+    pub fn new(name: &str) -> Self {
+        TechnicalSeparation {
+            name: name.to_string(),
+            alternate: [0.0, 0.0, 0.0, 0.0],
+            tint: 1.0,
+            overprint: true,
+            buffer: String::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    pub fn white() -> Self {
+        Self::new("White")
+    }
+
+    pub fn varnish() -> Self {
+        Self::new("Varnish")
+    }
+
+    // The color used to preview the plate on devices that don't separate it.
+    pub fn alternate_cmyk(mut self, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.alternate = [
+            c.clamp(0.0, 1.0),
+            m.clamp(0.0, 1.0),
+            y.clamp(0.0, 1.0),
+            k.clamp(0.0, 1.0),
+        ];
+        self
+    }
+
+    pub fn tint(mut self, tint: f32) -> Self {
+        self.tint = tint.clamp(0.0, 1.0);
+        self
+    }
+
+    // Overprinting is on by default so the plate doesn't knock out the process colors beneath it.
+    pub fn overprint(mut self, enabled: bool) -> Self {
+        self.overprint = enabled;
+        self
+    }
+
+    pub fn add<T: Serialize>(&mut self, item: &T) {
+        self.buffer.push_str(&item.to_postscript_string());
+        self.warnings.extend(item.warnings());
+    }
+}
+
+impl Serialize for TechnicalSeparation {
+    fn to_postscript_string(&self) -> String {
+        let mut result = String::new();
+
+        if self.buffer.is_empty() || self.name.is_empty() {
+            return result;
+        }
+
+        // The color operators are redefined in a dictionary that is only on the dictionary stack
+        // while the items are drawn.
+        write!(
+            &mut result,
+            "gsave 4 dict begin /spotcolor {{ {}{} setcolor }} def /setgray {{ pop spotcolor }} def /setrgbcolor {{ pop pop pop spotcolor }} def /setcmykcolor {{ pop pop pop pop spotcolor }} def {} setoverprint ",
+            separation_space(&self.name, self.alternate),
+            self.tint,
+            self.overprint
+        )
+        .unwrap();
+        result.push_str(&self.buffer);
+        result.push_str("end grestore ");
+
+        result
+    }
+
+    fn warnings(&self) -> Vec<String> {
+        self.warnings.clone()
+    }
+}
+
+impl GroupItem for TechnicalSeparation {}
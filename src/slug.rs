@@ -0,0 +1,77 @@
+use chrono::Utc;
+
+use crate::{text::font_name, Font, Text};
+
+pub enum SlugPosition {
+    Top, // Default
+    Bottom,
+}
+
+// A line of job details printed in the slug area outside the trim box, so sheets can be matched
+// to their job after printing. Fields are printed in the order they're added.
+pub struct SlugLine {
+    fields: Vec<String>,
+    position: SlugPosition,
+    font: String,
+    size: f32,
+}
+
+impl Default for SlugLine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SlugLine {
+    pub fn new() -> Self {
+        SlugLine {
+            fields: Vec::new(),
+            position: SlugPosition::Top,
+            font: "Helvetica".to_string(),
+            size: 6.0,
+        }
+    }
+
+    pub fn job_id(self, id: &str) -> Self {
+        self.field("Job", id)
+    }
+
+    pub fn operator(self, name: &str) -> Self {
+        self.field("Operator", name)
+    }
+
+    // The time the slug line is created, in UTC.
+    pub fn datetime(mut self) -> Self {
+        self.fields
+            .push(Utc::now().format("%Y-%m-%d %H:%M UTC").to_string());
+        self
+    }
+
+    pub fn field(mut self, label: &str, value: &str) -> Self {
+        self.fields.push(format!("{}: {}", label, value));
+        self
+    }
+
+    pub fn position(mut self, position: SlugPosition) -> Self {
+        self.position = position;
+        self
+    }
+
+    pub fn font<F: Into<Font>>(mut self, font: F, size: f32) -> Self {
+        self.font = font_name(font);
+        self.size = size.max(0.0);
+        self
+    }
+
+    pub(crate) fn size(&self) -> f32 {
+        self.size
+    }
+
+    pub(crate) fn is_top(&self) -> bool {
+        matches!(self.position, SlugPosition::Top)
+    }
+
+    pub(crate) fn text(&self, x: f32, y: f32) -> Text {
+        Text::new(&self.fields.join("  |  "), x, y).font(self.font.as_str(), self.size)
+    }
+}
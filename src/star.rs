@@ -0,0 +1,150 @@
+use crate::{
+    regular_polygon::vertex, FillRule, Group, GroupItem, LineCap, LineJoin, PaintOrder, Polygon,
+    Serialize, Transform, TransformOrigin,
+};
+
+pub struct Star {
+    polygon: Polygon,
+}
+
+impl Star {
+    pub fn new(x: f32, y: f32, outer_radius: f32, inner_radius: f32, points: u32) -> Self {
+        let points = points.max(2);
+        let step = 180.0 / points as f32;
+        let vertices = (0..points * 2)
+            .map(|i| {
+                let radius = if i % 2 == 0 {
+                    outer_radius
+                } else {
+                    inner_radius
+                };
+                vertex(x, y, radius, 90.0 + (i as f32 * step))
+            })
+            .collect();
+        Star {
+            polygon: Polygon::new(vertices),
+        }
+    }
+
+    pub fn fill_rgb(mut self, r: f32, g: f32, b: f32) -> Self {
+        self.polygon = self.polygon.fill_rgb(r, g, b);
+        self
+    }
+
+    pub fn fill_cmyk(mut self, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.polygon = self.polygon.fill_cmyk(c, m, y, k);
+        self
+    }
+
+    pub fn fill_rule(mut self, rule: FillRule) -> Self {
+        self.polygon = self.polygon.fill_rule(rule);
+        self
+    }
+
+    pub fn opacity(mut self, opacity: f32) -> Self {
+        self.polygon = self.polygon.opacity(opacity);
+        self
+    }
+
+    pub fn backdrop_rgb(mut self, r: f32, g: f32, b: f32) -> Self {
+        self.polygon = self.polygon.backdrop_rgb(r, g, b);
+        self
+    }
+
+    pub fn backdrop_cmyk(mut self, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.polygon = self.polygon.backdrop_cmyk(c, m, y, k);
+        self
+    }
+
+    pub fn stroke_rgb(mut self, width: f32, r: f32, g: f32, b: f32) -> Self {
+        self.polygon = self.polygon.stroke_rgb(width, r, g, b);
+        self
+    }
+
+    pub fn stroke_cmyk(mut self, width: f32, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.polygon = self.polygon.stroke_cmyk(width, c, m, y, k);
+        self
+    }
+
+    pub fn paint_order(mut self, order: PaintOrder) -> Self {
+        self.polygon = self.polygon.paint_order(order);
+        self
+    }
+
+    pub fn line_cap(mut self, cap: LineCap) -> Self {
+        self.polygon = self.polygon.line_cap(cap);
+        self
+    }
+
+    pub fn line_join(mut self, join: LineJoin) -> Self {
+        self.polygon = self.polygon.line_join(join);
+        self
+    }
+
+    pub fn miter_limit(mut self, limit: f32) -> Self {
+        self.polygon = self.polygon.miter_limit(limit);
+        self
+    }
+
+    pub fn shadow_rgb(mut self, dx: f32, dy: f32, r: f32, g: f32, b: f32) -> Self {
+        self.polygon = self.polygon.shadow_rgb(dx, dy, r, g, b);
+        self
+    }
+
+    pub fn shadow_cmyk(mut self, dx: f32, dy: f32, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.polygon = self.polygon.shadow_cmyk(dx, dy, c, m, y, k);
+        self
+    }
+
+    pub fn round_corners(mut self, radius: f32) -> Self {
+        self.polygon = self.polygon.round_corners(radius);
+        self
+    }
+
+    pub fn scale(mut self, x: f32, y: f32) -> Self {
+        self.polygon = self.polygon.scale(x, y);
+        self
+    }
+
+    pub fn flip_horizontal(mut self) -> Self {
+        self.polygon = self.polygon.flip_horizontal();
+        self
+    }
+
+    pub fn flip_vertical(mut self) -> Self {
+        self.polygon = self.polygon.flip_vertical();
+        self
+    }
+
+    pub fn skew(mut self, x: f32, y: f32) -> Self {
+        self.polygon = self.polygon.skew(x, y);
+        self
+    }
+
+    pub fn transform(mut self, matrix: Transform) -> Self {
+        self.polygon = self.polygon.transform(matrix);
+        self
+    }
+
+    pub fn set_orign(mut self, origin: TransformOrigin) -> Self {
+        self.polygon = self.polygon.set_orign(origin);
+        self
+    }
+
+    pub fn rotate(mut self, angle: f32) -> Self {
+        self.polygon = self.polygon.rotate(angle);
+        self
+    }
+}
+
+impl Serialize for Star {
+    fn to_postscript_string(&self) -> String {
+        self.polygon.to_postscript_string()
+    }
+}
+
+impl GroupItem for Star {
+    fn to_postscript_string_in(&self, group: &Group) -> String {
+        self.polygon.to_postscript_string_in(group)
+    }
+}
@@ -0,0 +1,125 @@
+use std::fmt::Write;
+
+use crate::{encoding, GroupItem, Serialize};
+
+pub enum StepOrientation {
+    HeadToFoot, // Default
+    HeadToHead,
+}
+
+// Tiles a form across a sheet, eg: labels or cartons ganged for cutting. The form is drawn with
+// its bleed around the trim, so it's the trim size plus the bleed on every side.
+pub struct StepRepeat {
+    form: String,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    columns: u32,
+    rows: u32,
+    gap: (f32, f32),
+    bleed: f32,
+    orientation: StepOrientation,
+}
+
+impl StepRepeat {
+    // The width and height are the trim size of a single copy.
+    pub fn new(form_name: &str, width: f32, height: f32) -> Self {
+        StepRepeat {
+            form: encoding::name_text(form_name),
+            x: 0.0,
+            y: 0.0,
+            width: width.max(0.0),
+            height: height.max(0.0),
+            columns: 1,
+            rows: 1,
+            gap: (0.0, 0.0),
+            bleed: 0.0,
+            orientation: StepOrientation::HeadToFoot,
+        }
+    }
+
+    // The bottom left corner of the trim of the first copy.
+    pub fn origin(mut self, x: f32, y: f32) -> Self {
+        self.x = x;
+        self.y = y;
+        self
+    }
+
+    pub fn grid(mut self, columns: u32, rows: u32) -> Self {
+        self.columns = columns;
+        self.rows = rows;
+        self
+    }
+
+    pub fn gap(mut self, horizontal: f32, vertical: f32) -> Self {
+        self.gap = (horizontal.max(0.0), vertical.max(0.0));
+        self
+    }
+
+    pub fn bleed(mut self, bleed: f32) -> Self {
+        self.bleed = bleed.max(0.0);
+        self
+    }
+
+    // Head to head layouts turn every other row upside down.
+    pub fn orientation(mut self, orientation: StepOrientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+}
+
+impl Serialize for StepRepeat {
+    fn to_postscript_string(&self) -> String {
+        let mut result = String::new();
+
+        if self.width == 0.0 || self.height == 0.0 || self.form.is_empty() {
+            return result;
+        }
+
+        // Each copy is clipped halfway into the gap so its bleed never covers its neighbours.
+        let (gap_x, gap_y) = self.gap;
+        let clip_x = self.bleed.min(gap_x / 2.0);
+        let clip_y = self.bleed.min(gap_y / 2.0);
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                let x = self.x + column as f32 * (self.width + gap_x);
+                let y = self.y + row as f32 * (self.height + gap_y);
+                write!(
+                    &mut result,
+                    "gsave newpath {} {} moveto {} 0 rlineto 0 {} rlineto {} 0 rlineto closepath clip newpath ",
+                    x - clip_x,
+                    y - clip_y,
+                    self.width + clip_x * 2.0,
+                    self.height + clip_y * 2.0,
+                    -(self.width + clip_x * 2.0)
+                )
+                .unwrap();
+                let turned =
+                    matches!(self.orientation, StepOrientation::HeadToHead) && row % 2 == 1;
+                if turned {
+                    write!(
+                        &mut result,
+                        "{} {} translate 180 rotate ",
+                        x + self.width + self.bleed,
+                        y + self.height + self.bleed
+                    )
+                    .unwrap();
+                } else {
+                    write!(
+                        &mut result,
+                        "{} {} translate ",
+                        x - self.bleed,
+                        y - self.bleed
+                    )
+                    .unwrap();
+                }
+                write!(&mut result, "{} execform grestore ", self.form).unwrap();
+            }
+        }
+
+        result
+    }
+}
+
+impl GroupItem for StepRepeat {}
@@ -0,0 +1,721 @@
+use crate::{
+    ColorMode, FillRule, HatchPattern, LineCap, LineJoin, PaintOrder, Transform, TransformOrigin,
+};
+use std::fmt::Write;
+
+pub(crate) struct FillStyle {
+    color_rgb: [f32; 3],
+    color_cmyk: [f32; 4],
+    color_mode: ColorMode,
+    enabled: bool,
+    even_odd: bool,
+    opacity: f32,
+    backdrop_rgb: [f32; 3],
+    backdrop_cmyk: [f32; 4],
+}
+
+impl FillStyle {
+    pub(crate) fn new() -> Self {
+        FillStyle {
+            color_rgb: [0.0, 0.0, 0.0],
+            color_cmyk: [0.0, 0.0, 0.0, 0.0],
+            color_mode: ColorMode::RGB,
+            enabled: false,
+            even_odd: false,
+            opacity: 1.0,
+            backdrop_rgb: [1.0, 1.0, 1.0],
+            backdrop_cmyk: [0.0, 0.0, 0.0, 0.0],
+        }
+    }
+
+    pub(crate) fn rgb(&mut self, r: f32, g: f32, b: f32) {
+        self.color_rgb[0] = r.clamp(0.0, 1.0);
+        self.color_rgb[1] = g.clamp(0.0, 1.0);
+        self.color_rgb[2] = b.clamp(0.0, 1.0);
+        self.color_mode = ColorMode::RGB;
+        self.enabled = true;
+    }
+
+    pub(crate) fn cmyk(&mut self, c: f32, m: f32, y: f32, k: f32) {
+        self.color_cmyk[0] = c.clamp(0.0, 1.0);
+        self.color_cmyk[1] = m.clamp(0.0, 1.0);
+        self.color_cmyk[2] = y.clamp(0.0, 1.0);
+        self.color_cmyk[3] = k.clamp(0.0, 1.0);
+        self.color_mode = ColorMode::CMYK;
+        self.enabled = true;
+    }
+
+    pub(crate) fn rule(&mut self, rule: &FillRule) {
+        self.even_odd = matches!(rule, FillRule::EvenOdd);
+    }
+
+    pub(crate) fn opacity(&mut self, opacity: f32) {
+        self.opacity = opacity.clamp(0.0, 1.0);
+    }
+
+    // The backdrop is kept in both color spaces (naively converted) so it can be blended with
+    // either kind of fill.
+    pub(crate) fn backdrop_rgb(&mut self, r: f32, g: f32, b: f32) {
+        let rgb = [r.clamp(0.0, 1.0), g.clamp(0.0, 1.0), b.clamp(0.0, 1.0)];
+        let k = 1.0 - rgb[0].max(rgb[1]).max(rgb[2]);
+        let ink = |value: f32| {
+            if k < 1.0 {
+                (1.0 - value - k) / (1.0 - k)
+            } else {
+                0.0
+            }
+        };
+        self.backdrop_rgb = rgb;
+        self.backdrop_cmyk = [ink(rgb[0]), ink(rgb[1]), ink(rgb[2]), k];
+    }
+
+    pub(crate) fn backdrop_cmyk(&mut self, c: f32, m: f32, y: f32, k: f32) {
+        let cmyk = [
+            c.clamp(0.0, 1.0),
+            m.clamp(0.0, 1.0),
+            y.clamp(0.0, 1.0),
+            k.clamp(0.0, 1.0),
+        ];
+        let light = |value: f32| (1.0 - value) * (1.0 - cmyk[3]);
+        self.backdrop_rgb = [light(cmyk[0]), light(cmyk[1]), light(cmyk[2])];
+        self.backdrop_cmyk = cmyk;
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub(crate) fn write(&self, result: &mut String) {
+        self.write_as(result, self);
+    }
+
+    // Fills with the color of this style, using the fill rule and opacity of `own` (the element's
+    // own fill when the color comes from a group). PostScript has no transparency, so
+    // translucent fills are blended with the backdrop color unless the document enables the
+    // SetTransparency pdfmark by defining pslib_transparency. Whether pdfmark exists can't be
+    // tested for, as it is defined on every Ghostscript device.
+    pub(crate) fn write_as(&self, result: &mut String, own: &FillStyle) {
+        if !self.enabled {
+            return;
+        }
+        let prefix = if own.even_odd { "eo" } else { "" };
+        if own.opacity >= 1.0 {
+            self.write_color(result, prefix, 1.0, own);
+            return;
+        }
+        write!(
+            result,
+            "/pslib_transparency where {{ pop [ /ca {} /SetTransparency pdfmark ",
+            own.opacity
+        )
+        .unwrap();
+        self.write_color(result, prefix, 1.0, own);
+        result.push_str("[ /ca 1 /SetTransparency pdfmark } { ");
+        self.write_color(result, prefix, own.opacity, own);
+        result.push_str("} ifelse ");
+    }
+
+    fn write_color(&self, result: &mut String, prefix: &str, opacity: f32, own: &FillStyle) {
+        let blend = |value: f32, backdrop: f32| {
+            if opacity >= 1.0 {
+                return value;
+            }
+            let value = value * opacity + backdrop * (1.0 - opacity);
+            (value * 10000.0).round() / 10000.0 + 0.0
+        };
+        match self.color_mode {
+            ColorMode::RGB => {
+                write!(
+                    result,
+                    "{} {} {} {}fillrgb ",
+                    blend(self.color_rgb[0], own.backdrop_rgb[0]),
+                    blend(self.color_rgb[1], own.backdrop_rgb[1]),
+                    blend(self.color_rgb[2], own.backdrop_rgb[2]),
+                    prefix
+                )
+                .unwrap();
+            }
+            ColorMode::CMYK => {
+                write!(
+                    result,
+                    "{} {} {} {} {}fillcmyk ",
+                    blend(self.color_cmyk[0], own.backdrop_cmyk[0]),
+                    blend(self.color_cmyk[1], own.backdrop_cmyk[1]),
+                    blend(self.color_cmyk[2], own.backdrop_cmyk[2]),
+                    blend(self.color_cmyk[3], own.backdrop_cmyk[3]),
+                    prefix,
+                )
+                .unwrap();
+            }
+        }
+    }
+}
+
+pub(crate) struct StrokeStyle {
+    width: f32,
+    color_rgb: [f32; 3],
+    color_cmyk: [f32; 4],
+    color_mode: ColorMode,
+    cap: Option<LineCap>,
+    join: Option<LineJoin>,
+    miter_limit: Option<f32>,
+    color_set: bool,
+    stroke_first: bool,
+}
+
+impl StrokeStyle {
+    pub(crate) fn new(width: f32) -> Self {
+        StrokeStyle {
+            width,
+            color_rgb: [0.0, 0.0, 0.0],
+            color_cmyk: [0.0, 0.0, 0.0, 0.0],
+            color_mode: ColorMode::RGB,
+            cap: None,
+            join: None,
+            miter_limit: None,
+            color_set: false,
+            stroke_first: false,
+        }
+    }
+
+    pub(crate) fn rgb(&mut self, width: f32, r: f32, g: f32, b: f32) {
+        self.width = width.max(0.0);
+        self.color_rgb[0] = r.clamp(0.0, 1.0);
+        self.color_rgb[1] = g.clamp(0.0, 1.0);
+        self.color_rgb[2] = b.clamp(0.0, 1.0);
+        self.color_mode = ColorMode::RGB;
+        self.color_set = true;
+    }
+
+    pub(crate) fn cmyk(&mut self, width: f32, c: f32, m: f32, y: f32, k: f32) {
+        self.width = width.max(0.0);
+        self.color_cmyk[0] = c.clamp(0.0, 1.0);
+        self.color_cmyk[1] = m.clamp(0.0, 1.0);
+        self.color_cmyk[2] = y.clamp(0.0, 1.0);
+        self.color_cmyk[3] = k.clamp(0.0, 1.0);
+        self.color_mode = ColorMode::CMYK;
+        self.color_set = true;
+    }
+
+    // Whether the stroke was styled explicitly rather than left at its default.
+    pub(crate) fn is_set(&self) -> bool {
+        self.color_set
+    }
+
+    pub(crate) fn cap(&mut self, cap: LineCap) {
+        self.cap = Some(cap);
+    }
+
+    pub(crate) fn join(&mut self, join: LineJoin) {
+        self.join = Some(join);
+    }
+
+    pub(crate) fn paint_order(&mut self, order: PaintOrder) {
+        self.stroke_first = matches!(order, PaintOrder::StrokeFirst);
+    }
+
+    // PostScript requires a miter limit of at least 1.
+    pub(crate) fn miter_limit(&mut self, limit: f32) {
+        self.miter_limit = Some(limit.max(1.0));
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.width > 0.0
+    }
+
+    pub(crate) fn write(&self, result: &mut String) {
+        self.write_with_state(result, self);
+    }
+
+    // Strokes with the color and width of this style, using the cap, join, and miter limit of
+    // `state`. Only the settings that differ from the interpreter defaults are emitted.
+    pub(crate) fn write_with_state(&self, result: &mut String, state: &StrokeStyle) {
+        if !self.is_enabled() {
+            return;
+        }
+        let has_state = state.cap.is_some() || state.join.is_some() || state.miter_limit.is_some();
+        if has_state {
+            result.push_str("gsave ");
+            write_stroke_state(
+                result,
+                state.cap.as_ref(),
+                state.join.as_ref(),
+                state.miter_limit,
+            );
+        }
+        match self.color_mode {
+            ColorMode::RGB => {
+                write!(
+                    result,
+                    "{} {} {} {} strokergb ",
+                    self.color_rgb[0], self.color_rgb[1], self.color_rgb[2], self.width,
+                )
+                .unwrap();
+            }
+            ColorMode::CMYK => {
+                write!(
+                    result,
+                    "{} {} {} {} {} strokecmyk ",
+                    self.color_cmyk[0],
+                    self.color_cmyk[1],
+                    self.color_cmyk[2],
+                    self.color_cmyk[3],
+                    self.width,
+                )
+                .unwrap();
+            }
+        }
+        if has_state {
+            result.push_str("grestore ");
+        }
+    }
+}
+
+// Paints the fill (and anything painted along with it, eg: hatching) and the stroke in the paint
+// order of `state`.
+pub(crate) fn write_paint<F: FnOnce(&mut String)>(
+    result: &mut String,
+    stroke: &StrokeStyle,
+    state: &StrokeStyle,
+    fill: F,
+) {
+    if state.stroke_first {
+        stroke.write_with_state(result, state);
+        fill(result);
+    } else {
+        fill(result);
+        stroke.write_with_state(result, state);
+    }
+}
+
+pub(crate) fn write_stroke_state(
+    result: &mut String,
+    cap: Option<&LineCap>,
+    join: Option<&LineJoin>,
+    miter_limit: Option<f32>,
+) {
+    if let Some(cap) = cap {
+        let value = match cap {
+            LineCap::Butt => 0,
+            LineCap::Round => 1,
+            LineCap::Square => 2,
+        };
+        write!(result, "{} setlinecap ", value).unwrap();
+    }
+    if let Some(join) = join {
+        let value = match join {
+            LineJoin::Miter => 0,
+            LineJoin::Round => 1,
+            LineJoin::Bevel => 2,
+        };
+        write!(result, "{} setlinejoin ", value).unwrap();
+    }
+    if let Some(limit) = miter_limit {
+        write!(result, "{} setmiterlimit ", limit).unwrap();
+    }
+}
+
+pub(crate) struct ShadowStyle {
+    offset: (f32, f32),
+    fill: FillStyle,
+}
+
+impl ShadowStyle {
+    pub(crate) fn new() -> Self {
+        ShadowStyle {
+            offset: (0.0, 0.0),
+            fill: FillStyle::new(),
+        }
+    }
+
+    pub(crate) fn rgb(&mut self, dx: f32, dy: f32, r: f32, g: f32, b: f32) {
+        self.offset = (dx, dy);
+        self.fill.rgb(r, g, b);
+    }
+
+    pub(crate) fn cmyk(&mut self, dx: f32, dy: f32, c: f32, m: f32, y: f32, k: f32) {
+        self.offset = (dx, dy);
+        self.fill.cmyk(c, m, y, k);
+    }
+
+    pub(crate) fn rule(&mut self, rule: &FillRule) {
+        self.fill.rule(rule);
+    }
+
+    // Fills an offset copy of the path behind the shape.
+    pub(crate) fn write(&self, result: &mut String, path: &str) {
+        if !self.fill.is_enabled() {
+            return;
+        }
+        write!(
+            result,
+            "gsave {} {} translate {}",
+            self.offset.0, self.offset.1, path
+        )
+        .unwrap();
+        self.fill.write(result);
+        result.push_str("grestore ");
+    }
+}
+
+pub(crate) struct HatchStyle {
+    pattern: Option<HatchPattern>,
+    spacing: f32,
+    width: f32,
+    color_rgb: [f32; 3],
+    color_cmyk: [f32; 4],
+    color_mode: ColorMode,
+    even_odd: bool,
+}
+
+impl HatchStyle {
+    pub(crate) fn new() -> Self {
+        HatchStyle {
+            pattern: None,
+            spacing: 0.0,
+            width: 0.0,
+            color_rgb: [0.0, 0.0, 0.0],
+            color_cmyk: [0.0, 0.0, 0.0, 0.0],
+            color_mode: ColorMode::RGB,
+            even_odd: false,
+        }
+    }
+
+    pub(crate) fn set(&mut self, pattern: HatchPattern, spacing: f32, width: f32) {
+        self.pattern = Some(pattern);
+        self.spacing = spacing.max(0.0);
+        self.width = width.max(0.0);
+    }
+
+    pub(crate) fn rgb(&mut self, r: f32, g: f32, b: f32) {
+        self.color_rgb[0] = r.clamp(0.0, 1.0);
+        self.color_rgb[1] = g.clamp(0.0, 1.0);
+        self.color_rgb[2] = b.clamp(0.0, 1.0);
+        self.color_mode = ColorMode::RGB;
+    }
+
+    pub(crate) fn cmyk(&mut self, c: f32, m: f32, y: f32, k: f32) {
+        self.color_cmyk[0] = c.clamp(0.0, 1.0);
+        self.color_cmyk[1] = m.clamp(0.0, 1.0);
+        self.color_cmyk[2] = y.clamp(0.0, 1.0);
+        self.color_cmyk[3] = k.clamp(0.0, 1.0);
+        self.color_mode = ColorMode::CMYK;
+    }
+
+    pub(crate) fn rule(&mut self, rule: &FillRule) {
+        self.even_odd = matches!(rule, FillRule::EvenOdd);
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.pattern.is_some() && self.spacing > 0.0 && self.width > 0.0
+    }
+
+    // Strokes parallel lines across the bounding box, clipped to the current path. The path is
+    // left in place for any stroke that follows.
+    pub(crate) fn write(&self, result: &mut String, x: f32, y: f32, width: f32, height: f32) {
+        if !self.is_enabled() {
+            return;
+        }
+        let angles: &[i32] = match self.pattern {
+            Some(HatchPattern::Horizontal) => &[0],
+            Some(HatchPattern::Vertical) => &[90],
+            Some(HatchPattern::Diagonal) => &[45],
+            Some(HatchPattern::Cross) => &[45, -45],
+            None => &[],
+        };
+        result.push_str("gsave ");
+        // Clipping doesn't consume the path, so the even-odd clip narrows the one made by hatch.
+        if self.even_odd {
+            result.push_str("eoclip ");
+        }
+        match self.color_mode {
+            ColorMode::RGB => write!(
+                result,
+                "{} {} {} setrgbcolor ",
+                self.color_rgb[0], self.color_rgb[1], self.color_rgb[2]
+            )
+            .unwrap(),
+            ColorMode::CMYK => write!(
+                result,
+                "{} {} {} {} setcmykcolor ",
+                self.color_cmyk[0], self.color_cmyk[1], self.color_cmyk[2], self.color_cmyk[3]
+            )
+            .unwrap(),
+        }
+        for angle in angles {
+            write!(
+                result,
+                "{} {} {} {} {} {} {} hatch ",
+                x, y, width, height, self.spacing, angle, self.width
+            )
+            .unwrap();
+        }
+        result.push_str("grestore ");
+    }
+}
+
+pub(crate) struct TransformStyle {
+    rotate: f32,
+    scale: [f32; 2],
+    do_rotate: bool,
+    do_scale: bool,
+    flip: [bool; 2],
+    skew: Option<(f32, f32)>,
+    matrix: Option<Transform>,
+    translate: Option<(f32, f32)>,
+    origin: TransformOrigin,
+}
+
+impl TransformStyle {
+    pub(crate) fn new() -> Self {
+        TransformStyle {
+            rotate: 0.0,
+            scale: [1.0, 1.0],
+            do_rotate: false,
+            do_scale: false,
+            flip: [false, false],
+            skew: None,
+            matrix: None,
+            translate: None,
+            origin: TransformOrigin::Center,
+        }
+    }
+
+    pub(crate) fn rotate(&mut self, angle: f32) {
+        self.rotate = angle.clamp(-360.0, 360.0);
+        self.do_rotate = true;
+    }
+
+    pub(crate) fn scale(&mut self, x: f32, y: f32) {
+        self.scale[0] = x;
+        self.scale[1] = y;
+        self.do_scale = true;
+    }
+
+    pub(crate) fn flip_horizontal(&mut self) {
+        self.flip[0] = !self.flip[0];
+    }
+
+    pub(crate) fn flip_vertical(&mut self) {
+        self.flip[1] = !self.flip[1];
+    }
+
+    pub(crate) fn skew(&mut self, x: f32, y: f32) {
+        self.skew = Some((x, y));
+    }
+
+    pub(crate) fn matrix(&mut self, matrix: Transform) {
+        self.matrix = Some(matrix);
+    }
+
+    pub(crate) fn translate(&mut self, x: f32, y: f32) {
+        self.translate = Some((x, y));
+    }
+
+    pub(crate) fn set_origin(&mut self, origin: TransformOrigin) {
+        self.origin = origin;
+    }
+
+    pub(crate) fn is_active(&self) -> bool {
+        self.do_rotate
+            || self.do_scale
+            || self.flip[0]
+            || self.flip[1]
+            || self.skew.is_some()
+            || self.matrix.is_some()
+            || self.translate.is_some()
+    }
+
+    pub(crate) fn begin(&self, result: &mut String, x: f32, y: f32, width: f32, height: f32) {
+        if !self.is_active() {
+            return;
+        }
+        result.push_str("gsave ");
+        if let Some(matrix) = &self.matrix {
+            write!(result, "{} concat ", matrix).unwrap();
+        }
+        if let Some((x, y)) = self.translate {
+            write!(result, "{} {} translate ", x, y).unwrap();
+        }
+        let origin = match self.origin {
+            TransformOrigin::TopLeft => (x, y + height),
+            TransformOrigin::TopRight => (x + width, y + height),
+            TransformOrigin::BottomLeft => (x, y),
+            TransformOrigin::BottomRight => (x + width, y),
+            TransformOrigin::Center => (x + (width / 2.0), y + (height / 2.0)),
+        };
+        // Transforms around the user space origin don't need to move it.
+        let moved = origin != (0.0, 0.0);
+        if moved {
+            write!(result, "{} {} translate ", origin.0, origin.1).unwrap();
+        }
+
+        if self.do_rotate {
+            write!(result, "{} rotate ", self.rotate).unwrap();
+        }
+
+        if let Some((x, y)) = self.skew {
+            write_skew(result, x, y);
+        }
+
+        // Flips are a negative scale around the origin.
+        if self.do_scale || self.flip[0] || self.flip[1] {
+            let x = if self.flip[0] {
+                -self.scale[0]
+            } else {
+                self.scale[0]
+            };
+            let y = if self.flip[1] {
+                -self.scale[1]
+            } else {
+                self.scale[1]
+            };
+            write!(result, "{} {} scale ", x, y).unwrap();
+        }
+
+        if moved {
+            write!(result, "{} {} translate ", -origin.0, -origin.1).unwrap();
+        }
+    }
+
+    pub(crate) fn end(&self, result: &mut String) {
+        if self.is_active() {
+            result.push_str("grestore ");
+        }
+    }
+}
+
+// Shears user space by the given angles (in degrees) along the x and y axes.
+pub(crate) fn write_skew(result: &mut String, x: f32, y: f32) {
+    let shear = |angle: f32| (angle.to_radians().tan() * 10000.0).round() / 10000.0 + 0.0;
+    write!(result, "[1 {} {} 1 0 0] concat ", shear(y), shear(x)).unwrap();
+}
+
+pub(crate) fn bounds(points: &[(f32, f32)]) -> (f32, f32, f32, f32) {
+    if points.is_empty() {
+        return (0.0, 0.0, 0.0, 0.0);
+    }
+    let mut min = points[0];
+    let mut max = points[0];
+    for point in points {
+        min.0 = min.0.min(point.0);
+        min.1 = min.1.min(point.1);
+        max.0 = max.0.max(point.0);
+        max.1 = max.1.max(point.1);
+    }
+    (min.0, min.1, max.0 - min.0, max.1 - min.1)
+}
+
+// The radius of the arc replacing the corner between two edges, clamped so the arc meets each edge
+// within its first half (leaving the rest for the corner at its other end). Returns None when an
+// edge has no length or the edges are collinear, where there's no corner to round.
+pub(crate) fn corner_radius(
+    previous: (f32, f32),
+    corner: (f32, f32),
+    next: (f32, f32),
+    radius: f32,
+) -> Option<f32> {
+    let a = (previous.0 - corner.0, previous.1 - corner.1);
+    let b = (next.0 - corner.0, next.1 - corner.1);
+    let (length_a, length_b) = (a.0.hypot(a.1), b.0.hypot(b.1));
+    if length_a < 1e-6
+        || length_b < 1e-6
+        || (a.0 * b.1 - a.1 * b.0).abs() < 1e-6 * length_a * length_b
+    {
+        return None;
+    }
+    // The arc meets each edge radius / tan(angle / 2) from the corner.
+    let cos = ((a.0 * b.0 + a.1 * b.1) / (length_a * length_b)).clamp(-1.0, 1.0);
+    let tan_half = ((1.0 - cos) / (1.0 + cos)).sqrt();
+    let tangent = length_a.min(length_b) / 2.0;
+    if radius / tan_half <= tangent {
+        Some(radius)
+    } else {
+        Some(tangent * tan_half)
+    }
+}
+
+// Writes the arc replacing a corner, or a line to it when there's no corner to round.
+pub(crate) fn write_corner(
+    result: &mut String,
+    previous: (f32, f32),
+    corner: (f32, f32),
+    next: (f32, f32),
+    radius: f32,
+) {
+    match corner_radius(previous, corner, next, radius) {
+        Some(radius) => write!(
+            result,
+            "{} {} {} {} {} arcto 4 {{ pop }} repeat ",
+            corner.0, corner.1, next.0, next.1, radius
+        )
+        .unwrap(),
+        None => write!(result, "{} {} lineto ", corner.0, corner.1).unwrap(),
+    }
+}
+
+// Writes a closed path through the vertices with each corner replaced by an arc of the given
+// radius. The path starts halfway along the first edge so every vertex is rounded. Repeated
+// vertices are skipped.
+pub(crate) fn write_rounded_vertices(result: &mut String, points: &[(f32, f32)], radius: f32) {
+    let points: Vec<(f32, f32)> = points
+        .iter()
+        .enumerate()
+        .filter(|(i, point)| **point != points[(i + points.len() - 1) % points.len()])
+        .map(|(_, point)| *point)
+        .collect();
+    if points.len() < 2 {
+        return;
+    }
+    let start = (
+        (points[0].0 + points[1].0) / 2.0,
+        (points[0].1 + points[1].1) / 2.0,
+    );
+    write!(result, "newpath {} {} moveto ", start.0, start.1).unwrap();
+    for i in 1..=points.len() {
+        let previous = points[i - 1];
+        let corner = points[i % points.len()];
+        let next = points[(i + 1) % points.len()];
+        write_corner(result, previous, corner, next, radius);
+    }
+    result.push_str("closepath ");
+}
+
+// Writes the vertices followed by `procedure` (poly or pline). When the vertex list would not fit
+// on the operand stack the path is built with incremental lineto calls instead.
+pub(crate) fn write_vertices(
+    result: &mut String,
+    points: &[(f32, f32)],
+    procedure: &str,
+    operand_limit: usize,
+) {
+    // Leave room for operands pushed by enclosing procedures.
+    let pairs = (operand_limit.saturating_sub(16) / 2).max(1);
+
+    if points.len() <= pairs {
+        for point in points[1..].iter().rev() {
+            write!(result, "{} {} ", point.0, point.1).unwrap();
+        }
+        write!(
+            result,
+            "{} {} {} {} ",
+            points.len() - 1,
+            points[0].0,
+            points[0].1,
+            procedure
+        )
+        .unwrap();
+        return;
+    }
+
+    write!(result, "newpath {} {} moveto ", points[0].0, points[0].1).unwrap();
+    for chunk in points[1..].chunks(pairs) {
+        for point in chunk.iter().rev() {
+            write!(result, "{} {} ", point.0, point.1).unwrap();
+        }
+        write!(result, "{} {{ lineto }} repeat ", chunk.len()).unwrap();
+    }
+    if procedure == "poly" {
+        result.push_str("closepath ");
+    }
+}
@@ -0,0 +1,328 @@
+use std::io::{Error, ErrorKind};
+
+use crate::path::PathSegment;
+
+struct Parser<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn skip_separators(&mut self) {
+        while self.position < self.data.len()
+            && (self.data[self.position].is_ascii_whitespace() || self.data[self.position] == b',')
+        {
+            self.position += 1;
+        }
+    }
+
+    fn error(&self, message: &str) -> Error {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Invalid SVG path data at {}: {}", self.position, message),
+        )
+    }
+
+    fn command(&mut self) -> Option<u8> {
+        self.skip_separators();
+        let byte = *self.data.get(self.position)?;
+        if byte.is_ascii_alphabetic() && byte != b'e' && byte != b'E' {
+            self.position += 1;
+            return Some(byte);
+        }
+        None
+    }
+
+    fn has_number(&mut self) -> bool {
+        self.skip_separators();
+        matches!(
+            self.data.get(self.position),
+            Some(b'0'..=b'9' | b'-' | b'+' | b'.')
+        )
+    }
+
+    fn number(&mut self) -> Result<f32, Error> {
+        self.skip_separators();
+        let start = self.position;
+        if matches!(self.data.get(self.position), Some(b'-' | b'+')) {
+            self.position += 1;
+        }
+        let mut seen_point = false;
+        while let Some(byte) = self.data.get(self.position) {
+            match byte {
+                b'0'..=b'9' => {}
+                b'.' if !seen_point => seen_point = true,
+                _ => break,
+            }
+            self.position += 1;
+        }
+        if matches!(self.data.get(self.position), Some(b'e' | b'E')) {
+            self.position += 1;
+            if matches!(self.data.get(self.position), Some(b'-' | b'+')) {
+                self.position += 1;
+            }
+            while matches!(self.data.get(self.position), Some(b'0'..=b'9')) {
+                self.position += 1;
+            }
+        }
+        std::str::from_utf8(&self.data[start..self.position])
+            .ok()
+            .and_then(|number| number.parse().ok())
+            .ok_or_else(|| {
+                self.position = start;
+                self.error("expected a number")
+            })
+    }
+
+    // Arc flags may be written without separators (eg: "a5 5 0 0110 10").
+    fn flag(&mut self) -> Result<bool, Error> {
+        self.skip_separators();
+        match self.data.get(self.position) {
+            Some(b'0') => {
+                self.position += 1;
+                Ok(false)
+            }
+            Some(b'1') => {
+                self.position += 1;
+                Ok(true)
+            }
+            _ => Err(self.error("expected an arc flag")),
+        }
+    }
+}
+
+fn round(value: f64) -> f32 {
+    ((value * 1000.0).round() / 1000.0 + 0.0) as f32
+}
+
+// Converts an SVG elliptical arc into cubic Bézier curves, following the endpoint to center
+// conversion in the SVG specification (appendix B.2.4).
+#[allow(clippy::too_many_arguments)]
+fn arc_to_curves(
+    from: (f32, f32),
+    rx: f32,
+    ry: f32,
+    rotation: f32,
+    large_arc: bool,
+    sweep: bool,
+    to: (f32, f32),
+    segments: &mut Vec<PathSegment>,
+) {
+    if from == to {
+        return;
+    }
+    let (x1, y1) = (from.0 as f64, from.1 as f64);
+    let (x2, y2) = (to.0 as f64, to.1 as f64);
+    let (mut rx, mut ry) = ((rx as f64).abs(), (ry as f64).abs());
+    if rx == 0.0 || ry == 0.0 {
+        segments.push(PathSegment::LineTo(to.0, to.1));
+        return;
+    }
+    let (sin, cos) = (rotation as f64).to_radians().sin_cos();
+    let dx = (x1 - x2) / 2.0;
+    let dy = (y1 - y2) / 2.0;
+    let x1p = cos * dx + sin * dy;
+    let y1p = -sin * dx + cos * dy;
+
+    let lambda = (x1p * x1p) / (rx * rx) + (y1p * y1p) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+    let numerator = rx * rx * ry * ry - rx * rx * y1p * y1p - ry * ry * x1p * x1p;
+    let denominator = rx * rx * y1p * y1p + ry * ry * x1p * x1p;
+    let mut coefficient = (numerator / denominator).max(0.0).sqrt();
+    if large_arc == sweep {
+        coefficient = -coefficient;
+    }
+    let cxp = coefficient * rx * y1p / ry;
+    let cyp = -coefficient * ry * x1p / rx;
+    let cx = cos * cxp - sin * cyp + (x1 + x2) / 2.0;
+    let cy = sin * cxp + cos * cyp + (y1 + y2) / 2.0;
+
+    let angle = |ux: f64, uy: f64, vx: f64, vy: f64| (ux * vy - uy * vx).atan2(ux * vx + uy * vy);
+    let start = angle(1.0, 0.0, (x1p - cxp) / rx, (y1p - cyp) / ry);
+    let mut delta = angle(
+        (x1p - cxp) / rx,
+        (y1p - cyp) / ry,
+        (-x1p - cxp) / rx,
+        (-y1p - cyp) / ry,
+    );
+    if !sweep && delta > 0.0 {
+        delta -= std::f64::consts::TAU;
+    } else if sweep && delta < 0.0 {
+        delta += std::f64::consts::TAU;
+    }
+
+    let point = |a: f64| {
+        (
+            cx + rx * a.cos() * cos - ry * a.sin() * sin,
+            cy + rx * a.cos() * sin + ry * a.sin() * cos,
+        )
+    };
+    let derivative = |a: f64| {
+        (
+            -rx * a.sin() * cos - ry * a.cos() * sin,
+            -rx * a.sin() * sin + ry * a.cos() * cos,
+        )
+    };
+    let count = (delta.abs() / std::f64::consts::FRAC_PI_2).ceil().max(1.0) as usize;
+    let step = delta / count as f64;
+    let t = 4.0 / 3.0 * (step / 4.0).tan();
+    for i in 0..count {
+        let a1 = start + step * i as f64;
+        let a2 = a1 + step;
+        let (p1, d1) = (point(a1), derivative(a1));
+        let (p2, d2) = (point(a2), derivative(a2));
+        let end = if i == count - 1 {
+            to
+        } else {
+            (round(p2.0), round(p2.1))
+        };
+        segments.push(PathSegment::CurveTo(
+            round(p1.0 + t * d1.0),
+            round(p1.1 + t * d1.1),
+            round(p2.0 - t * d2.0),
+            round(p2.1 - t * d2.1),
+            end.0,
+            end.1,
+        ));
+    }
+}
+
+// Parses SVG path data into path segments. Relative commands are resolved to absolute
+// coordinates, quadratic curves and arcs are converted to cubic curves.
+pub(crate) fn parse(data: &str) -> Result<Vec<PathSegment>, Error> {
+    let mut parser = Parser {
+        data: data.as_bytes(),
+        position: 0,
+    };
+    let mut segments = Vec::new();
+    let mut current = (0.0, 0.0);
+    let mut start = (0.0, 0.0);
+    // The last control point, used to reflect the first control point of S and T commands.
+    let mut last_cubic: Option<(f32, f32)> = None;
+    let mut last_quad: Option<(f32, f32)> = None;
+    let mut command = None;
+
+    loop {
+        let next = match parser.command() {
+            Some(next) => next,
+            None if parser.position >= parser.data.len() => break,
+            None => match command {
+                // Additional coordinates repeat the previous command (moveto becomes lineto).
+                Some(b'M') if parser.has_number() => b'L',
+                Some(b'm') if parser.has_number() => b'l',
+                Some(previous) if previous != b'Z' && previous != b'z' && parser.has_number() => {
+                    previous
+                }
+                _ => return Err(parser.error("expected a command")),
+            },
+        };
+        command = Some(next);
+        let relative = next.is_ascii_lowercase();
+        let offset = if relative { current } else { (0.0, 0.0) };
+        let mut cubic = None;
+        let mut quad = None;
+
+        match next.to_ascii_uppercase() {
+            b'M' => {
+                let point = (parser.number()? + offset.0, parser.number()? + offset.1);
+                segments.push(PathSegment::MoveTo(point.0, point.1));
+                current = point;
+                start = point;
+            }
+            b'L' => {
+                let point = (parser.number()? + offset.0, parser.number()? + offset.1);
+                segments.push(PathSegment::LineTo(point.0, point.1));
+                current = point;
+            }
+            b'H' => {
+                let x = parser.number()? + offset.0;
+                segments.push(PathSegment::LineTo(x, current.1));
+                current.0 = x;
+            }
+            b'V' => {
+                let y = parser.number()? + if relative { current.1 } else { 0.0 };
+                segments.push(PathSegment::LineTo(current.0, y));
+                current.1 = y;
+            }
+            b'C' | b'S' => {
+                let c1 = if next.eq_ignore_ascii_case(&b'C') {
+                    (parser.number()? + offset.0, parser.number()? + offset.1)
+                } else {
+                    match last_cubic {
+                        Some(c) => (2.0 * current.0 - c.0, 2.0 * current.1 - c.1),
+                        None => current,
+                    }
+                };
+                let c2 = (parser.number()? + offset.0, parser.number()? + offset.1);
+                let point = (parser.number()? + offset.0, parser.number()? + offset.1);
+                segments.push(PathSegment::CurveTo(
+                    c1.0, c1.1, c2.0, c2.1, point.0, point.1,
+                ));
+                cubic = Some(c2);
+                current = point;
+            }
+            b'Q' | b'T' => {
+                let control = if next.eq_ignore_ascii_case(&b'Q') {
+                    (parser.number()? + offset.0, parser.number()? + offset.1)
+                } else {
+                    match last_quad {
+                        Some(c) => (2.0 * current.0 - c.0, 2.0 * current.1 - c.1),
+                        None => current,
+                    }
+                };
+                let point = (parser.number()? + offset.0, parser.number()? + offset.1);
+                let (cx, cy) = (control.0 as f64, control.1 as f64);
+                segments.push(PathSegment::CurveTo(
+                    round(current.0 as f64 + 2.0 / 3.0 * (cx - current.0 as f64)),
+                    round(current.1 as f64 + 2.0 / 3.0 * (cy - current.1 as f64)),
+                    round(point.0 as f64 + 2.0 / 3.0 * (cx - point.0 as f64)),
+                    round(point.1 as f64 + 2.0 / 3.0 * (cy - point.1 as f64)),
+                    point.0,
+                    point.1,
+                ));
+                quad = Some(control);
+                current = point;
+            }
+            b'A' => {
+                let rx = parser.number()?;
+                let ry = parser.number()?;
+                let rotation = parser.number()?;
+                let large_arc = parser.flag()?;
+                let sweep = parser.flag()?;
+                let point = (parser.number()? + offset.0, parser.number()? + offset.1);
+                arc_to_curves(
+                    current,
+                    rx,
+                    ry,
+                    rotation,
+                    large_arc,
+                    sweep,
+                    point,
+                    &mut segments,
+                );
+                current = point;
+            }
+            b'Z' => {
+                segments.push(PathSegment::Close);
+                current = start;
+            }
+            _ => {
+                parser.position -= 1;
+                return Err(parser.error("unsupported command"));
+            }
+        }
+        last_cubic = cubic;
+        last_quad = quad;
+    }
+
+    if !matches!(segments.first(), None | Some(PathSegment::MoveTo(..))) {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "Invalid SVG path data: path data must begin with a moveto command",
+        ));
+    }
+    Ok(segments)
+}
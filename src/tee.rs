@@ -0,0 +1,63 @@
+use std::io::{Error, ErrorKind, Write};
+
+pub struct TeeWriter<'a> {
+    sinks: Vec<Box<dyn Write + 'a>>,
+    failed: bool,
+}
+
+impl<'a> Default for TeeWriter<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> TeeWriter<'a> {
+    pub fn new() -> Self {
+        TeeWriter {
+            sinks: Vec::new(),
+            failed: false,
+        }
+    }
+
+    pub fn with_sink<T: Write + 'a>(mut self, sink: T) -> Self {
+        self.sinks.push(Box::new(sink));
+        self
+    }
+
+    fn check_failed(&self) -> Result<(), Error> {
+        if self.failed {
+            return Err(Error::new(
+                ErrorKind::BrokenPipe,
+                "A sink failed earlier, so the sinks are out of step.",
+            ));
+        }
+        Ok(())
+    }
+}
+
+// Every sink receives the complete buffer, so a short write on one sink can't leave the others
+// out of step. When a sink fails the sinks before it already have the buffer, so retrying would
+// duplicate it; the tee is unusable after an error and every later call fails.
+impl<'a> Write for TeeWriter<'a> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        self.check_failed()?;
+        for sink in &mut self.sinks {
+            if let Err(error) = sink.write_all(buf) {
+                self.failed = true;
+                return Err(error);
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.check_failed()?;
+        for sink in &mut self.sinks {
+            if let Err(error) = sink.flush() {
+                self.failed = true;
+                return Err(error);
+            }
+        }
+        Ok(())
+    }
+}
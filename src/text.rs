@@ -0,0 +1,455 @@
+use crate::{
+    encoding::{self, escape_latin1, glyph_name},
+    metrics::{decorations, glyph_warnings, measure_text},
+    Font, FontMetrics, GroupItem, Serialize,
+};
+use std::{borrow::Cow, collections::HashMap, fmt::Write};
+
+pub(crate) enum TextColor {
+    Gray(f32),
+    Rgb([f32; 3]),
+    Cmyk([f32; 4]),
+}
+
+pub struct Text {
+    text: String,
+    x: f32,
+    y: f32,
+    font: String,
+    size: f32,
+    color: TextColor,
+    rotate: f32,
+    tracking: f32,
+    word_spacing: f32,
+    metrics: Option<FontMetrics>,
+    underline: bool,
+    strikethrough: bool,
+    fit_box: Option<(f32, f32)>,
+    min_size: f32,
+    truncate: Option<f32>,
+}
+
+impl TextColor {
+    pub(crate) fn write(&self, result: &mut String) {
+        match self {
+            TextColor::Gray(gray) => write!(result, "{} setgray ", gray).unwrap(),
+            TextColor::Rgb(rgb) => {
+                write!(result, "{} {} {} setrgbcolor ", rgb[0], rgb[1], rgb[2]).unwrap()
+            }
+            TextColor::Cmyk(cmyk) => write!(
+                result,
+                "{} {} {} {} setcmykcolor ",
+                cmyk[0], cmyk[1], cmyk[2], cmyk[3]
+            )
+            .unwrap(),
+        }
+    }
+}
+
+// Characters that would end a PostScript name are dropped.
+pub(crate) fn font_name<F: Into<Font>>(font: F) -> String {
+    encoding::name_text(font.into().name())
+}
+
+// Text without Latin-1 characters above ASCII is shown with the font's own encoding, since other
+// characters are shown by glyph name. Otherwise it's shown with a copy of the font re-encoded to
+// ISOLatin1Encoding by the latin1font procedure, except for Symbol and ZapfDingbats which have
+// their own character sets and CID-keyed fonts which are shown by glyph index.
+pub(crate) fn write_font(result: &mut String, font: &str, size: f32, text: &str, cid: bool) {
+    let latin1 = text.chars().any(|c| ('\u{80}'..='\u{FF}').contains(&c));
+    if !latin1 || cid || font == "Symbol" || font == "ZapfDingbats" {
+        write!(result, "/{} findfont {} scalefont setfont ", font, size).unwrap();
+    } else {
+        write!(
+            result,
+            "/{}-Latin1 /{} latin1font {} scalefont setfont ",
+            font, font, size
+        )
+        .unwrap();
+    }
+}
+
+// Extra space added when text is shown, in points: tracking after every character and word
+// spacing after every space. Kerning pairs are in 1/1000 em and scaled by the font size. Text in
+// a CID-keyed font is shown as two byte glyph indexes. The metrics also give the font's fallbacks.
+pub(crate) struct Spacing<'a> {
+    pub(crate) size: f32,
+    pub(crate) tracking: f32,
+    pub(crate) word_spacing: f32,
+    pub(crate) font: &'a str,
+    pub(crate) metrics: Option<&'a FontMetrics>,
+}
+
+impl Spacing<'_> {
+    fn kerning(&self, font: &str) -> Option<&HashMap<(char, char), f32>> {
+        self.metrics.and_then(|metrics| metrics.kerning(font))
+    }
+
+    pub(crate) fn glyphs(&self, font: &str) -> Option<&HashMap<char, u16>> {
+        self.metrics.and_then(|metrics| metrics.glyphs(font))
+    }
+
+    // The space added to the width of the text, not including kerning.
+    pub(crate) fn width(&self, text: &str) -> f32 {
+        self.tracking * text.chars().count() as f32
+            + self.word_spacing * text.chars().filter(|c| *c == ' ').count() as f32
+    }
+
+    fn write_run(
+        &self,
+        result: &mut String,
+        run: &mut String,
+        glyphs: Option<&HashMap<char, u16>>,
+    ) {
+        if run.is_empty() {
+            return;
+        }
+        // Characters the font doesn't have are shown with its .notdef glyph.
+        let (text, space) = match glyphs {
+            Some(glyphs) => {
+                let mut hex = String::from("<");
+                for c in run.chars() {
+                    write!(&mut hex, "{:04X}", glyphs.get(&c).copied().unwrap_or(0)).unwrap();
+                }
+                hex.push('>');
+                (hex, glyphs.get(&' ').copied().unwrap_or(0))
+            }
+            None => (format!("({})", escape_latin1(run)), 32),
+        };
+        if self.word_spacing != 0.0 {
+            write!(
+                result,
+                "{} 0 {} {} 0 {} awidthshow ",
+                self.word_spacing, space, self.tracking, text
+            )
+            .unwrap();
+        } else if self.tracking != 0.0 {
+            write!(result, "{} 0 {} ashow ", self.tracking, text).unwrap();
+        } else {
+            write!(result, "{} show ", text).unwrap();
+        }
+        run.clear();
+    }
+}
+
+// Characters the font doesn't have are shown in the first of its fallbacks that does. Fallback
+// fonts are selected for their run only; the current font is left on the stack and restored after.
+pub(crate) fn write_show(result: &mut String, text: &str, spacing: &Spacing) {
+    let runs = match spacing.metrics {
+        Some(metrics) => metrics.runs(spacing.font, text),
+        None => vec![(spacing.font, text.to_string())],
+    };
+    for (font, run) in runs {
+        if font == spacing.font {
+            write_show_in(result, &run, spacing, font);
+            continue;
+        }
+        result.push_str("currentfont ");
+        let glyphs = spacing.glyphs(font);
+        write_font(result, font, spacing.size, &run, glyphs.is_some());
+        write_show_in(result, &run, spacing, font);
+        result.push_str("setfont ");
+    }
+}
+
+// Characters outside Latin-1 are shown by glyph name. Kerned pairs are split between strings and
+// moved apart with rmoveto.
+fn write_show_in(result: &mut String, text: &str, spacing: &Spacing, font: &str) {
+    let kerning = spacing.kerning(font);
+    let glyphs = spacing.glyphs(font);
+    let mut run = String::new();
+    let mut previous = None;
+    for c in text.chars() {
+        let kern = previous
+            .zip(kerning)
+            .and_then(|(previous, kerning)| kerning.get(&(previous, c)))
+            .map_or(0.0, |kern| kern * spacing.size / 1000.0);
+        previous = Some(c);
+        if kern != 0.0 {
+            spacing.write_run(result, &mut run, glyphs);
+            write!(result, "{} 0 rmoveto ", (kern * 1000.0).round() / 1000.0).unwrap();
+        }
+        if (c as u32) <= 0xFF || glyphs.is_some() {
+            run.push(c);
+            continue;
+        }
+        spacing.write_run(result, &mut run, glyphs);
+        write!(result, "/{} glyphshow ", glyph_name(c)).unwrap();
+        if spacing.tracking != 0.0 {
+            write!(result, "{} 0 rmoveto ", spacing.tracking).unwrap();
+        }
+    }
+    spacing.write_run(result, &mut run, glyphs);
+}
+
+// Reorders a line of text from logical to visual order so right-to-left runs, eg: Hebrew and
+// Arabic, are shown from right to left with their brackets mirrored. The direction of the line is
+// taken from its first strong character. Without the bidi feature text is shown in logical order.
+#[cfg(feature = "bidi")]
+pub(crate) fn visual_order(text: &str) -> Cow<'_, str> {
+    let info = unicode_bidi::BidiInfo::new(text, None);
+    if !info.has_rtl() {
+        return Cow::Borrowed(text);
+    }
+    let mut result = String::with_capacity(text.len());
+    for paragraph in &info.paragraphs {
+        let (levels, runs) = info.visual_runs(paragraph, paragraph.range.clone());
+        for run in runs {
+            if levels[run.start].is_rtl() {
+                result.extend(text[run].chars().rev().map(|c| match c {
+                    '(' => ')',
+                    ')' => '(',
+                    '[' => ']',
+                    ']' => '[',
+                    '{' => '}',
+                    '}' => '{',
+                    '<' => '>',
+                    '>' => '<',
+                    '\u{AB}' => '\u{BB}',
+                    '\u{BB}' => '\u{AB}',
+                    _ => c,
+                }));
+            } else {
+                result.push_str(&text[run]);
+            }
+        }
+    }
+    Cow::Owned(result)
+}
+
+#[cfg(not(feature = "bidi"))]
+pub(crate) fn visual_order(text: &str) -> Cow<'_, str> {
+    Cow::Borrowed(text)
+}
+
+// Returns the largest size, in steps of half a point from the font size down to the minimum, at
+// which the text fits. Text that doesn't fit at any size is set at the minimum.
+pub(crate) fn fit_size<F: Fn(f32) -> bool>(size: f32, min_size: f32, fits: F) -> f32 {
+    let mut fitted = size;
+    while fitted > min_size && !fits(fitted) {
+        fitted = (fitted - 0.5).max(min_size);
+    }
+    fitted
+}
+
+// Shows the text and draws each decoration line, given by its position from the baseline and its
+// thickness, from where the text starts to where the device left the current point, so the lines
+// match the width the text was actually shown at. The current point is left at the end of the text.
+pub(crate) fn write_decorated(
+    result: &mut String,
+    text: &str,
+    spacing: &Spacing,
+    baseline: f32,
+    lines: &[(f32, f32)],
+) {
+    if lines.is_empty() {
+        write_show(result, text, spacing);
+        return;
+    }
+    result.push_str("currentpoint pop ");
+    write_show(result, text, spacing);
+    result.push_str("currentpoint ");
+    for (position, thickness) in lines {
+        write!(
+            result,
+            "newpath 2 index {y} moveto 1 index {y} lineto {} setlinewidth 0 setlinecap stroke ",
+            thickness,
+            y = baseline + position
+        )
+        .unwrap();
+    }
+    result.push_str("moveto pop ");
+}
+
+impl Text {
+    // The position is the start of the text baseline.
+    pub fn new(text: &str, x: f32, y: f32) -> Self {
+        Text {
+            text: text.to_string(),
+            x,
+            y,
+            font: "Helvetica".to_string(),
+            size: 12.0,
+            color: TextColor::Gray(0.0),
+            rotate: 0.0,
+            tracking: 0.0,
+            word_spacing: 0.0,
+            metrics: None,
+            underline: false,
+            strikethrough: false,
+            fit_box: None,
+            min_size: 6.0,
+            truncate: None,
+        }
+    }
+
+    pub fn font<F: Into<Font>>(mut self, font: F, size: f32) -> Self {
+        self.font = font_name(font);
+        self.size = size.max(0.0);
+        self
+    }
+
+    pub fn fill_gray(mut self, gray: f32) -> Self {
+        self.color = TextColor::Gray(gray.clamp(0.0, 1.0));
+        self
+    }
+
+    pub fn fill_rgb(mut self, r: f32, g: f32, b: f32) -> Self {
+        self.color = TextColor::Rgb([r.clamp(0.0, 1.0), g.clamp(0.0, 1.0), b.clamp(0.0, 1.0)]);
+        self
+    }
+
+    pub fn fill_cmyk(mut self, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.color = TextColor::Cmyk([
+            c.clamp(0.0, 1.0),
+            m.clamp(0.0, 1.0),
+            y.clamp(0.0, 1.0),
+            k.clamp(0.0, 1.0),
+        ]);
+        self
+    }
+
+    // Rotates the text counterclockwise around its position.
+    pub fn rotate(mut self, angle: f32) -> Self {
+        self.rotate = angle.clamp(-360.0, 360.0);
+        self
+    }
+
+    // Adds space after every character, in points.
+    pub fn tracking(mut self, tracking: f32) -> Self {
+        self.tracking = tracking;
+        self
+    }
+
+    // Adds space after every space character, in points.
+    pub fn word_spacing(mut self, word_spacing: f32) -> Self {
+        self.word_spacing = word_spacing;
+        self
+    }
+
+    // Kerning pairs are applied when the metrics have them for the font.
+    pub fn metrics(mut self, metrics: &FontMetrics) -> Self {
+        self.metrics = Some(metrics.clone());
+        self
+    }
+
+    // Reduces the font size, in steps of half a point down to the minimum size, until the text is
+    // no wider than the box and the font is no taller.
+    pub fn fit_box(mut self, width: f32, height: f32) -> Self {
+        self.fit_box = Some((width.max(0.0), height.max(0.0)));
+        self
+    }
+
+    // The smallest size text is shrunk to, 6pt by default.
+    pub fn min_size(mut self, size: f32) -> Self {
+        self.min_size = size.max(0.5);
+        self
+    }
+
+    // Text wider than the maximum width is cut short and ends with an ellipsis.
+    pub fn truncate(mut self, max_width: f32) -> Self {
+        self.truncate = Some(max_width.max(0.0));
+        self
+    }
+
+    pub fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    pub fn strikethrough(mut self) -> Self {
+        self.strikethrough = true;
+        self
+    }
+}
+
+impl Serialize for Text {
+    fn to_postscript_string(&self) -> String {
+        let mut result = String::new();
+
+        if self.text.is_empty() || self.size == 0.0 || self.font.is_empty() {
+            return result;
+        }
+
+        let spacing = |size| Spacing {
+            size,
+            tracking: self.tracking,
+            word_spacing: self.word_spacing,
+            font: &self.font,
+            metrics: self.metrics.as_ref(),
+        };
+        let measure = |text: &str, size| {
+            measure_text(self.metrics.as_ref(), text, &self.font, size) + spacing(size).width(text)
+        };
+        let size = match self.fit_box {
+            Some((width, height)) => fit_size(self.size, self.min_size, |size| {
+                measure(&self.text, size) <= width && size <= height
+            }),
+            None => self.size,
+        };
+
+        // Characters are dropped from the end until the rest of the text and an ellipsis fit.
+        let mut text = self.text.clone();
+        if let Some(max_width) = self.truncate {
+            if measure(&text, size) > max_width {
+                loop {
+                    text = text.trim_end().to_string();
+                    let candidate = format!("{}\u{2026}", text);
+                    if measure(&candidate, size) <= max_width {
+                        text = candidate;
+                        break;
+                    }
+                    if text.pop().is_none() {
+                        return result;
+                    }
+                }
+            }
+        }
+
+        result.push_str("gsave ");
+        self.color.write(&mut result);
+        write_font(
+            &mut result,
+            &self.font,
+            size,
+            &text,
+            spacing(size).glyphs(&self.font).is_some(),
+        );
+        let baseline = if self.rotate != 0.0 {
+            write!(
+                &mut result,
+                "{} {} translate {} rotate 0 0 moveto ",
+                self.x, self.y, self.rotate
+            )
+            .unwrap();
+            0.0
+        } else {
+            write!(&mut result, "{} {} moveto ", self.x, self.y).unwrap();
+            self.y
+        };
+        let lines = decorations(self.metrics.as_ref(), &self.font).lines(
+            size,
+            self.underline,
+            self.strikethrough,
+        );
+        write_decorated(
+            &mut result,
+            &visual_order(&text),
+            &spacing(size),
+            baseline,
+            &lines,
+        );
+        result.push_str("grestore ");
+
+        result
+    }
+
+    fn warnings(&self) -> Vec<String> {
+        if self.text.is_empty() || self.size == 0.0 || self.font.is_empty() {
+            return Vec::new();
+        }
+        glyph_warnings(self.metrics.as_ref(), &self.text, &self.font)
+    }
+}
+
+impl GroupItem for Text {}
@@ -0,0 +1,89 @@
+use std::fmt;
+
+// A 2x3 affine matrix in PostScript order ([a b c d tx ty]), mapping a point (x, y) to
+// (a * x + c * y + tx, b * x + d * y + ty).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub tx: f32,
+    pub ty: f32,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Transform {
+    pub fn new(a: f32, b: f32, c: f32, d: f32, tx: f32, ty: f32) -> Self {
+        Transform { a, b, c, d, tx, ty }
+    }
+
+    pub fn identity() -> Self {
+        Self::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)
+    }
+
+    pub fn translate(x: f32, y: f32) -> Self {
+        Self::new(1.0, 0.0, 0.0, 1.0, x, y)
+    }
+
+    pub fn scale(x: f32, y: f32) -> Self {
+        Self::new(x, 0.0, 0.0, y, 0.0, 0.0)
+    }
+
+    pub fn rotate(angle: f32) -> Self {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        Self::new(cos, sin, -sin, cos, 0.0, 0.0)
+    }
+
+    pub fn skew(x: f32, y: f32) -> Self {
+        Self::new(
+            1.0,
+            y.to_radians().tan(),
+            x.to_radians().tan(),
+            1.0,
+            0.0,
+            0.0,
+        )
+    }
+
+    // Returns the transform that applies this transform followed by `other`.
+    pub fn then(&self, other: &Transform) -> Self {
+        Self::new(
+            self.a * other.a + self.b * other.c,
+            self.a * other.b + self.b * other.d,
+            self.c * other.a + self.d * other.c,
+            self.c * other.b + self.d * other.d,
+            self.tx * other.a + self.ty * other.c + other.tx,
+            self.tx * other.b + self.ty * other.d + other.ty,
+        )
+    }
+
+    pub fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            self.a * x + self.c * y + self.tx,
+            self.b * x + self.d * y + self.ty,
+        )
+    }
+}
+
+// Values are rounded so trigonometry noise doesn't end up in the output.
+impl fmt::Display for Transform {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let round = |value: f32| (value * 100000.0).round() / 100000.0 + 0.0;
+        write!(
+            f,
+            "[{} {} {} {} {} {}]",
+            round(self.a),
+            round(self.b),
+            round(self.c),
+            round(self.d),
+            round(self.tx),
+            round(self.ty)
+        )
+    }
+}
@@ -0,0 +1,877 @@
+use crate::{
+    encoding::{self, glyph_name, UsedGlyphs},
+    metrics::{Decorations, Widths},
+    FontMetrics,
+};
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::Write,
+    fs,
+    io::{Error, ErrorKind},
+    path::Path,
+};
+
+// Tables the Type42 rasterizer uses. Everything else (names, kerning, layout tables) is dropped to
+// keep the font program small.
+const TABLES: [&[u8; 4]; 11] = [
+    b"cvt ", b"fpgm", b"glyf", b"head", b"hhea", b"hmtx", b"loca", b"maxp", b"prep", b"vhea",
+    b"vmtx",
+];
+
+// Strings in the sfnts array are limited to 65535 bytes including the padding byte.
+const MAX_STRING: usize = 65534;
+
+fn invalid(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message.to_string())
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, Error> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .ok_or_else(|| invalid("Unexpected end of TrueType data."))
+}
+
+fn read_i16(data: &[u8], offset: usize) -> Result<i16, Error> {
+    read_u16(data, offset).map(|value| value as i16)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, Error> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| invalid("Unexpected end of TrueType data."))
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+struct Tables<'a> {
+    data: &'a [u8],
+    entries: HashMap<[u8; 4], (usize, usize)>,
+}
+
+impl<'a> Tables<'a> {
+    fn parse(data: &'a [u8]) -> Result<Self, Error> {
+        match read_u32(data, 0)? {
+            0x00010000 | 0x74727565 => {}
+            0x4F54544F => {
+                return Err(invalid(
+                    "OpenType fonts with CFF outlines can't be converted to Type42.",
+                ))
+            }
+            _ => return Err(invalid("Not a TrueType font.")),
+        }
+        let count = read_u16(data, 4)? as usize;
+        let mut entries = HashMap::new();
+        for i in 0..count {
+            let record = 12 + i * 16;
+            let tag = data
+                .get(record..record + 4)
+                .ok_or_else(|| invalid("Unexpected end of TrueType data."))?;
+            let offset = read_u32(data, record + 8)? as usize;
+            let length = read_u32(data, record + 12)? as usize;
+            if offset
+                .checked_add(length)
+                .is_none_or(|end| end > data.len())
+            {
+                return Err(invalid("TrueType table extends past the end of the file."));
+            }
+            entries.insert([tag[0], tag[1], tag[2], tag[3]], (offset, length));
+        }
+        Ok(Tables { data, entries })
+    }
+
+    fn get(&self, tag: &[u8; 4]) -> Option<&'a [u8]> {
+        self.entries
+            .get(tag)
+            .map(|(offset, length)| &self.data[*offset..*offset + *length])
+    }
+
+    fn require(&self, tag: &[u8; 4]) -> Result<&'a [u8], Error> {
+        self.get(tag).ok_or_else(|| {
+            invalid(&format!(
+                "TrueType font is missing the {} table.",
+                String::from_utf8_lossy(tag).trim()
+            ))
+        })
+    }
+}
+
+// Lists the characters mapped by a format 4 cmap subtable with their glyph indexes. Symbol fonts
+// map their characters from 0xF000.
+fn cmap_entries(subtable: &[u8], base: u32) -> Result<Vec<(char, u16)>, Error> {
+    let segments = read_u16(subtable, 6)? as usize / 2;
+    let ends = 14;
+    let starts = ends + segments * 2 + 2;
+    let deltas = starts + segments * 2;
+    let ranges = deltas + segments * 2;
+    let mut entries = Vec::new();
+    for i in 0..segments {
+        let end = read_u16(subtable, ends + i * 2)? as u32;
+        let start = read_u16(subtable, starts + i * 2)? as u32;
+        let delta = read_u16(subtable, deltas + i * 2)?;
+        let range = read_u16(subtable, ranges + i * 2)? as usize;
+        for code in start..=end.min(0xFFFE) {
+            let glyph = if range == 0 {
+                (code as u16).wrapping_add(delta)
+            } else {
+                match read_u16(
+                    subtable,
+                    ranges + i * 2 + range + (code - start) as usize * 2,
+                )? {
+                    0 => 0,
+                    glyph => glyph.wrapping_add(delta),
+                }
+            };
+            if glyph == 0 || code < base {
+                continue;
+            }
+            if let Some(c) = char::from_u32(code - base) {
+                entries.push((c, glyph));
+            }
+        }
+    }
+    Ok(entries)
+}
+
+// Reads the glyph pairs from the horizontal format 0 subtables of a kern table.
+fn kern_pairs(kern: &[u8]) -> Result<Vec<(u16, u16, i16)>, Error> {
+    let mut pairs = Vec::new();
+    if read_u16(kern, 0)? != 0 {
+        return Ok(pairs);
+    }
+    let mut offset = 4;
+    for _ in 0..read_u16(kern, 2)? {
+        let length = read_u16(kern, offset + 2)? as usize;
+        let coverage = read_u16(kern, offset + 4)?;
+        // Format 0, horizontal kerning values rather than minimums or cross-stream.
+        if coverage & 0xFF07 == 0x0001 {
+            for i in 0..read_u16(kern, offset + 6)? as usize {
+                let pair = offset + 14 + i * 6;
+                pairs.push((
+                    read_u16(kern, pair)?,
+                    read_u16(kern, pair + 2)?,
+                    read_i16(kern, pair + 4)?,
+                ));
+            }
+        }
+        offset += length.max(6);
+    }
+    Ok(pairs)
+}
+
+// Returns a format 4 subtable and the offset added to character codes, which is 0xF000 for symbol
+// fonts.
+fn find_cmap(cmap: &[u8]) -> Result<(&[u8], u32), Error> {
+    let count = read_u16(cmap, 2)? as usize;
+    let mut found = None;
+    for i in 0..count {
+        let record = 4 + i * 8;
+        let platform = read_u16(cmap, record)?;
+        let encoding = read_u16(cmap, record + 2)?;
+        let offset = read_u32(cmap, record + 4)? as usize;
+        if read_u16(cmap, offset)? != 4 {
+            continue;
+        }
+        let length = read_u16(cmap, offset + 2)? as usize;
+        let subtable = cmap
+            .get(offset..offset + length)
+            .ok_or_else(|| invalid("Unexpected end of TrueType data."))?;
+        match (platform, encoding) {
+            (3, 1) => return Ok((subtable, 0)),
+            (0, _) => found = Some((subtable, 0)),
+            (3, 0) if found.is_none() => found = Some((subtable, 0xF000)),
+            _ => {}
+        }
+    }
+    found.ok_or_else(|| invalid("TrueType font has no supported cmap subtable."))
+}
+
+// The tag, offset and length of a table in the rebuilt sfnt.
+type TableLayout = ([u8; 4], usize, usize);
+
+// Builds a new sfnt containing only the tables listed in TABLES, with any replaced tables.
+fn rebuild(tables: &Tables, replaced: &HashMap<[u8; 4], Vec<u8>>) -> (Vec<u8>, Vec<TableLayout>) {
+    let table = |tag: &[u8; 4]| replaced.get(tag).map(Vec::as_slice).or(tables.get(tag));
+    let mut tags: Vec<&[u8; 4]> = TABLES
+        .iter()
+        .filter(|tag| table(tag).is_some())
+        .copied()
+        .collect();
+    tags.sort();
+    let count = tags.len();
+    let mut power = 1;
+    let mut selector = 0;
+    while power * 2 <= count {
+        power *= 2;
+        selector += 1;
+    }
+
+    let mut result = Vec::new();
+    result.extend_from_slice(&0x00010000u32.to_be_bytes());
+    result.extend_from_slice(&(count as u16).to_be_bytes());
+    result.extend_from_slice(&((power * 16) as u16).to_be_bytes());
+    result.extend_from_slice(&(selector as u16).to_be_bytes());
+    result.extend_from_slice(&((count * 16 - power * 16) as u16).to_be_bytes());
+
+    let mut offset = 12 + count * 16;
+    let mut layout = Vec::new();
+    for tag in &tags {
+        let mut data = table(tag).unwrap().to_vec();
+        if *tag == b"head" && data.len() >= 12 {
+            data[8..12].copy_from_slice(&[0; 4]);
+        }
+        result.extend_from_slice(*tag);
+        result.extend_from_slice(&checksum(&data).to_be_bytes());
+        result.extend_from_slice(&(offset as u32).to_be_bytes());
+        result.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        layout.push((**tag, offset, data.len()));
+        offset += (data.len() + 3) & !3;
+    }
+    for tag in &tags {
+        let data = table(tag).unwrap();
+        result.extend_from_slice(data);
+        if *tag == b"head" && data.len() >= 12 {
+            let start = result.len() - data.len();
+            result[start + 8..start + 12].copy_from_slice(&[0; 4]);
+        }
+        while result.len() % 4 != 0 {
+            result.push(0);
+        }
+    }
+    if let Some((_, head, _)) = layout.iter().find(|(tag, _, _)| tag == b"head") {
+        let adjustment = 0xB1B0AFBAu32.wrapping_sub(checksum(&result));
+        result[head + 8..head + 12].copy_from_slice(&adjustment.to_be_bytes());
+    }
+    (result, layout)
+}
+
+// The glyphs a composite glyph is built from.
+fn components(glyph: &[u8]) -> Vec<u16> {
+    let mut result = Vec::new();
+    if read_i16(glyph, 0).map_or(true, |contours| contours >= 0) {
+        return result;
+    }
+    let mut offset = 10;
+    while let (Ok(flags), Ok(component)) = (read_u16(glyph, offset), read_u16(glyph, offset + 2)) {
+        result.push(component);
+        offset += if flags & 0x0001 != 0 { 8 } else { 6 };
+        if flags & 0x0008 != 0 {
+            offset += 2;
+        } else if flags & 0x0040 != 0 {
+            offset += 4;
+        } else if flags & 0x0080 != 0 {
+            offset += 8;
+        }
+        if flags & 0x0020 == 0 {
+            break;
+        }
+    }
+    result
+}
+
+// A closed contour's points and whether each is on the curve.
+type Contour = Vec<(f32, f32, bool)>;
+
+// The contours of a glyph in font units as points flagged on or off the curve. Composite glyphs
+// are flattened; components positioned by matching points are placed at the origin.
+fn contours(
+    glyf: &[u8],
+    glyphs: &[usize],
+    index: u16,
+    depth: usize,
+) -> Result<Vec<Contour>, Error> {
+    let start = glyphs
+        .get(index as usize)
+        .copied()
+        .unwrap_or(0)
+        .min(glyf.len());
+    let end = glyphs
+        .get(index as usize + 1)
+        .copied()
+        .unwrap_or(0)
+        .clamp(start, glyf.len());
+    let glyph = &glyf[start..end];
+    if glyph.is_empty() || depth > 8 {
+        return Ok(Vec::new());
+    }
+
+    let byte = |offset: usize| {
+        glyph
+            .get(offset)
+            .copied()
+            .ok_or_else(|| invalid("Unexpected end of TrueType data."))
+    };
+    let count = read_i16(glyph, 0)?;
+    if count < 0 {
+        let mut result = Vec::new();
+        let mut offset = 10;
+        loop {
+            let flags = read_u16(glyph, offset)?;
+            let component = read_u16(glyph, offset + 2)?;
+            offset += 4;
+            let (dx, dy) = if flags & 0x0001 != 0 {
+                offset += 4;
+                (
+                    read_i16(glyph, offset - 4)? as f32,
+                    read_i16(glyph, offset - 2)? as f32,
+                )
+            } else {
+                offset += 2;
+                (
+                    byte(offset - 2)? as i8 as f32,
+                    byte(offset - 1)? as i8 as f32,
+                )
+            };
+            let (dx, dy) = if flags & 0x0002 != 0 {
+                (dx, dy)
+            } else {
+                (0.0, 0.0)
+            };
+            let f2dot14 = |offset: usize| read_i16(glyph, offset).map(|v| v as f32 / 16384.0);
+            let [a, b, c, d] = if flags & 0x0008 != 0 {
+                offset += 2;
+                let scale = f2dot14(offset - 2)?;
+                [scale, 0.0, 0.0, scale]
+            } else if flags & 0x0040 != 0 {
+                offset += 4;
+                [f2dot14(offset - 4)?, 0.0, 0.0, f2dot14(offset - 2)?]
+            } else if flags & 0x0080 != 0 {
+                offset += 8;
+                [
+                    f2dot14(offset - 8)?,
+                    f2dot14(offset - 6)?,
+                    f2dot14(offset - 4)?,
+                    f2dot14(offset - 2)?,
+                ]
+            } else {
+                [1.0, 0.0, 0.0, 1.0]
+            };
+            for contour in contours(glyf, glyphs, component, depth + 1)? {
+                result.push(
+                    contour
+                        .into_iter()
+                        .map(|(x, y, on)| (a * x + c * y + dx, b * x + d * y + dy, on))
+                        .collect(),
+                );
+            }
+            if flags & 0x0020 == 0 {
+                return Ok(result);
+            }
+        }
+    }
+
+    let count = count as usize;
+    let mut ends = Vec::with_capacity(count);
+    for i in 0..count {
+        ends.push(read_u16(glyph, 10 + i * 2)? as usize);
+    }
+    let points = ends.last().map_or(0, |end| end + 1);
+    let mut offset = 12 + count * 2 + read_u16(glyph, 10 + count * 2)? as usize;
+    let mut flags = Vec::with_capacity(points);
+    while flags.len() < points {
+        let flag = byte(offset)?;
+        offset += 1;
+        flags.push(flag);
+        if flag & 0x08 != 0 {
+            for _ in 0..byte(offset)? {
+                flags.push(flag);
+            }
+            offset += 1;
+        }
+    }
+    flags.truncate(points);
+
+    // Coordinates are deltas, either a byte with its sign in the flags or a signed word.
+    let mut read_coordinates = |short: u8, same: u8| -> Result<Vec<f32>, Error> {
+        let mut value = 0i32;
+        let mut result = Vec::with_capacity(points);
+        for flag in &flags {
+            if flag & short != 0 {
+                let delta = byte(offset)? as i32;
+                offset += 1;
+                value += if flag & same != 0 { delta } else { -delta };
+            } else if flag & same == 0 {
+                value += read_i16(glyph, offset)? as i32;
+                offset += 2;
+            }
+            result.push(value as f32);
+        }
+        Ok(result)
+    };
+    let xs = read_coordinates(0x02, 0x10)?;
+    let ys = read_coordinates(0x04, 0x20)?;
+
+    let mut result = Vec::with_capacity(count);
+    let mut first = 0;
+    for end in ends {
+        if end < first || end >= points {
+            return Err(invalid("Invalid TrueType glyph contours."));
+        }
+        result.push(
+            (first..=end)
+                .map(|i| (xs[i], ys[i], flags[i] & 0x01 != 0))
+                .collect(),
+        );
+        first = end + 1;
+    }
+    Ok(result)
+}
+
+// Splits the sfnt into strings that start at a table boundary, or at a glyph boundary within the
+// glyf table, as required by the Type42 format.
+fn split(sfnt: &[u8], layout: &[TableLayout], glyphs: &[usize]) -> Vec<Vec<u8>> {
+    let mut breaks = vec![0];
+    let mut previous = 12 + layout.len() * 16;
+    breaks.push(previous);
+    for (tag, offset, length) in layout {
+        let end = (offset + length + 3) & !3;
+        let mut points = vec![*offset, end];
+        if tag == b"glyf" {
+            points.extend(
+                glyphs
+                    .iter()
+                    .filter(|glyph| **glyph <= *length)
+                    .map(|glyph| offset + glyph),
+            );
+        }
+        points.sort();
+        for point in points {
+            // Tables without glyph boundaries are split at an even offset when they don't fit.
+            while point - previous > MAX_STRING {
+                previous += MAX_STRING;
+                breaks.push(previous);
+            }
+            if point > previous {
+                breaks.push(point);
+                previous = point;
+            }
+        }
+    }
+    breaks.dedup();
+
+    let mut strings: Vec<Vec<u8>> = Vec::new();
+    let mut start = 0;
+    for i in 1..breaks.len() {
+        if breaks[i] - start > MAX_STRING {
+            strings.push(sfnt[start..breaks[i - 1]].to_vec());
+            start = breaks[i - 1];
+        }
+    }
+    if start < sfnt.len() {
+        strings.push(sfnt[start..].to_vec());
+    }
+    strings
+}
+
+pub struct TrueTypeFont {
+    name: String,
+    bbox: [f32; 4],
+    characters: Vec<(char, u16)>,
+    glyph_count: usize,
+    data: Vec<u8>,
+    glyphs: Vec<usize>,
+    widths: Widths,
+    sfnts: Vec<Vec<u8>>,
+    cid: bool,
+}
+
+impl TrueTypeFont {
+    pub fn load(name: &str, path: &Path) -> Result<Self, Error> {
+        TrueTypeFont::from_bytes(name, &fs::read(path)?)
+    }
+
+    // Characters that would end a PostScript name are dropped from the font name.
+    pub fn from_bytes(name: &str, data: &[u8]) -> Result<Self, Error> {
+        let name = encoding::name_text(name);
+        if name.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Font name can't be empty.",
+            ));
+        }
+        let tables = Tables::parse(data)?;
+        let head = tables.require(b"head")?;
+        for tag in [b"hhea", b"hmtx", b"loca", b"glyf", b"maxp"] {
+            tables.require(tag)?;
+        }
+        let units = read_u16(head, 18)?.max(1) as f32;
+        let bbox = [
+            read_i16(head, 36)? as f32 / units,
+            read_i16(head, 38)? as f32 / units,
+            read_i16(head, 40)? as f32 / units,
+            read_i16(head, 42)? as f32 / units,
+        ];
+
+        let glyph_count = read_u16(tables.require(b"maxp")?, 4)? as usize;
+        let loca = tables.require(b"loca")?;
+        let mut glyphs = Vec::with_capacity(glyph_count + 1);
+        for i in 0..=glyph_count {
+            glyphs.push(match read_i16(head, 50)? {
+                0 => read_u16(loca, i * 2)? as usize * 2,
+                _ => read_u32(loca, i * 4)? as usize,
+            });
+        }
+
+        let (cmap, base) = find_cmap(tables.require(b"cmap")?)?;
+        let mut characters = cmap_entries(cmap, base)?;
+        characters.retain(|(_, glyph)| (*glyph as usize) < glyph_count);
+        characters.sort();
+        characters.dedup_by_key(|(c, _)| *c);
+
+        let hmtx = tables.require(b"hmtx")?;
+        let metrics = read_u16(tables.require(b"hhea")?, 34)?.max(1) as usize;
+        let advance = |glyph: u16| -> Result<f32, Error> {
+            let index = (glyph as usize).min(metrics - 1);
+            Ok(read_u16(hmtx, index * 4)? as f32 * 1000.0 / units)
+        };
+        let mut widths = HashMap::new();
+        for (c, glyph) in &characters {
+            widths.insert(*c, advance(*glyph)?);
+        }
+
+        let mut kerning = HashMap::new();
+        if let Some(kern) = tables.get(b"kern") {
+            let mut characters_by_glyph: HashMap<u16, Vec<char>> = HashMap::new();
+            for (c, glyph) in &characters {
+                characters_by_glyph.entry(*glyph).or_default().push(*c);
+            }
+            for (left, right, value) in kern_pairs(kern)? {
+                let (Some(lefts), Some(rights)) = (
+                    characters_by_glyph.get(&left),
+                    characters_by_glyph.get(&right),
+                ) else {
+                    continue;
+                };
+                for l in lefts {
+                    for r in rights {
+                        kerning.insert((*l, *r), value as f32 * 1000.0 / units);
+                    }
+                }
+            }
+        }
+
+        // The underline and strikeout positions in the post and OS/2 tables are the top of the
+        // line.
+        let scale = |value: i16| value as f32 * 1000.0 / units;
+        let mut decorations = Decorations::default();
+        if let Some(post) = tables.get(b"post") {
+            let thickness = scale(read_i16(post, 10)?);
+            decorations.underline_position = scale(read_i16(post, 8)?) - thickness / 2.0;
+            decorations.underline_thickness = thickness;
+        }
+        if let Some(os2) = tables.get(b"OS/2") {
+            let thickness = scale(read_i16(os2, 26)?);
+            decorations.strikeout_position = scale(read_i16(os2, 28)?) - thickness / 2.0;
+            decorations.strikeout_thickness = thickness;
+        }
+
+        let (sfnt, layout) = rebuild(&tables, &HashMap::new());
+        let sfnts = split(&sfnt, &layout, &glyphs);
+        Ok(TrueTypeFont {
+            name,
+            bbox,
+            characters,
+            glyph_count,
+            data: data.to_vec(),
+            glyphs,
+            widths: Widths::new(widths, advance(0)?, kerning, decorations),
+            sfnts,
+            cid: false,
+        })
+    }
+
+    // A copy of the font with only the glyphs that were used, and the glyphs composite glyphs are
+    // built from. Glyph indexes are unchanged; unused glyphs are left empty.
+    pub(crate) fn subset(&self, used: &UsedGlyphs) -> Result<TrueTypeFont, Error> {
+        let tables = Tables::parse(&self.data)?;
+        let glyf = tables.require(b"glyf")?;
+        let glyph = |index: u16| {
+            let start = self.glyphs[index as usize].min(glyf.len());
+            let end = self.glyphs[index as usize + 1].clamp(start, glyf.len());
+            &glyf[start..end]
+        };
+
+        let characters: Vec<(char, u16)> = self
+            .characters
+            .iter()
+            .filter(|(c, _)| used.characters.contains(c) || used.names.contains(&glyph_name(*c)))
+            .copied()
+            .collect();
+        let mut keep: BTreeSet<u16> = characters.iter().map(|(_, glyph)| *glyph).collect();
+        keep.insert(0);
+        if self.cid {
+            keep.extend(
+                used.indexes
+                    .iter()
+                    .filter(|index| (**index as usize) < self.glyph_count),
+            );
+        }
+        let mut pending: Vec<u16> = keep.iter().copied().collect();
+        while let Some(index) = pending.pop() {
+            for component in components(glyph(index)) {
+                if (component as usize) < self.glyph_count && keep.insert(component) {
+                    pending.push(component);
+                }
+            }
+        }
+
+        let mut subset_glyf = Vec::new();
+        let mut glyphs = Vec::with_capacity(self.glyph_count + 1);
+        for index in 0..self.glyph_count {
+            glyphs.push(subset_glyf.len());
+            if keep.contains(&(index as u16)) {
+                subset_glyf.extend_from_slice(glyph(index as u16));
+                while subset_glyf.len() % 4 != 0 {
+                    subset_glyf.push(0);
+                }
+            }
+        }
+        glyphs.push(subset_glyf.len());
+        let mut loca = Vec::new();
+        for offset in &glyphs {
+            match read_i16(tables.require(b"head")?, 50)? {
+                0 => loca.extend_from_slice(&((offset / 2) as u16).to_be_bytes()),
+                _ => loca.extend_from_slice(&(*offset as u32).to_be_bytes()),
+            }
+        }
+
+        let replaced = HashMap::from([(*b"glyf", subset_glyf), (*b"loca", loca)]);
+        let (sfnt, layout) = rebuild(&tables, &replaced);
+        Ok(TrueTypeFont {
+            name: self.name.clone(),
+            bbox: self.bbox,
+            characters: if self.cid {
+                self.characters.clone()
+            } else {
+                characters
+            },
+            glyph_count: self.glyph_count,
+            data: Vec::new(),
+            sfnts: split(&sfnt, &layout, &glyphs),
+            glyphs,
+            widths: self.widths.clone(),
+            cid: self.cid,
+        })
+    }
+
+    // Defines the font as a CIDFont with a Type0 font composed over it, for fonts with more
+    // characters than can be shown by glyph name, eg: Chinese, Japanese and Korean fonts. Text is
+    // shown by glyph index, so it must be given the font's metrics. Requires LanguageLevel 3.
+    pub fn cid_keyed(mut self) -> Self {
+        self.cid = true;
+        self.widths
+            .set_glyphs(Some(self.characters.iter().copied().collect()));
+        self
+    }
+
+    pub(crate) fn is_cid_keyed(&self) -> bool {
+        self.cid
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn widths(&self) -> &Widths {
+        &self.widths
+    }
+
+    // The text as a path built from the glyph outlines, with its baseline starting at x, y. The
+    // path can be filled like any other so the font doesn't need to be embedded at all.
+    pub fn outline(&self, text: &str, x: f32, y: f32, size: f32) -> Result<crate::Path, Error> {
+        let tables = Tables::parse(&self.data)?;
+        let glyf = tables.require(b"glyf")?;
+        let scale = size / read_u16(tables.require(b"head")?, 18)?.max(1) as f32;
+        let glyphs: HashMap<char, u16> = self.characters.iter().copied().collect();
+
+        let mut path = crate::Path::new();
+        let mut origin = x;
+        let mut previous = None;
+        for c in text.chars() {
+            if let Some(previous) = previous {
+                origin += self.widths.kerning(previous, c) * size / 1000.0;
+            }
+            let point = |(px, py, _): (f32, f32, bool)| (origin + px * scale, y + py * scale);
+            let glyph = glyphs.get(&c).copied().unwrap_or(0);
+            for contour in contours(glyf, &self.glyphs, glyph, 0)? {
+                // Two points off the curve in a row have an implied point on the curve halfway
+                // between them. A contour may start off the curve too.
+                let first = contour.iter().position(|(_, _, on)| *on);
+                let start = match first {
+                    Some(i) => contour[i],
+                    None => {
+                        let ((ax, ay, _), (bx, by, _)) = (contour[0], contour[contour.len() - 1]);
+                        ((ax + bx) / 2.0, (ay + by) / 2.0, true)
+                    }
+                };
+                let (sx, sy) = point(start);
+                path = path.move_to(sx, sy);
+                let mut control: Option<(f32, f32, bool)> = None;
+                let offset = first.map_or(0, |i| i + 1);
+                let count = contour.len() - usize::from(first.is_some());
+                for i in 0..count {
+                    let current = contour[(offset + i) % contour.len()];
+                    match (control, current.2) {
+                        (None, true) => {
+                            let (px, py) = point(current);
+                            path = path.line_to(px, py);
+                        }
+                        (None, false) => control = Some(current),
+                        (Some(off), true) => {
+                            let ((cx, cy), (px, py)) = (point(off), point(current));
+                            path = path.quad_to(cx, cy, px, py);
+                            control = None;
+                        }
+                        (Some(off), false) => {
+                            let middle =
+                                ((off.0 + current.0) / 2.0, (off.1 + current.1) / 2.0, true);
+                            let ((cx, cy), (px, py)) = (point(off), point(middle));
+                            path = path.quad_to(cx, cy, px, py);
+                            control = Some(current);
+                        }
+                    }
+                }
+                if let Some(off) = control {
+                    let ((cx, cy), (px, py)) = (point(off), (sx, sy));
+                    path = path.quad_to(cx, cy, px, py);
+                }
+                path = path.close();
+            }
+            origin += self.widths.advance(c) * size / 1000.0;
+            previous = Some(c);
+        }
+        Ok(path)
+    }
+
+    // Latin-1 characters are encoded by their character code. Every character in the font has a
+    // glyph name so characters outside Latin-1 can be shown with glyphshow.
+    pub fn definition(&self) -> String {
+        if self.cid {
+            return self.cid_definition();
+        }
+        let mut result = String::new();
+        write!(
+            &mut result,
+            "%%BeginResource: font {}\n11 dict begin\n/FontName /{} def\n/FontType 42 def\n/PaintType 0 def\n/FontMatrix [1 0 0 1 0 0] def\n/FontBBox [{} {} {} {}] def\n",
+            self.name, self.name, self.bbox[0], self.bbox[1], self.bbox[2], self.bbox[3]
+        )
+        .unwrap();
+        result.push_str("/Encoding 256 array\n0 1 255 { 1 index exch /.notdef put } for\n");
+        for (c, _) in &self.characters {
+            if matches!(*c as u32, 32..=126 | 160..=255) {
+                writeln!(&mut result, "dup {} /{} put", *c as u32, glyph_name(*c)).unwrap();
+            }
+        }
+        result.push_str("readonly def\n");
+        writeln!(
+            &mut result,
+            "/CharStrings {} dict dup begin\n/.notdef 0 def",
+            self.characters.len() + 1
+        )
+        .unwrap();
+        for (c, glyph) in &self.characters {
+            writeln!(&mut result, "/{} {} def", glyph_name(*c), glyph).unwrap();
+        }
+        result.push_str("end readonly def\n/sfnts [\n");
+        write_sfnts(&mut result, &self.sfnts);
+        result.push_str("] def\nFontName currentdict end definefont pop\n%%EndResource");
+        result
+    }
+
+    // CIDs are glyph indexes and are encoded as two bytes by the Identity-H CMap.
+    fn cid_definition(&self) -> String {
+        let mut result = String::new();
+        write!(
+            &mut result,
+            "%%BeginResource: font {}\n12 dict begin\n/CIDFontName /{}-CID def\n/CIDFontType 2 def\n/CIDSystemInfo 3 dict dup begin\n/Registry (Adobe) def\n/Ordering (Identity) def\n/Supplement 0 def\nend def\n/FontMatrix [1 0 0 1 0 0] def\n/FontBBox [{} {} {} {}] def\n/CIDCount {} def\n/GDBytes 2 def\n/CIDMap 0 def\n",
+            self.name, self.name, self.bbox[0], self.bbox[1], self.bbox[2], self.bbox[3], self.glyph_count
+        )
+        .unwrap();
+        result.push_str(
+            "/Encoding 256 array\n0 1 255 { 1 index exch /.notdef put } for\nreadonly def\n",
+        );
+        result.push_str(
+            "/CharStrings 1 dict dup begin\n/.notdef 0 def\nend readonly def\n/sfnts [\n",
+        );
+        write_sfnts(&mut result, &self.sfnts);
+        write!(
+            &mut result,
+            "] def\nCIDFontName currentdict end /CIDFont defineresource pop\n/{} /Identity-H [/{}-CID /CIDFont findresource] composefont pop\n%%EndResource",
+            self.name, self.name
+        )
+        .unwrap();
+        result
+    }
+}
+
+// Each string carries an extra padding byte which the interpreter ignores.
+fn write_sfnts(result: &mut String, sfnts: &[Vec<u8>]) {
+    for string in sfnts {
+        result.push('<');
+        for (i, byte) in string.iter().chain([0u8].iter()).enumerate() {
+            if i > 0 && i % 64 == 0 {
+                result.push('\n');
+            }
+            write!(result, "{:02X}", byte).unwrap();
+        }
+        result.push_str(">\n");
+    }
+}
+
+pub struct FontRegistry {
+    fonts: HashMap<String, TrueTypeFont>,
+}
+
+impl Default for FontRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FontRegistry {
+    pub fn new() -> Self {
+        FontRegistry {
+            fonts: HashMap::new(),
+        }
+    }
+
+    pub fn load_ttf(&mut self, name: &str, path: &Path) -> Result<(), Error> {
+        self.add_font(TrueTypeFont::load(name, path)?);
+        Ok(())
+    }
+
+    pub fn load_cid_ttf(&mut self, name: &str, path: &Path) -> Result<(), Error> {
+        self.add_font(TrueTypeFont::load(name, path)?.cid_keyed());
+        Ok(())
+    }
+
+    pub fn add_font(&mut self, font: TrueTypeFont) {
+        self.fonts.insert(font.name.clone(), font);
+    }
+
+    pub fn get_font(&self, name: &str) -> Option<&TrueTypeFont> {
+        self.fonts.get(name)
+    }
+
+    pub fn list_fonts(&self) -> Vec<&TrueTypeFont> {
+        self.fonts.values().collect()
+    }
+
+    pub(crate) fn into_fonts(self) -> Vec<TrueTypeFont> {
+        self.fonts.into_values().collect()
+    }
+
+    // Metrics for the base fonts and every font in the registry.
+    pub fn metrics(&self) -> FontMetrics {
+        let mut metrics = FontMetrics::new();
+        for font in self.fonts.values() {
+            metrics.add_font(font);
+        }
+        metrics
+    }
+}
@@ -0,0 +1,168 @@
+use crate::{
+    style::{StrokeStyle, TransformStyle},
+    ColorMode, GroupItem, Serialize, Transform,
+};
+use std::fmt::Write;
+
+pub struct Vignette {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    corner_radius: f32,
+    buffer: String,
+    warnings: Vec<String>,
+    fade_width: f32,
+    fade_steps: u32,
+    fade_rgb: [f32; 3],
+    fade_cmyk: [f32; 4],
+    fade_mode: ColorMode,
+    transform: TransformStyle,
+}
+
+impl Vignette {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Vignette {
+            x,
+            y,
+            width: width.max(0.0),
+            height: height.max(0.0),
+            corner_radius: 0.0,
+            buffer: String::new(),
+            warnings: Vec::new(),
+            fade_width: 0.0,
+            fade_steps: 0,
+            fade_rgb: [1.0, 1.0, 1.0],
+            fade_cmyk: [0.0, 0.0, 0.0, 0.0],
+            fade_mode: ColorMode::RGB,
+            transform: TransformStyle::new(),
+        }
+    }
+
+    pub fn add<T: Serialize>(&mut self, item: &T) {
+        self.buffer.push_str(&item.to_postscript_string());
+        self.warnings.extend(item.warnings());
+    }
+
+    pub fn corner_radius(mut self, radius: f32) -> Self {
+        self.corner_radius = radius.max(0.0);
+        self
+    }
+
+    pub fn fade_rgb(mut self, width: f32, steps: u32, r: f32, g: f32, b: f32) -> Self {
+        self.fade_width = width.max(0.0);
+        self.fade_steps = steps;
+        self.fade_rgb = [r.clamp(0.0, 1.0), g.clamp(0.0, 1.0), b.clamp(0.0, 1.0)];
+        self.fade_mode = ColorMode::RGB;
+        self
+    }
+
+    pub fn fade_cmyk(mut self, width: f32, steps: u32, c: f32, m: f32, y: f32, k: f32) -> Self {
+        self.fade_width = width.max(0.0);
+        self.fade_steps = steps;
+        self.fade_cmyk = [
+            c.clamp(0.0, 1.0),
+            m.clamp(0.0, 1.0),
+            y.clamp(0.0, 1.0),
+            k.clamp(0.0, 1.0),
+        ];
+        self.fade_mode = ColorMode::CMYK;
+        self
+    }
+
+    pub fn transform(mut self, matrix: Transform) -> Self {
+        self.transform.matrix(matrix);
+        self
+    }
+
+    // Writes the outline of the vignette shape inset by the given distance.
+    fn write_shape(&self, result: &mut String, inset: f32) {
+        let width = (self.width - inset * 2.0).max(0.0);
+        let height = (self.height - inset * 2.0).max(0.0);
+        if self.corner_radius > 0.0 {
+            let radius = (self.corner_radius - inset)
+                .min(width / 2.0)
+                .min(height / 2.0)
+                .max(0.0);
+            write!(
+                result,
+                "{} {} {} {} {} rrect ",
+                round(radius),
+                round(width),
+                round(height),
+                round(self.x + inset),
+                round(self.y + inset)
+            )
+            .unwrap();
+        } else {
+            write!(
+                result,
+                "{} {} {} {} ellipse ",
+                round(self.x + self.width / 2.0),
+                round(self.y + self.height / 2.0),
+                round(width / 2.0),
+                round(height / 2.0)
+            )
+            .unwrap();
+        }
+    }
+}
+
+fn round(value: f32) -> f32 {
+    (value * 1000.0).round() / 1000.0 + 0.0
+}
+
+impl Serialize for Vignette {
+    fn to_postscript_string(&self) -> String {
+        let mut result = String::new();
+
+        if self.width == 0.0 || self.height == 0.0 {
+            return result;
+        }
+
+        self.transform
+            .begin(&mut result, self.x, self.y, self.width, self.height);
+        result.push_str("gsave ");
+        self.write_shape(&mut result, 0.0);
+        result.push_str("clip newpath ");
+        result.push_str(&self.buffer);
+
+        // The fading edge is simulated with concentric bands of the edge color that lighten
+        // towards the center.
+        if self.fade_width > 0.0 && self.fade_steps > 0 {
+            let band = self.fade_width / self.fade_steps as f32;
+            for step in 0..self.fade_steps {
+                let tint = 1.0 - step as f32 / self.fade_steps as f32;
+                let mut stroke = StrokeStyle::new(0.0);
+                match self.fade_mode {
+                    ColorMode::RGB => stroke.rgb(
+                        round(band),
+                        round(1.0 - tint * (1.0 - self.fade_rgb[0])),
+                        round(1.0 - tint * (1.0 - self.fade_rgb[1])),
+                        round(1.0 - tint * (1.0 - self.fade_rgb[2])),
+                    ),
+                    ColorMode::CMYK => stroke.cmyk(
+                        round(band),
+                        round(tint * self.fade_cmyk[0]),
+                        round(tint * self.fade_cmyk[1]),
+                        round(tint * self.fade_cmyk[2]),
+                        round(tint * self.fade_cmyk[3]),
+                    ),
+                }
+                self.write_shape(&mut result, band * (step as f32 + 0.5));
+                stroke.write(&mut result);
+            }
+        }
+
+        result.push_str("grestore ");
+        self.transform.end(&mut result);
+
+        result
+    }
+
+    fn warnings(&self) -> Vec<String> {
+        self.warnings.clone()
+    }
+}
+
+impl GroupItem for Vignette {}
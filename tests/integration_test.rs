@@ -1,11 +1,23 @@
 use pslib::{
-    Document, DocumentBuilder, DocumentType, Line, Page, ProcedureRegistry, Rect,
-    TransformLineOrigin,
+    Accounting, Arc, Border, BorderMotif, Cancelled, ChangeBar, ChapterStart, CheckDigit, Clip,
+    ColorPatch, ColorPatches, Condition, DieLine, Document, DocumentBuilder, DocumentType,
+    FillRule, FinishingMarks, Font, FontMetrics, FontRegistry, Form, FormRegistry, Group,
+    HatchPattern, ImageFit, ImageRegistry, InlineImage, LanguageLevel, Line, LineCap, LineEnding,
+    LineJoin, Marker, MarkerSymbol, Numbering, Orientation, PacedWriter, Page, PaintOrder,
+    Paragraph, Polygon, Polyline, Procedure, ProcedureRegistry, Rect, RegularPolygon,
+    ResourcePolicy, RevisionCloud, RevisionMark, RichText, Ring, ScoreDirection, Serialize,
+    SlugLine, SlugPosition, Span, Star, StepOrientation, StepRepeat, TabAlignment, TabStop,
+    TechnicalSeparation, TeeWriter, Text, Transform, TransformLineOrigin, TransformOrigin,
+    TrueTypeFont, Vignette, Wedge,
 };
 use std::{
+    cell::RefCell,
     fs::{self, OpenOptions},
-    io::{BufWriter, Error},
+    io::{BufWriter, Error, ErrorKind, Write},
     path::Path,
+    rc::Rc,
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
 };
 
 #[test]
@@ -14,7 +26,11 @@ fn test_ps_file() -> Result<(), Error> {
     if path.exists() {
         let _ = fs::remove_file(path);
     }
-    let file = OpenOptions::new().write(true).create(true).open(path)?;
+    let file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)?;
     let writer = BufWriter::new(&file);
 
     let mut doc = Document::new(writer);
@@ -55,7 +71,11 @@ fn test_eps_file() -> Result<(), Error> {
     if path.exists() {
         let _ = fs::remove_file(path);
     }
-    let file = OpenOptions::new().write(true).create(true).open(path)?;
+    let file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)?;
 
     let mut doc = DocumentBuilder::builder()
         .document_type(DocumentType::EPS)
@@ -73,3 +93,3160 @@ fn test_eps_file() -> Result<(), Error> {
 
     Ok(())
 }
+
+#[test]
+fn test_conditional_rendering() -> Result<(), Error> {
+    let mut page = Page::new(400, 400);
+    let stamp = Rect::new(10.0, 10.0, 50.0, 20.0).fill_rgb(1.0, 0.0, 0.0);
+    page.add_if(Condition::flag("past_due"), &stamp)?;
+    let notice = Rect::new(20.0, 20.0, 30.0, 30.0).fill_rgb(0.0, 1.0, 0.0);
+    page.add_if(Condition::not_flag("past_due"), &notice)?;
+
+    let mut output = Vec::new();
+    let mut doc = Document::new(BufWriter::new(&mut output));
+    doc.set_flag("past_due", true);
+    doc.add(&page)?;
+    doc.set_flag("past_due", false);
+    doc.add(&page)?;
+    doc.close()?;
+
+    let output = String::from_utf8(output).unwrap();
+    let pages: Vec<&str> = output.split("%%Page:").collect();
+    assert_eq!(pages.len(), 3);
+    assert!(pages[1].contains("10 10 rect"));
+    assert!(!pages[1].contains("20 20 rect"));
+    assert!(!pages[2].contains("10 10 rect"));
+    assert!(pages[2].contains("20 20 rect"));
+
+    // Warnings are only reported for the pages a block is rendered on
+    let mut page = Page::new(400, 400);
+    page.add(&Text::new("\u{3A9}", 0.0, 0.0))?;
+    page.add_if(
+        Condition::flag("past_due"),
+        &Text::new("\u{3A3}", 0.0, 20.0),
+    )?;
+    let mut output = Vec::new();
+    let report = {
+        let mut doc = Document::new(BufWriter::new(&mut output));
+        doc.add(&page)?;
+        doc.set_flag("past_due", true);
+        doc.add(&page)?;
+        doc.close()?
+    };
+    assert_eq!(
+        report.warnings(),
+        [
+            "Page 1: Helvetica has no glyphs for \"\u{3A9}\".",
+            "Page 2: Helvetica has no glyphs for \"\u{3A9}\".",
+            "Page 2: Helvetica has no glyphs for \"\u{3A3}\".",
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_polygon() {
+    let polygon = Polygon::new(vec![(0.0, 0.0), (100.0, 0.0), (50.0, 80.0)])
+        .fill_rgb(0.0, 0.0, 1.0)
+        .stroke_cmyk(1.0, 0.0, 0.0, 0.0, 1.0);
+    assert_eq!(
+        polygon.to_postscript_string(),
+        "50 80 100 0 2 0 0 poly 0 0 1 fillrgb 0 0 0 1 1 strokecmyk "
+    );
+
+    let empty = Polygon::new(vec![(0.0, 0.0), (100.0, 0.0)]);
+    assert_eq!(empty.to_postscript_string(), "");
+}
+
+#[test]
+fn test_revision_marks() -> Result<(), Error> {
+    let cloud = RevisionCloud::new(0.0, 0.0, 20.0, 10.0).arc_length(10.0);
+    assert_eq!(
+        cloud.to_postscript_string(),
+        "newpath 5 0 5 180 360 arc 15 0 5 180 360 arc 20 5 5 270 450 arc \
+         15 10 5 0 180 arc 5 10 5 0 180 arc 0 5 5 90 270 arc closepath 0 0 0 1 strokergb "
+    );
+
+    let mut page = Page::new(400, 400);
+    page.tag_region("clause-4", 72.0, 300.0, 200.0, 40.0);
+    page.mark_revision("clause-4", RevisionMark::ChangeBar)?;
+    assert!(page.mark_revision("clause-5", RevisionMark::Cloud).is_err());
+
+    let bar = ChangeBar::new(36.0, 300.0, 40.0);
+    assert_eq!(
+        bar.to_postscript_string(),
+        "0 40 36 300 line 0 0 0 2 strokergb "
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_rounded_rect() {
+    let rect = Rect::new(10.0, 20.0, 100.0, 40.0)
+        .corner_radius(8.0)
+        .fill_rgb(0.0, 0.5, 0.0);
+    assert_eq!(
+        rect.to_postscript_string(),
+        "8 100 40 10 20 rrect 0 0.5 0 fillrgb "
+    );
+
+    let clamped = Rect::new(0.0, 0.0, 100.0, 40.0)
+        .corner_radius(50.0)
+        .fill_rgb(0.0, 0.0, 0.0);
+    assert!(clamped
+        .to_postscript_string()
+        .starts_with("20 100 40 0 0 rrect"));
+}
+
+#[test]
+fn test_arc() {
+    let arc = Arc::new(100.0, 100.0, 50.0, 0.0, 90.0).stroke_rgb(2.0, 1.0, 0.0, 0.0);
+    assert_eq!(
+        arc.to_postscript_string(),
+        "100 100 50 0 90 arcline 1 0 0 2 strokergb "
+    );
+
+    let pie = Arc::new(100.0, 100.0, 50.0, 0.0, 90.0)
+        .pie()
+        .fill_cmyk(0.0, 0.0, 1.0, 0.0);
+    assert_eq!(
+        pie.to_postscript_string(),
+        "100 100 50 0 90 sector 0 0 1 0 fillcmyk "
+    );
+
+    // Without pie() a filled arc is closed with a chord
+    let chord = Arc::new(100.0, 100.0, 50.0, 0.0, 90.0).fill_rgb(1.0, 0.0, 0.0);
+    assert_eq!(
+        chord.to_postscript_string(),
+        "100 100 50 0 90 arcline 1 0 0 fillrgb "
+    );
+}
+
+#[test]
+fn test_page_background_form() -> Result<(), Error> {
+    let mut letterhead = Form::new("letterhead", 612, 792);
+    letterhead.add(&Rect::new(0.0, 742.0, 612.0, 50.0).fill_rgb(0.0, 0.0, 0.5));
+    let mut forms = FormRegistry::new();
+    forms.add_form(letterhead);
+
+    let mut output = Vec::new();
+    let mut doc = DocumentBuilder::builder()
+        .writer(BufWriter::new(&mut output))
+        .load_procedures(ProcedureRegistry::with_builtins())
+        .load_forms(forms)
+        .build();
+    let mut page = Page::new(612, 792);
+    page.set_background("letterhead")?;
+    doc.add(&page)?;
+    doc.close()?;
+
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains(
+        "/letterhead << /FormType 1 /BBox [0 0 612 792] /Matrix [1 0 0 1 0 0] /PaintProc { pop -612 0 0 -50 612 0 0 50 0 742 rect 0 0 0.5 fillrgb } >> def\n"
+    ));
+    assert!(output.contains(
+        "%%BeginPageSetup\n<< /PageSize [612 792] >> setpagedevice\nletterhead execform\n%%EndPageSetup\n"
+    ));
+
+    // The background name is cleaned up the same way as the form's own name
+    let mut page = Page::new(612, 792);
+    page.set_background("Letter Head")?;
+    let mut output = Vec::new();
+    {
+        let mut doc = Document::new(BufWriter::new(&mut output));
+        doc.add(&page)?;
+        doc.close()?;
+    }
+    assert_eq!(Form::new("Letter Head", 612, 792).name(), "LetterHead");
+    assert!(String::from_utf8(output)
+        .unwrap()
+        .contains("\nLetterHead execform\n"));
+    let error = page.set_background(" (/) ").err().unwrap();
+    assert_eq!(error.kind(), ErrorKind::InvalidInput);
+
+    Ok(())
+}
+
+#[test]
+fn test_color_patches() -> Result<(), Error> {
+    let patches = ColorPatches::new(10.0)
+        .gap(2.0)
+        .patch(ColorPatch::cmyk(0.0, 0.0, 0.0, 1.0).expected_lab(16.0, 0.0, 0.0))
+        .patch(ColorPatch::cmyk(1.0, 0.0, 0.0, 0.0));
+
+    let mut page = Page::new(648, 828);
+    assert_eq!(
+        page.add_color_patches(&patches).unwrap_err().kind(),
+        ErrorKind::NotFound
+    );
+    page.set_trim_box(18.0, 18.0, 612.0, 792.0);
+    page.add(&Rect::new(0.0, 0.0, 10.0, 10.0))?;
+    page.add_color_patches(&patches)?;
+
+    let mut output = Vec::new();
+    let mut doc = DocumentBuilder::builder()
+        .writer(BufWriter::new(&mut output))
+        .build();
+    doc.add(&page)?;
+    doc.close()?;
+
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains(
+        "\n%%PslibColorPatch: 1 18 4 10 CMYK 0 0 0 1 Lab 16 0 0\n\
+gsave 0 0 0 1 setcmykcolor newpath 18 4 moveto 10 0 rlineto 0 10 rlineto -10 0 rlineto closepath fill grestore\n\
+%%PslibColorPatch: 2 30 4 10 CMYK 1 0 0 0\n"
+    ));
+
+    let mut page = Page::new(648, 828);
+    page.set_trim_box(18.0, 8.0, 612.0, 792.0);
+    assert_eq!(
+        page.add_color_patches(&patches).unwrap_err().kind(),
+        ErrorKind::InvalidInput
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_slug_line() -> Result<(), Error> {
+    let slug = SlugLine::new()
+        .job_id("J-1042")
+        .operator("kim")
+        .field("Press", "2");
+    let mut page = Page::new(648, 828);
+    assert_eq!(
+        page.add_slug_line(&slug).unwrap_err().kind(),
+        ErrorKind::NotFound
+    );
+    page.set_trim_box(18.0, 18.0, 612.0, 792.0);
+    page.add_slug_line(&slug)?;
+    page.add_slug_line(&slug.position(SlugPosition::Bottom).font(Font::Courier, 8.0))?;
+
+    let mut output = Vec::new();
+    let mut doc = DocumentBuilder::builder()
+        .writer(BufWriter::new(&mut output))
+        .build();
+    doc.add(&page)?;
+    doc.close()?;
+
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains(
+        "/Helvetica findfont 6 scalefont setfont 18 816 moveto (Job: J-1042  |  Operator: kim  |  Press: 2) show "
+    ));
+    assert!(output.contains("/Courier findfont 8 scalefont setfont 18 5 moveto (Job: J-1042"));
+
+    let slug = SlugLine::new().datetime().font(Font::Helvetica, 20.0);
+    let mut page = Page::new(648, 828);
+    page.set_trim_box(18.0, 18.0, 612.0, 792.0);
+    assert_eq!(
+        page.add_slug_line(&slug).unwrap_err().kind(),
+        ErrorKind::InvalidInput
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_finishing_marks() -> Result<(), Error> {
+    let marks = FinishingMarks::new()
+        .grommets(60.0, 10.0, 8.0)
+        .drill_hole(18.0, 50.0, 6.0)
+        .score(ScoreDirection::Vertical, 100.0)
+        .score(ScoreDirection::Horizontal, 50.0);
+    let mut page = Page::new(272, 172);
+    assert_eq!(
+        page.add_finishing_marks(&marks).unwrap_err().kind(),
+        ErrorKind::NotFound
+    );
+    page.set_trim_box(36.0, 36.0, 200.0, 100.0);
+    page.add_finishing_marks(&marks)?;
+
+    let mut output = Vec::new();
+    let mut doc = DocumentBuilder::builder()
+        .writer(BufWriter::new(&mut output))
+        .build();
+    doc.add(&page)?;
+    doc.close()?;
+
+    let output = String::from_utf8(output).unwrap();
+    assert_eq!(output.matches(" 4 0 360 arc stroke ").count(), 10);
+    assert!(output.contains(
+        "gsave 1 1 1 1 setcmykcolor 0.5 setlinewidth newpath 46 46 4 0 360 arc stroke newpath 106 46 4 0 360 arc stroke newpath 166 46 4 0 360 arc stroke newpath 226 46 4 0 360 arc stroke newpath 226 86 4 0 360 arc stroke "
+    ));
+    assert!(output.contains(
+        "newpath 54 86 3 0 360 arc stroke newpath 49.5 86 moveto 9 0 rlineto 54 81.5 moveto 0 9 rlineto stroke "
+    ));
+    assert!(output.contains("136 139 moveto 0 12 rlineto 136 33 moveto 0 -12 rlineto stroke"));
+    assert!(
+        output.contains("33 86 moveto -12 0 rlineto 239 86 moveto 12 0 rlineto stroke grestore")
+    );
+
+    let mut page = Page::new(272, 172);
+    page.set_trim_box(10.0, 36.0, 200.0, 100.0);
+    assert_eq!(
+        page.add_finishing_marks(&marks).unwrap_err().kind(),
+        ErrorKind::InvalidInput
+    );
+    page.set_trim_box(36.0, 36.0, 200.0, 100.0);
+    assert_eq!(
+        page.add_finishing_marks(&FinishingMarks::new().grommets(60.0, 50.0, 8.0))
+            .unwrap_err()
+            .kind(),
+        ErrorKind::InvalidInput
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_landscape_page() -> Result<(), Error> {
+    let mut output = Vec::new();
+    let mut doc = DocumentBuilder::builder()
+        .writer(BufWriter::new(&mut output))
+        .build();
+    doc.add(&Page::new(612, 792))?;
+    let mut table = Page::new(612, 792);
+    table.set_orientation(Orientation::Landscape);
+    table.add(&Text::new("Page 2", 36.0, 20.0))?;
+    doc.add(&table)?;
+    doc.close()?;
+
+    let output = String::from_utf8(output).unwrap();
+    assert_eq!(output.matches("%%PageOrientation: Landscape\n").count(), 1);
+    assert!(output.contains(
+        "%%Page: 2 2\n%%PageBoundingBox: 0 0 612 792\n%%PageOrientation: Landscape\n<< /PageSize [612 792] >> setpagedevice\ngsave 612 0 translate 90 rotate\n"
+    ));
+    assert!(output.contains("(Page 2) show grestore grestore\nshowpage\n"));
+
+    Ok(())
+}
+
+#[test]
+fn test_conditional_pages() -> Result<(), Error> {
+    let mut invoice = Page::new(612, 792);
+    invoice.add(&Text::new("Invoice", 72.0, 720.0))?;
+    let mut terms = Page::new(612, 792);
+    terms.add(&Text::new("Terms", 72.0, 720.0))?;
+
+    for duplex in [false, true] {
+        let mut output = Vec::new();
+        let mut doc = DocumentBuilder::builder()
+            .writer(BufWriter::new(&mut output))
+            .duplex(duplex)
+            .build();
+        for new_customer in [true, false, true] {
+            doc.set_flag("new_customer", new_customer);
+            doc.add(&invoice)?;
+            doc.add_if(Condition::flag("new_customer"), &terms)?;
+        }
+        let report = doc.close()?;
+
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.matches("(Terms) show").count(), 2);
+        assert_eq!(
+            output.contains("<< /Duplex true >> setpagedevice\n"),
+            duplex
+        );
+        if duplex {
+            // The skipped terms page is replaced with a blank back.
+            assert_eq!(report.page_count(), 6);
+            assert!(output.contains("%%Page: 4 4\n%%PageBoundingBox: 0 0 612 792\n<< /PageSize [612 792] >> setpagedevice\nshowpage\n%%Page: 5 5\n"));
+        } else {
+            assert_eq!(report.page_count(), 5);
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_bookmarks() -> Result<(), Error> {
+    let page = Page::new(612, 792);
+    let mut output = Vec::new();
+    let mut doc = DocumentBuilder::builder()
+        .writer(BufWriter::new(&mut output))
+        .build();
+    doc.bookmark("Acme (1042)");
+    doc.add(&page)?;
+    doc.add(&page)?;
+    doc.bookmark("Zo\u{EB}");
+    doc.add(&page)?;
+    doc.bookmark("Unused");
+    let report = doc.close()?;
+
+    assert_eq!(
+        report.bookmarks(),
+        [
+            ("Acme (1042)".to_string(), 1, 2),
+            ("Zo\u{EB}".to_string(), 3, 3)
+        ]
+    );
+    let output = String::from_utf8(output).unwrap();
+    assert!(output
+        .contains("%%Page: 1 1\n%%PageBoundingBox: 0 0 612 792\n%%BeginPageSetup\n/pdfmark where"));
+    assert!(output.contains("[ /Title (Acme \\(1042\\)) /OUT pdfmark\n"));
+    assert!(output.contains("[ /Title <FEFF005A006F00EB> /OUT pdfmark\n"));
+    assert_eq!(output.matches("/OUT pdfmark").count(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_chapter_start() -> Result<(), Error> {
+    let mut output = Vec::new();
+    let mut doc = DocumentBuilder::builder()
+        .writer(BufWriter::new(&mut output))
+        .chapter_start(ChapterStart::OddPage)
+        .build();
+    let mut title = Page::new(612, 792);
+    title.add(&Text::new("Chapter 1", 72.0, 720.0))?;
+    doc.add_chapter(&title)?;
+    doc.add(&Page::new(612, 792))?;
+    let mut title = Page::new(612, 792);
+    title.add(&Text::new("Chapter 2", 72.0, 720.0))?;
+    doc.add_chapter(&title)?;
+    doc.add_blank_page(612, 792)?;
+    doc.close()?;
+
+    let output = String::from_utf8(output).unwrap();
+    assert_eq!(output.matches("%%Page: ").count(), 4);
+    let chapter = output.find("(Chapter 2)").unwrap();
+    assert!(output[..chapter].contains("%%Page: 3 3\n"));
+    assert!(output.ends_with(
+        "%%Page: 4 4\n%%PageBoundingBox: 0 0 612 792\n<< /PageSize [612 792] >> setpagedevice\nshowpage\n%%Trailer\n%%Pages: 4\n%%EOF"
+    ));
+
+    let mut output = Vec::new();
+    let mut doc = DocumentBuilder::builder()
+        .writer(BufWriter::new(&mut output))
+        .chapter_start(ChapterStart::OddPage)
+        .build();
+    doc.add(&Page::new(612, 792))?;
+    doc.add_chapter(&Page::new(500, 700))?;
+    doc.close()?;
+
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains(
+        "%%Page: 2 2\n%%PageBoundingBox: 0 0 500 700\n<< /PageSize [500 700] >> setpagedevice\nshowpage\n%%Page: 3 3\n"
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_step_repeat() {
+    let labels = StepRepeat::new("label", 100.0, 50.0)
+        .origin(10.0, 20.0)
+        .grid(2, 2)
+        .gap(4.0, 10.0)
+        .bleed(3.0)
+        .orientation(StepOrientation::HeadToHead);
+    let output = labels.to_postscript_string();
+    assert_eq!(output.matches("label execform").count(), 4);
+    assert!(output.starts_with(
+        "gsave newpath 8 17 moveto 104 0 rlineto 0 56 rlineto -104 0 rlineto closepath clip newpath 7 17 translate label execform grestore \
+gsave newpath 112 17 moveto"
+    ));
+    assert!(output.contains(
+        "gsave newpath 8 77 moveto 104 0 rlineto 0 56 rlineto -104 0 rlineto closepath clip newpath 113 133 translate 180 rotate label execform grestore "
+    ));
+
+    assert_eq!(
+        StepRepeat::new("label", 0.0, 50.0).to_postscript_string(),
+        ""
+    );
+
+    // The form name is cleaned up the same way as by Form::new
+    let output = StepRepeat::new("Shipping Label", 100.0, 50.0).to_postscript_string();
+    assert!(output.contains(" ShippingLabel execform "));
+    assert_eq!(
+        StepRepeat::new(" ( ) ", 100.0, 50.0).to_postscript_string(),
+        ""
+    );
+}
+
+#[test]
+fn test_die_line() -> Result<(), Error> {
+    let outline = || {
+        pslib::Path::new()
+            .move_to(10.0, 10.0)
+            .line_to(60.0, 10.0)
+            .line_to(60.0, 40.0)
+            .close()
+    };
+    assert_eq!(
+        DieLine::new(outline()).to_postscript_string(),
+        "newpath 10 10 moveto 60 10 lineto 60 40 lineto closepath gsave [/Separation (CutContour) /DeviceCMYK { dup 0 mul exch dup 1 mul exch dup 0 mul exch 0 mul }] setcolorspace 1 setcolor true setoverprint 0.25 setlinewidth stroke grestore "
+    );
+    let kiss_cut = DieLine::new(outline())
+        .spot_color("Kiss Cut", 0.0, 0.5, 1.0, 0.0)
+        .width(0.5)
+        .to_postscript_string();
+    assert!(kiss_cut.contains("[/Separation (Kiss Cut) /DeviceCMYK { dup 0 mul exch dup 0.5 mul exch dup 1 mul exch 0 mul }]"));
+    assert!(kiss_cut.contains("0.5 setlinewidth stroke"));
+    assert_eq!(
+        DieLine::new(outline()).width(0.0).to_postscript_string(),
+        ""
+    );
+
+    let mut output = Vec::new();
+    let doc = DocumentBuilder::builder()
+        .writer(BufWriter::new(&mut output))
+        .custom_color("CutContour", 0.0, 1.0, 0.0, 0.0)
+        .custom_color("White", 0.0, 0.0, 0.0, 0.1)
+        .build();
+    doc.close()?;
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains(
+        "%%DocumentCustomColors: (CutContour) (White)\n%%CMYKCustomColor: 0 1 0 0 (CutContour)\n%%CMYKCustomColor: 0 0 0 0.1 (White)\n%%EndComments"
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_technical_separation() {
+    let mut white = TechnicalSeparation::white().alternate_cmyk(0.2, 0.0, 0.0, 0.0);
+    white.add(&Rect::new(10.0, 10.0, 50.0, 20.0).fill_rgb(1.0, 0.0, 0.0));
+    assert_eq!(
+        white.to_postscript_string(),
+        "gsave 4 dict begin /spotcolor { [/Separation (White) /DeviceCMYK { dup 0.2 mul exch dup 0 mul exch dup 0 mul exch 0 mul }] setcolorspace 1 setcolor } def /setgray { pop spotcolor } def /setrgbcolor { pop pop pop spotcolor } def /setcmykcolor { pop pop pop pop spotcolor } def true setoverprint \
+-50 0 0 -20 50 0 0 20 10 10 rect 1 0 0 fillrgb end grestore "
+    );
+
+    let mut varnish = TechnicalSeparation::varnish().tint(0.5).overprint(false);
+    varnish.add(&Text::new("Gloss", 0.0, 0.0));
+    let output = varnish.to_postscript_string();
+    assert!(output.contains("/Separation (Varnish)"));
+    assert!(output.contains("0.5 setcolor } def"));
+    assert!(output.contains("false setoverprint gsave 0 setgray"));
+
+    assert_eq!(TechnicalSeparation::white().to_postscript_string(), "");
+
+    // Warnings of the content are reported
+    let mut white = TechnicalSeparation::white();
+    white.add(&Text::new("\u{3A9}", 0.0, 0.0));
+    assert_eq!(
+        white.warnings(),
+        ["Helvetica has no glyphs for \"\u{3A9}\"."]
+    );
+}
+
+#[test]
+fn test_binary_tokens() -> Result<(), Error> {
+    let mut output = Vec::new();
+    let mut doc = DocumentBuilder::builder()
+        .writer(BufWriter::new(&mut output))
+        .binary_tokens(true)
+        .build();
+    let mut page = Page::new(400, 400);
+    page.add(&Rect::new(5.0, 5.0, 10.0, 20.0).fill_rgb(1.0, 0.0, 0.5))?;
+    doc.add(&page)?;
+    doc.close()?;
+
+    let expected: Vec<u8> = [
+        &[136, 246, 136, 0, 136, 0, 136, 236][..],
+        // 10 would be encoded with a line feed byte
+        b" 10 ",
+        &[136, 0, 136, 0, 136, 20, 136, 5, 136, 5][..],
+        b" rect ",
+        &[136, 1, 136, 0, 138, 0x3f, 0, 0, 0],
+        b" fillrgb ",
+    ]
+    .concat();
+    assert!(output
+        .windows(expected.len())
+        .any(|window| window == expected.as_slice()));
+    assert!(output
+        .windows(b"%%DocumentData: Binary\n".len())
+        .any(|window| window == b"%%DocumentData: Binary\n"));
+    assert!(output
+        .windows(b"%%PageBoundingBox: 0 0 400 400\n".len())
+        .any(|window| window == b"%%PageBoundingBox: 0 0 400 400\n"));
+
+    // Binary tokens need LanguageLevel 2
+    let mut output = Vec::new();
+    let report = {
+        let mut doc = DocumentBuilder::builder()
+            .writer(BufWriter::new(&mut output))
+            .language_level(LanguageLevel::Level1)
+            .binary_tokens(true)
+            .build();
+        let mut page = Page::new(400, 400);
+        page.add(&Rect::new(5.0, 5.0, 10.0, 20.0).fill_rgb(1.0, 0.0, 0.5))?;
+        doc.add(&page)?;
+        doc.close()?
+    };
+    assert!(output.iter().all(|byte| *byte < 128));
+    assert!(!String::from_utf8(output)
+        .unwrap()
+        .contains("%%DocumentData: Binary"));
+    assert_eq!(
+        report.warnings(),
+        ["Binary tokens are not written at LanguageLevel 1."]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_path() {
+    let path = pslib::Path::new()
+        .move_to(0.0, 0.0)
+        .line_to(100.0, 0.0)
+        .curve_to(120.0, 20.0, 120.0, 60.0, 100.0, 80.0)
+        .quad_to(50.0, 120.0, 0.0, 80.0)
+        .arc_to(0.0, 0.0, 100.0, 0.0, 10.0)
+        .close()
+        .stroke_rgb(1.0, 0.0, 0.0, 0.0);
+    assert_eq!(
+        path.to_postscript_string(),
+        "newpath 0 0 moveto 100 0 lineto 120 20 120 60 100 80 curveto 50 120 0 80 quadto \
+         0 0 100 0 10 arcto 4 { pop } repeat closepath 0 0 0 1 strokergb "
+    );
+    assert_eq!(
+        pslib::Path::new()
+            .fill_rgb(1.0, 0.0, 0.0)
+            .to_postscript_string(),
+        ""
+    );
+
+    // Segments that need a current point are ignored until the path has one
+    let path = pslib::Path::new()
+        .curve_to(0.0, 10.0, 10.0, 10.0, 10.0, 0.0)
+        .quad_to(5.0, 5.0, 10.0, 0.0)
+        .arc_to(0.0, 0.0, 10.0, 0.0, 2.0)
+        .close()
+        .move_to(0.0, 0.0)
+        .curve_to(0.0, 10.0, 10.0, 10.0, 10.0, 0.0)
+        .stroke_rgb(1.0, 0.0, 0.0, 0.0);
+    assert_eq!(
+        path.to_postscript_string(),
+        "newpath 0 0 moveto 0 10 10 10 10 0 curveto 0 0 0 1 strokergb "
+    );
+    for path in [
+        pslib::Path::new().curve_to(0.0, 10.0, 10.0, 10.0, 10.0, 0.0),
+        pslib::Path::new().quad_to(5.0, 5.0, 10.0, 0.0),
+        pslib::Path::new().arc_to(0.0, 0.0, 10.0, 0.0, 2.0),
+        pslib::Path::new().close(),
+    ] {
+        assert_eq!(
+            path.stroke_rgb(1.0, 0.0, 0.0, 0.0).to_postscript_string(),
+            ""
+        );
+    }
+
+    // A leading line_to starts the path
+    let path = pslib::Path::new()
+        .line_to(10.0, 10.0)
+        .line_to(20.0, 10.0)
+        .stroke_rgb(1.0, 0.0, 0.0, 0.0);
+    assert_eq!(
+        path.to_postscript_string(),
+        "newpath 10 10 moveto 20 10 lineto 0 0 0 1 strokergb "
+    );
+}
+
+#[test]
+fn test_svg_path_data() -> Result<(), Error> {
+    let path = pslib::Path::from_svg_data("M10 10 h20 v20 H10 Z m5,5 l5-5 q5 5 10 0 t10 0")?
+        .stroke_rgb(1.0, 0.0, 0.0, 0.0);
+    assert_eq!(
+        path.to_postscript_string(),
+        "newpath 10 10 moveto 30 10 lineto 30 30 lineto 10 30 lineto closepath 15 15 moveto \
+         20 10 lineto 23.333 13.333 26.667 13.333 30 10 curveto \
+         33.333 6.667 36.667 6.667 40 10 curveto 0 0 0 1 strokergb "
+    );
+    let path =
+        pslib::Path::from_svg_data("M0,0L.5.5-1e1,2 A5 5 0 0 1 0 10")?.fill_rgb(0.0, 0.0, 0.0);
+    assert!(path
+        .to_postscript_string()
+        .starts_with("newpath 0 0 moveto 0.5 0.5 lineto -10 2 lineto "));
+    assert!(path.to_postscript_string().contains(" 0 10 curveto "));
+
+    let error = pslib::Path::from_svg_data("M0 0 L 5").err().unwrap();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+    assert!(pslib::Path::from_svg_data("L0 0").is_err());
+    assert!(pslib::Path::from_svg_data("M0 0 X 5").is_err());
+
+    Ok(())
+}
+
+struct RawPostScript<'a>(&'a str);
+
+impl Serialize for RawPostScript<'_> {
+    fn to_postscript_string(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+#[test]
+fn test_line_endings_and_seven_bit() -> Result<(), Error> {
+    let mut output = Vec::new();
+    let mut doc = DocumentBuilder::builder()
+        .writer(BufWriter::new(&mut output))
+        .line_ending(LineEnding::CRLF)
+        .seven_bit(true)
+        .binary_tokens(true)
+        .build();
+    let mut page = Page::new(400, 400);
+    page.add(&RawPostScript(
+        "% caf\u{e9} \u{2014}\u{1F600}\n10 10 moveto (caf\u{e9}) show\n\
+         <~%9~> pop (caf\u{e9}) show <~(9~> pop (caf\u{e9}) show\n",
+    ))?;
+    doc.add(&page)?;
+    doc.close()?;
+
+    assert!(output.iter().all(|byte| *byte < 128));
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("%%DocumentData: Clean7Bit\r\n%%EndComments\r\n"));
+    assert!(output.contains("% cafe ??\r\n10 10 moveto (caf\\303\\251) show\r\n"));
+    // ASCII85 strings don't start comments or strings
+    assert!(output.contains("<~%9~> pop (caf\\303\\251) show <~(9~> pop (caf\\303\\251) show\r\n"));
+    assert!(!output.replace("\r\n", "").contains('\n'));
+
+    Ok(())
+}
+
+#[test]
+fn test_polyline() {
+    let polyline = Polyline::new(vec![(0.0, 0.0), (10.0, 10.0), (20.0, 0.0), (30.0, 10.0)])
+        .stroke_rgb(2.0, 0.0, 0.0, 1.0);
+    assert_eq!(
+        polyline.to_postscript_string(),
+        "30 10 20 0 10 10 3 0 0 pline 0 0 1 2 strokergb "
+    );
+
+    let styled = Polyline::new(vec![(0.0, 0.0), (10.0, 10.0), (20.0, 0.0), (30.0, 10.0)])
+        .segment_rgb(1, 4.0, 1.0, 0.0, 0.0);
+    assert_eq!(
+        styled.to_postscript_string(),
+        "10 10 1 0 0 pline 0 0 0 1 strokergb \
+         20 0 1 10 10 pline 1 0 0 4 strokergb \
+         30 10 1 20 0 pline 0 0 0 1 strokergb "
+    );
+}
+
+#[test]
+fn test_max_line_length() -> Result<(), Error> {
+    let mut output = Vec::new();
+    let mut doc = DocumentBuilder::builder()
+        .writer(BufWriter::new(&mut output))
+        .load_procedures(ProcedureRegistry::with_builtins())
+        .max_line_length(40)
+        .build();
+    let mut page = Page::new(400, 400);
+    let points = (0..50).map(|i| (i as f32 * 7.5, (i % 7) as f32)).collect();
+    page.add(&Polyline::new(points))?;
+    page.add(&RawPostScript(
+        "(a long string that will not fit on a single line \\351) show <0123456789abcdef0123456789abcdef0123456789abcdef> pop\n",
+    ))?;
+    doc.add(&page)?;
+    doc.close()?;
+
+    let output = String::from_utf8(output).unwrap();
+    assert!(output
+        .lines()
+        .filter(|line| !line.starts_with('%'))
+        .all(|line| line.len() <= 40));
+    assert!(output.contains("\\\n"));
+    assert!(output.contains("\\351"));
+    assert!(output.contains("<0123456789abcdef"));
+
+    // ASCII85 data is never split before a '%', which would start a comment line.
+    let data = format!("<~{}~>", "a%".repeat(60));
+    for offset in 0..4 {
+        let mut output = Vec::new();
+        let mut doc = DocumentBuilder::builder()
+            .writer(BufWriter::new(&mut output))
+            .max_line_length(40)
+            .build();
+        let mut page = Page::new(400, 400);
+        page.add(&RawPostScript(&format!(
+            "{} {} pop\n",
+            "x".repeat(offset),
+            data
+        )))?;
+        doc.add(&page)?;
+        doc.close()?;
+        let output = String::from_utf8(output).unwrap();
+        assert!(output
+            .lines()
+            .filter(|line| !line.starts_with("%%") && !line.starts_with("%!"))
+            .all(|line| line.len() <= 40 && !line.starts_with('%')));
+        assert!(output.replace('\n', "").contains(&data));
+    }
+
+    // Lines aren't wrapped unless a limit is set.
+    let long = "x".repeat(300);
+    let mut output = Vec::new();
+    let mut doc = DocumentBuilder::builder()
+        .writer(BufWriter::new(&mut output))
+        .build();
+    let mut page = Page::new(400, 400);
+    page.add(&Text::new(&long, 0.0, 0.0))?;
+    doc.add(&page)?;
+    doc.close()?;
+    assert!(String::from_utf8(output)
+        .unwrap()
+        .contains(&format!("({})", long)));
+
+    Ok(())
+}
+
+#[test]
+fn test_regular_polygon_and_star() {
+    let square = RegularPolygon::new(50.0, 50.0, 10.0, 4).fill_rgb(0.0, 0.0, 0.0);
+    assert_eq!(
+        square.to_postscript_string(),
+        "60 50 50 40 40 50 3 50 60 poly 0 0 0 fillrgb "
+    );
+
+    let star = Star::new(0.0, 0.0, 10.0, 5.0, 5).stroke_rgb(1.0, 0.0, 0.0, 0.0);
+    let output = star.to_postscript_string();
+    assert!(output.contains(" 9 0 10 poly "));
+    assert!(output.ends_with("0 0 0 1 strokergb "));
+}
+
+#[test]
+fn test_wedge() {
+    let wedge = Wedge::new(100.0, 100.0, 40.0, 30.0, 120.0)
+        .fill_cmyk(0.0, 1.0, 0.0, 0.0)
+        .stroke_rgb(0.5, 1.0, 1.0, 1.0);
+    assert_eq!(
+        wedge.to_postscript_string(),
+        "100 100 40 30 120 sector 0 1 0 0 fillcmyk 1 1 1 0.5 strokergb "
+    );
+    assert_eq!(
+        Wedge::new(0.0, 0.0, 10.0, 0.0, 90.0).to_postscript_string(),
+        ""
+    );
+}
+
+#[test]
+fn test_ring() {
+    let ring = Ring::new(100.0, 100.0, 50.0, 30.0).fill_rgb(0.0, 0.5, 0.0);
+    assert_eq!(
+        ring.to_postscript_string(),
+        "100 100 50 30 ring 0 0.5 0 fillrgb "
+    );
+    assert_eq!(
+        Ring::new(0.0, 0.0, 10.0, 20.0)
+            .fill_rgb(0.0, 0.0, 0.0)
+            .to_postscript_string(),
+        ""
+    );
+}
+
+#[test]
+fn test_marker() {
+    let marker = Marker::new(10.0, 20.0, MarkerSymbol::Diamond, 6.0).fill_rgb(1.0, 0.0, 0.0);
+    assert_eq!(
+        marker.to_postscript_string(),
+        "10 20 6 mdiamond 1 0 0 fillrgb "
+    );
+    let marker = Marker::new(10.0, 20.0, MarkerSymbol::Cross, 6.0).stroke_rgb(0.5, 0.0, 0.0, 0.0);
+    assert_eq!(
+        marker.to_postscript_string(),
+        "10 20 6 mcross 0 0 0 0.5 strokergb "
+    );
+    let registry = ProcedureRegistry::with_builtins();
+    for name in [
+        "mcircle",
+        "msquare",
+        "mcross",
+        "mplus",
+        "mtriangle",
+        "mdiamond",
+    ] {
+        assert!(registry.get_procedure(name).is_some());
+    }
+}
+
+#[test]
+fn test_vignette() {
+    let mut vignette = Vignette::new(0.0, 0.0, 200.0, 100.0).fade_rgb(10.0, 2, 0.0, 0.0, 0.0);
+    vignette.add(&Rect::new(0.0, 0.0, 200.0, 100.0).fill_rgb(1.0, 0.0, 0.0));
+    assert_eq!(
+        vignette.to_postscript_string(),
+        "gsave 100 50 100 50 ellipse clip newpath \
+         -200 0 0 -100 200 0 0 100 0 0 rect 1 0 0 fillrgb \
+         100 50 97.5 47.5 ellipse 0 0 0 5 strokergb \
+         100 50 92.5 42.5 ellipse 0.5 0.5 0.5 5 strokergb grestore "
+    );
+
+    let vignette = Vignette::new(10.0, 10.0, 100.0, 100.0).corner_radius(20.0);
+    assert_eq!(
+        vignette.to_postscript_string(),
+        "gsave 20 100 100 10 10 rrect clip newpath grestore "
+    );
+
+    // Warnings of the content are reported
+    let mut vignette = Vignette::new(0.0, 0.0, 100.0, 100.0);
+    vignette.add(&Text::new("\u{3A9}", 0.0, 0.0));
+    assert_eq!(
+        vignette.warnings(),
+        ["Helvetica has no glyphs for \"\u{3A9}\"."]
+    );
+}
+
+#[test]
+fn test_hatch() {
+    let rect = Rect::new(0.0, 0.0, 100.0, 50.0)
+        .hatch(HatchPattern::Cross, 4.0, 0.5)
+        .hatch_cmyk(0.0, 0.0, 0.0, 1.0);
+    assert_eq!(
+        rect.to_postscript_string(),
+        "-100 0 0 -50 100 0 0 50 0 0 rect gsave 0 0 0 1 setcmykcolor \
+         0 0 100 50 4 45 0.5 hatch 0 0 100 50 4 -45 0.5 hatch grestore "
+    );
+    let polygon = Polygon::new(vec![(0.0, 0.0), (10.0, 20.0), (20.0, 0.0)])
+        .hatch(HatchPattern::Horizontal, 2.0, 0.25)
+        .stroke_rgb(1.0, 0.0, 0.0, 0.0);
+    assert_eq!(
+        polygon.to_postscript_string(),
+        "20 0 10 20 2 0 0 poly gsave 0 0 0 setrgbcolor 0 0 20 20 2 0 0.25 hatch grestore \
+         0 0 0 1 strokergb "
+    );
+}
+
+#[test]
+fn test_shadow() {
+    let rect = Rect::new(10.0, 10.0, 20.0, 20.0)
+        .fill_rgb(1.0, 1.0, 1.0)
+        .shadow_rgb(2.0, -2.0, 0.5, 0.5, 0.5);
+    assert_eq!(
+        rect.to_postscript_string(),
+        "gsave 2 -2 translate -20 0 0 -20 20 0 0 20 10 10 rect 0.5 0.5 0.5 fillrgb grestore \
+         -20 0 0 -20 20 0 0 20 10 10 rect 1 1 1 fillrgb "
+    );
+    let wedge = Wedge::new(0.0, 0.0, 10.0, 0.0, 90.0)
+        .fill_rgb(1.0, 0.0, 0.0)
+        .shadow_cmyk(1.0, -1.0, 0.0, 0.0, 0.0, 0.5);
+    assert_eq!(
+        wedge.to_postscript_string(),
+        "gsave 1 -1 translate 0 0 10 0 90 sector 0 0 0 0.5 fillcmyk grestore \
+         0 0 10 0 90 sector 1 0 0 fillrgb "
+    );
+}
+
+#[test]
+fn test_round_corners() {
+    let polygon = Polygon::new(vec![(0.0, 0.0), (10.0, 20.0), (20.0, 0.0)])
+        .round_corners(2.0)
+        .fill_rgb(0.0, 0.0, 0.0);
+    assert_eq!(
+        polygon.to_postscript_string(),
+        "newpath 5 10 moveto 10 20 20 0 2 arcto 4 { pop } repeat \
+         20 0 0 0 2 arcto 4 { pop } repeat 0 0 10 20 2 arcto 4 { pop } repeat closepath \
+         0 0 0 fillrgb "
+    );
+
+    let path = pslib::Path::new()
+        .move_to(0.0, 0.0)
+        .line_to(10.0, 0.0)
+        .line_to(10.0, 10.0)
+        .close()
+        .move_to(20.0, 0.0)
+        .line_to(30.0, 0.0)
+        .line_to(30.0, 10.0)
+        .round_corners(1.0)
+        .stroke_rgb(1.0, 0.0, 0.0, 0.0);
+    assert_eq!(
+        path.to_postscript_string(),
+        "newpath 5 0 moveto 10 0 10 10 1 arcto 4 { pop } repeat \
+         10 10 0 0 1 arcto 4 { pop } repeat 0 0 10 0 1 arcto 4 { pop } repeat closepath \
+         20 0 moveto 30 0 30 10 1 arcto 4 { pop } repeat 30 10 lineto 0 0 0 1 strokergb "
+    );
+
+    // The radius is clamped to fit each corner's edges, repeated vertices are skipped and
+    // collinear vertices aren't rounded.
+    let polygon = Polygon::new(vec![
+        (0.0, 0.0),
+        (5.0, 0.0),
+        (10.0, 0.0),
+        (10.0, 0.0),
+        (10.0, 10.0),
+        (0.0, 10.0),
+    ])
+    .round_corners(20.0)
+    .fill_rgb(0.0, 0.0, 0.0);
+    assert_eq!(
+        polygon.to_postscript_string(),
+        "newpath 2.5 0 moveto 5 0 lineto 10 0 10 10 2.5 arcto 4 { pop } repeat \
+         10 10 0 10 5 arcto 4 { pop } repeat 0 10 0 0 5 arcto 4 { pop } repeat \
+         0 0 5 0 2.5 arcto 4 { pop } repeat closepath 0 0 0 fillrgb "
+    );
+
+    let path = pslib::Path::new()
+        .move_to(0.0, 0.0)
+        .line_to(4.0, 0.0)
+        .line_to(4.0, 4.0)
+        .line_to(0.0, 4.0)
+        .close()
+        .round_corners(10.0)
+        .stroke_rgb(1.0, 0.0, 0.0, 0.0);
+    assert_eq!(
+        path.to_postscript_string(),
+        "newpath 2 0 moveto 4 0 4 4 2 arcto 4 { pop } repeat 4 4 0 4 2 arcto 4 { pop } repeat \
+         0 4 0 0 2 arcto 4 { pop } repeat 0 0 4 0 2 arcto 4 { pop } repeat closepath \
+         0 0 0 1 strokergb "
+    );
+}
+
+#[test]
+fn test_flip() {
+    let rect = Rect::new(10.0, 10.0, 20.0, 40.0)
+        .flip_horizontal()
+        .fill_rgb(0.0, 0.0, 0.0);
+    assert_eq!(
+        rect.to_postscript_string(),
+        "gsave 20 30 translate -1 1 scale -20 -30 translate \
+         -20 0 0 -40 20 0 0 40 10 10 rect 0 0 0 fillrgb grestore "
+    );
+    let rect = Rect::new(10.0, 10.0, 20.0, 40.0)
+        .scale(2.0, 2.0)
+        .set_orign(TransformOrigin::BottomLeft)
+        .flip_vertical()
+        .fill_rgb(0.0, 0.0, 0.0);
+    assert!(rect
+        .to_postscript_string()
+        .starts_with("gsave 10 10 translate 2 -2 scale -10 -10 translate "));
+    let line = Line::new(10.0, 10.0, 100.0).flip_horizontal();
+    assert!(line
+        .to_postscript_string()
+        .starts_with("gsave 60 10.5 translate -1 1 scale "));
+}
+
+#[test]
+fn test_skew() {
+    let rect = Rect::new(0.0, 0.0, 20.0, 20.0)
+        .skew(45.0, 0.0)
+        .fill_rgb(0.0, 0.0, 0.0);
+    assert_eq!(
+        rect.to_postscript_string(),
+        "gsave 10 10 translate [1 0 1 1 0 0] concat -10 -10 translate \
+         -20 0 0 -20 20 0 0 20 0 0 rect 0 0 0 fillrgb grestore "
+    );
+    let line = Line::new(0.0, 0.0, 10.0).skew(0.0, 30.0);
+    assert!(line
+        .to_postscript_string()
+        .contains("[1 0.5774 0 1 0 0] concat"));
+}
+
+#[test]
+fn test_transform_matrix() {
+    let matrix = Transform::scale(2.0, 2.0).then(&Transform::translate(10.0, 5.0));
+    assert_eq!(matrix.apply(1.0, 1.0), (12.0, 7.0));
+    assert_eq!(matrix.to_string(), "[2 0 0 2 10 5]");
+    assert_eq!(Transform::rotate(90.0).to_string(), "[0 1 -1 0 0 0]");
+
+    let rect = Rect::new(0.0, 0.0, 20.0, 20.0)
+        .transform(matrix)
+        .fill_rgb(0.0, 0.0, 0.0);
+    assert_eq!(
+        rect.to_postscript_string(),
+        "gsave [2 0 0 2 10 5] concat 10 10 translate -10 -10 translate \
+         -20 0 0 -20 20 0 0 20 0 0 rect 0 0 0 fillrgb grestore "
+    );
+    let ring = Ring::new(0.0, 0.0, 10.0, 5.0)
+        .transform(Transform::translate(5.0, 0.0))
+        .fill_rgb(0.0, 0.0, 0.0);
+    assert!(ring
+        .to_postscript_string()
+        .starts_with("gsave [1 0 0 1 5 0] concat "));
+    let line = Line::new(0.0, 0.0, 10.0).transform(Transform::identity());
+    assert!(line
+        .to_postscript_string()
+        .starts_with("gsave [1 0 0 1 0 0] concat "));
+}
+
+#[test]
+fn test_fill_rule() -> Result<(), Error> {
+    let path = pslib::Path::from_svg_data("M0 0 H10 V10 H0 Z M2 2 H8 V8 H2 Z")?
+        .fill_cmyk(0.0, 0.0, 0.0, 1.0)
+        .fill_rule(FillRule::EvenOdd)
+        .shadow_rgb(1.0, -1.0, 0.5, 0.5, 0.5)
+        .hatch(HatchPattern::Horizontal, 2.0, 0.5);
+    let output = path.to_postscript_string();
+    assert!(output.contains("0.5 0.5 0.5 eofillrgb grestore "));
+    assert!(output.contains("0 0 0 1 eofillcmyk gsave eoclip 0 0 0 setrgbcolor "));
+    assert!(ProcedureRegistry::with_builtins()
+        .get_procedure("eofill_rgb")
+        .is_some());
+
+    let rect = Rect::new(0.0, 0.0, 10.0, 10.0)
+        .fill_rule(FillRule::NonZero)
+        .fill_rgb(1.0, 0.0, 0.0);
+    assert!(rect.to_postscript_string().ends_with("1 0 0 fillrgb "));
+
+    Ok(())
+}
+
+#[test]
+fn test_inline_image() -> Result<(), Error> {
+    let mut greymap = b"P5\n# comment\n2 2\n255\n".to_vec();
+    greymap.extend_from_slice(&[0, 255, 128, 64]);
+    let image = InlineImage::from_bytes(&greymap, 10.0, 20.0, 100.0, 50.0)?;
+    assert_eq!(
+        image.to_postscript_string(),
+        "gsave 35 20 translate 50 50 scale /DeviceGray setcolorspace << /ImageType 1 /Width 2 /Height 2 \
+/BitsPerComponent 8 /Decode [0 1] /ImageMatrix [2 0 0 -2 0 2] /DataSource <~!<7R7~> >> image grestore "
+    );
+
+    // Samples are scaled to 8 bits and cropped images are clipped to the box.
+    let pixmap = InlineImage::from_bytes(b"P3 2 1 15 15 0 0 0 0 15", 0.0, 0.0, 10.0, 10.0)?
+        .fit(ImageFit::Crop)
+        .to_postscript_string();
+    assert!(pixmap.starts_with("gsave newpath 0 0 moveto 10 0 rlineto 0 10 rlineto -10 0 rlineto closepath clip newpath -5 0 translate 20 10 scale /DeviceRGB setcolorspace "));
+    assert!(pixmap.contains(
+        "/Decode [0 1 0 1 0 1] /ImageMatrix [2 0 0 -1 0 1] /DataSource <~rr<$!!<3~> >> image"
+    ));
+    let stretched = InlineImage::from_bytes(b"P3 2 1 15 15 0 0 0 0 15", 0.0, 0.0, 10.0, 10.0)?
+        .fit(ImageFit::StretchVertical)
+        .to_postscript_string();
+    assert!(stretched.starts_with("gsave 0 0 translate 10 10 scale "));
+
+    // Long data is split across strings.
+    let mut large = b"P5 300 200 255\n".to_vec();
+    large.resize(large.len() + 60000, 0);
+    let large = InlineImage::from_bytes(&large, 0.0, 0.0, 300.0, 200.0)?.to_postscript_string();
+    assert!(large.contains("2 dict begin /pslib_data [ <~zzz"));
+    assert!(large.contains("/DataSource { /pslib_index pslib_index 1 add def pslib_index pslib_data length lt { pslib_data pslib_index get } { () } ifelse } >> image end grestore "));
+
+    let error = InlineImage::from_bytes(b"GIF89a", 0.0, 0.0, 10.0, 10.0)
+        .err()
+        .unwrap();
+    assert_eq!(error.kind(), ErrorKind::Unsupported);
+    let error = InlineImage::from_bytes(b"P5 2 2 255\n\0", 0.0, 0.0, 10.0, 10.0)
+        .err()
+        .unwrap();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+    // Sizes larger than the data are rejected before allocating.
+    for header in [
+        &b"P2 4000000000 4000000000 255 1"[..],
+        b"P5 65536 65536 65535\n\0\0",
+        b"P6 3074457345618258602 2 255\n\0",
+    ] {
+        let error = InlineImage::from_bytes(header, 0.0, 0.0, 10.0, 10.0)
+            .err()
+            .unwrap();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_image_registry() -> Result<(), Error> {
+    let path = std::env::temp_dir().join("pslib_registry_logo.pgm");
+    fs::write(&path, b"P2 2 1 255 0 255")?;
+    let mut images = ImageRegistry::new();
+    images.add_image(&path)?;
+    assert_eq!(
+        images.get_procedure_id("pslib_registry_logo.pgm"),
+        Some("imager1".to_string())
+    );
+    assert_eq!(images.list_images()[0].procedure_name(), "imager1");
+    assert_eq!(
+        images.list_images()[0].definition(),
+        "/imager1 { gsave 0 0 translate 1 1 scale /DeviceGray setcolorspace << /ImageType 1 /Width 2 /Height 1 \
+/BitsPerComponent 8 /Decode [0 1] /ImageMatrix [2 0 0 -1 0 1] /DataSource <~!<3~> >> image grestore } def"
+    );
+
+    let mut output = Vec::new();
+    let mut doc = DocumentBuilder::builder()
+        .writer(BufWriter::new(&mut output))
+        .load_images(images)
+        .build();
+    doc.add(&Page::new(612, 792))?;
+    let report = doc.close()?;
+    assert!(report.resources().contains(&"imager1".to_string()));
+    assert!(String::from_utf8(output)
+        .unwrap()
+        .contains("/imager1 { gsave 0 0 translate"));
+
+    fs::write(&path, b"GIF89a")?;
+    let error = ImageRegistry::new().add_image(&path).err().unwrap();
+    assert_eq!(error.kind(), ErrorKind::Unsupported);
+    fs::remove_file(&path)?;
+    Ok(())
+}
+
+// The markers of a JPEG up to the start of scan; the compressed data is never decoded.
+fn jpeg_file(precision: u8, components: u8, adobe: bool) -> Vec<u8> {
+    let mut file = vec![0xFF, 0xD8, 0xFF, 0xE0, 0, 16];
+    file.extend_from_slice(b"JFIF\0\x01\x02\0\0\x01\0\x01\0\0");
+    if adobe {
+        file.extend_from_slice(&[0xFF, 0xEE, 0, 14]);
+        file.extend_from_slice(b"Adobe\0\x64\0\0\0\0\0");
+    }
+    file.extend_from_slice(&[
+        0xFF,
+        0xFF,
+        0xFF,
+        0xC0,
+        0,
+        8 + 3 * components,
+        precision,
+        0,
+        2,
+        0,
+        3,
+    ]);
+    file.push(components);
+    for id in 1..=components {
+        file.extend_from_slice(&[id, 0x11, 0]);
+    }
+    file.extend_from_slice(&[0xFF, 0xDA, 0, 2, 0xFF, 0xD9]);
+    file
+}
+
+#[test]
+fn test_jpeg_image() -> Result<(), Error> {
+    let rgb = jpeg_file(8, 3, false);
+    let image = InlineImage::from_bytes(&rgb, 0.0, 0.0, 30.0, 20.0)?.to_postscript_string();
+    assert!(image.contains("/DeviceRGB setcolorspace << /ImageType 1 /Width 3 /Height 2 "));
+    assert!(image.contains("/DataSource <~s4IA0"));
+    assert!(image.ends_with("~> /DCTDecode filter >> image grestore "));
+
+    let cmyk = jpeg_file(8, 4, true);
+    let image = InlineImage::from_bytes(&cmyk, 0.0, 0.0, 30.0, 20.0)?.to_postscript_string();
+    assert!(image.contains("/DeviceCMYK setcolorspace "));
+    assert!(image.contains("/Decode [1 0 1 0 1 0 1 0] "));
+
+    let error = InlineImage::from_bytes(&jpeg_file(12, 3, false), 0.0, 0.0, 10.0, 10.0)
+        .err()
+        .unwrap();
+    assert_eq!(error.kind(), ErrorKind::Unsupported);
+    let error = InlineImage::from_bytes(&rgb[..24], 0.0, 0.0, 10.0, 10.0)
+        .err()
+        .unwrap();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+    // Zero dimensions would make a singular image matrix
+    let mut no_height = rgb.clone();
+    no_height[28] = 0;
+    let error = InlineImage::from_bytes(&no_height, 0.0, 0.0, 10.0, 10.0)
+        .err()
+        .unwrap();
+    assert_eq!(error.kind(), ErrorKind::Unsupported);
+    let mut no_width = rgb.clone();
+    no_width[30] = 0;
+    let error = InlineImage::from_bytes(&no_width, 0.0, 0.0, 10.0, 10.0)
+        .err()
+        .unwrap();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+    Ok(())
+}
+
+// A PNG with the scanlines stored in an uncompressed deflate block.
+#[cfg(feature = "png")]
+fn png_file(
+    width: u32,
+    height: u32,
+    bit_depth: u8,
+    color_type: u8,
+    interlace: u8,
+    raw: &[u8],
+) -> Vec<u8> {
+    let crc = |data: &[u8]| {
+        let mut crc = 0xFFFFFFFFu32;
+        for byte in data {
+            crc ^= *byte as u32;
+            for _ in 0..8 {
+                crc = if crc & 1 != 0 {
+                    crc >> 1 ^ 0xEDB88320
+                } else {
+                    crc >> 1
+                };
+            }
+        }
+        !crc
+    };
+    let chunk = |file: &mut Vec<u8>, tag: &[u8; 4], data: &[u8]| {
+        file.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        let start = file.len();
+        file.extend_from_slice(tag);
+        file.extend_from_slice(data);
+        let crc = crc(&file[start..]);
+        file.extend_from_slice(&crc.to_be_bytes());
+    };
+    let mut header = Vec::new();
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    header.extend_from_slice(&[bit_depth, color_type, 0, 0, interlace]);
+    let mut zlib = vec![0x78, 0x01, 0x01];
+    zlib.extend_from_slice(&(raw.len() as u16).to_le_bytes());
+    zlib.extend_from_slice(&(!(raw.len() as u16)).to_le_bytes());
+    zlib.extend_from_slice(raw);
+    let (a, b) = raw.iter().fold((1u32, 0u32), |(a, b), byte| {
+        let a = (a + *byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    zlib.extend_from_slice(&(b << 16 | a).to_be_bytes());
+
+    let mut file = b"\x89PNG\r\n\x1a\n".to_vec();
+    chunk(&mut file, b"IHDR", &header);
+    chunk(&mut file, b"IDAT", &zlib);
+    chunk(&mut file, b"IEND", &[]);
+    file
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn test_png_image() -> Result<(), Error> {
+    // Transparent pixels are composited over white.
+    let rgba = png_file(2, 1, 8, 6, 0, &[0, 255, 0, 0, 255, 0, 0, 0, 0]);
+    let image = InlineImage::from_bytes(&rgba, 0.0, 0.0, 20.0, 10.0)?.to_postscript_string();
+    assert!(image.contains("/DeviceRGB setcolorspace << /ImageType 1 /Width 2 /Height 1 "));
+    assert!(image.contains("/DataSource <~rr<'!s8N~> >> image"));
+
+    // An interlaced 1 bit greymap, its Adam7 passes holding pixels (0, 0), (1, 0) and row 1.
+    let grey = png_file(2, 2, 1, 0, 1, &[0, 0x80, 0, 0x00, 0, 0x40]);
+    let image = InlineImage::from_bytes(&grey, 0.0, 0.0, 10.0, 10.0)?.to_postscript_string();
+    assert!(image.contains("/DeviceGray setcolorspace << /ImageType 1 /Width 2 /Height 2 "));
+    assert!(image.contains("/DataSource <~rr<'!~> >> image"));
+
+    let mut corrupt = rgba.clone();
+    corrupt[20] ^= 1;
+    let error = InlineImage::from_bytes(&corrupt, 0.0, 0.0, 10.0, 10.0)
+        .err()
+        .unwrap();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+    Ok(())
+}
+
+#[test]
+fn test_clip() -> Result<(), Error> {
+    let rect = Rect::new(0.0, 0.0, 20.0, 20.0).fill_rgb(1.0, 0.0, 0.0);
+
+    let mut clip = Clip::rect(5.0, 5.0, 10.0, 10.0);
+    assert_eq!(clip.to_postscript_string(), "");
+    clip.add(&rect);
+    assert_eq!(
+        clip.to_postscript_string(),
+        format!(
+            "gsave -10 0 0 -10 10 0 0 10 5 5 rect clip newpath {}grestore ",
+            rect.to_postscript_string()
+        )
+    );
+
+    let path = pslib::Path::from_svg_data("M0 0 H10 V10 Z")?;
+    let mut clip = Clip::path(&path).fill_rule(FillRule::EvenOdd);
+    clip.add(&rect);
+    assert!(clip.to_postscript_string().starts_with(
+        "gsave newpath 0 0 moveto 10 0 lineto 10 10 lineto closepath eoclip newpath "
+    ));
+
+    let mut clip = Clip::polygon(vec![(0.0, 0.0)]);
+    clip.add(&rect);
+    assert_eq!(clip.to_postscript_string(), "");
+
+    // Warnings of the clipped content are reported
+    let mut clip = Clip::rect(0.0, 0.0, 10.0, 10.0);
+    clip.add(&Text::new("\u{3A9}", 0.0, 0.0));
+    assert_eq!(
+        clip.warnings(),
+        ["Helvetica has no glyphs for \"\u{3A9}\"."]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_group() {
+    let mut group = Group::new()
+        .translate(100.0, 50.0)
+        .rotate(90.0)
+        .fill_rgb(1.0, 0.0, 0.0)
+        .stroke_rgb(2.0, 0.0, 0.0, 1.0);
+    assert_eq!(group.to_postscript_string(), "");
+    group.add(&Rect::new(0.0, 0.0, 10.0, 10.0));
+    group.add(&Rect::new(20.0, 0.0, 10.0, 10.0).fill_cmyk(0.0, 0.0, 0.0, 1.0));
+    group.add(&Polyline::new(vec![(0.0, 0.0), (10.0, 0.0)]));
+    group.add(&Line::new(0.0, 0.0, 10.0));
+    assert_eq!(
+        group.to_postscript_string(),
+        "gsave 100 50 translate 90 rotate \
+         -10 0 0 -10 10 0 0 10 0 0 rect 1 0 0 fillrgb 0 0 1 2 strokergb \
+         -10 0 0 -10 10 0 0 10 20 0 rect 0 0 0 1 fillcmyk 0 0 1 2 strokergb \
+         10 0 1 0 0 pline 0 0 1 2 strokergb \
+         10 0 0 0 line 0 0 0 1 strokergb grestore "
+    );
+
+    let mut outer = Group::new();
+    outer.add(&group);
+    assert_eq!(outer.to_postscript_string(), group.to_postscript_string());
+}
+
+#[test]
+fn test_opacity() {
+    let rect = Rect::new(0.0, 0.0, 10.0, 10.0)
+        .fill_rgb(1.0, 0.0, 0.0)
+        .opacity(0.25);
+    assert!(rect.to_postscript_string().ends_with(
+        "rect /pslib_transparency where { pop [ /ca 0.25 /SetTransparency pdfmark 1 0 0 fillrgb \
+         [ /ca 1 /SetTransparency pdfmark } { 1 0.75 0.75 fillrgb } ifelse "
+    ));
+
+    let ring = Ring::new(0.0, 0.0, 10.0, 5.0)
+        .fill_cmyk(1.0, 0.0, 0.0, 0.0)
+        .opacity(0.5)
+        .backdrop_rgb(0.0, 0.0, 0.0);
+    assert!(ring
+        .to_postscript_string()
+        .contains("{ 0.5 0 0 0.5 fillcmyk } ifelse "));
+
+    let opaque = Rect::new(0.0, 0.0, 10.0, 10.0)
+        .fill_rgb(1.0, 0.0, 0.0)
+        .opacity(1.0);
+    assert!(opaque
+        .to_postscript_string()
+        .ends_with("rect 1 0 0 fillrgb "));
+}
+
+#[test]
+fn test_opacity_with_bookmarks() -> Result<(), Error> {
+    let document = |transparency: bool| -> Result<String, Error> {
+        let mut page = Page::new(612, 792);
+        page.add(
+            &Rect::new(0.0, 0.0, 10.0, 10.0)
+                .fill_rgb(1.0, 0.0, 0.0)
+                .opacity(0.5),
+        )?;
+        let mut output = Vec::new();
+        let mut doc = DocumentBuilder::builder()
+            .writer(BufWriter::new(&mut output))
+            .transparency(transparency)
+            .build();
+        doc.bookmark("Statement");
+        doc.add(&page)?;
+        doc.close()?;
+        Ok(String::from_utf8(output).unwrap())
+    };
+
+    // The pdfmark defined for the bookmarks doesn't enable the pdfmark transparency.
+    let output = document(false)?;
+    assert!(output.contains("/pdfmark where { pop } { userdict /pdfmark /cleartomark load put } ifelse\n[ /Title (Statement) /OUT pdfmark\n"));
+    assert!(output.contains("/pslib_transparency where { pop [ /ca 0.5 /SetTransparency pdfmark"));
+    assert!(!output.contains("/pslib_transparency true def"));
+
+    let output = document(true)?;
+    assert!(output.contains(
+        "%%BeginSetup\n/pdfmark where { pop } { userdict /pdfmark /cleartomark load put } ifelse\n/pslib_transparency true def\n%%EndSetup\n"
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_border() {
+    let border = Border::new(
+        0.0,
+        0.0,
+        20.0,
+        10.0,
+        BorderMotif::Marker(MarkerSymbol::Circle),
+        2.0,
+    )
+    .spacing(5.0)
+    .corner(BorderMotif::Procedure("flourish".to_string()), 4.0)
+    .fill_rgb(0.0, 0.0, 0.0);
+    let output = border.to_postscript_string();
+    assert_eq!(output.matches("flourish 0 0 0 fillrgb").count(), 4);
+    assert_eq!(output.matches("mcircle 0 0 0 fillrgb").count(), 8);
+    assert!(output.starts_with("0 0 4 flourish 0 0 0 fillrgb 5 0 2 mcircle 0 0 0 fillrgb "));
+
+    let unstyled = Border::new(
+        0.0,
+        0.0,
+        20.0,
+        10.0,
+        BorderMotif::Marker(MarkerSymbol::Circle),
+        2.0,
+    );
+    assert_eq!(unstyled.to_postscript_string(), "");
+}
+
+#[test]
+fn test_paint_order() {
+    let rect = Rect::new(0.0, 0.0, 10.0, 10.0)
+        .fill_rgb(1.0, 1.0, 1.0)
+        .stroke_rgb(2.0, 0.0, 0.0, 0.0)
+        .paint_order(PaintOrder::StrokeFirst);
+    assert!(rect
+        .to_postscript_string()
+        .ends_with("rect 0 0 0 2 strokergb 1 1 1 fillrgb "));
+
+    let star = Star::new(0.0, 0.0, 10.0, 5.0, 5)
+        .fill_rgb(1.0, 1.0, 1.0)
+        .stroke_rgb(2.0, 0.0, 0.0, 0.0)
+        .paint_order(PaintOrder::FillFirst);
+    assert!(star
+        .to_postscript_string()
+        .ends_with("1 1 1 fillrgb 0 0 0 2 strokergb "));
+}
+
+#[test]
+fn test_text() {
+    let text = Text::new("Total (USD) \\ 100%", 10.0, 20.0);
+    assert_eq!(
+        text.to_postscript_string(),
+        "gsave 0 setgray /Helvetica findfont 12 scalefont setfont 10 20 moveto \
+         (Total \\(USD\\) \\\\ 100%) show grestore "
+    );
+
+    let text = Text::new("Hi", 10.0, 20.0)
+        .font("Times Roman/", 9.5)
+        .fill_cmyk(0.0, 1.0, 0.0, 0.0)
+        .rotate(90.0);
+    assert_eq!(
+        text.to_postscript_string(),
+        "gsave 0 1 0 0 setcmykcolor /TimesRoman findfont 9.5 scalefont setfont \
+         10 20 translate 90 rotate 0 0 moveto (Hi) show grestore "
+    );
+
+    let text = Text::new("Hi", 0.0, 0.0).font(Font::NewCenturySchoolbookBoldItalic, 10.0);
+    assert!(text
+        .to_postscript_string()
+        .contains("/NewCenturySchlbk-BoldItalic findfont 10 scalefont setfont "));
+    assert_eq!(Font::Custom("Optima".to_string()).name(), "Optima");
+
+    assert_eq!(Text::new("", 0.0, 0.0).to_postscript_string(), "");
+}
+
+#[test]
+fn test_line_cap_and_join() {
+    let polyline = Polyline::new(vec![(0.0, 0.0), (10.0, 10.0), (20.0, 0.0)])
+        .line_cap(LineCap::Round)
+        .line_join(LineJoin::Bevel)
+        .segment_rgb(1, 2.0, 1.0, 0.0, 0.0);
+    assert_eq!(
+        polyline.to_postscript_string(),
+        "10 10 1 0 0 pline gsave 1 setlinecap 2 setlinejoin 0 0 0 1 strokergb grestore \
+         20 0 1 10 10 pline gsave 1 setlinecap 2 setlinejoin 1 0 0 2 strokergb grestore "
+    );
+    let polygon = Polygon::new(vec![(0.0, 0.0), (10.0, 1.0), (0.0, 2.0)])
+        .stroke_rgb(1.0, 0.0, 0.0, 0.0)
+        .line_join(LineJoin::Miter)
+        .miter_limit(0.5);
+    assert!(polygon
+        .to_postscript_string()
+        .ends_with("gsave 0 setlinejoin 1 setmiterlimit 0 0 0 1 strokergb grestore "));
+    let rect = Rect::new(0.0, 0.0, 10.0, 10.0).line_join(LineJoin::Round);
+    assert!(!rect.to_postscript_string().contains("setlinejoin"));
+    let line = Line::new(0.0, 0.0, 10.0).line_cap(LineCap::Square);
+    assert!(line
+        .to_postscript_string()
+        .ends_with("gsave 2 setlinecap 0 0 0 1 strokergb grestore "));
+}
+
+#[test]
+fn test_operand_stack_chunking() {
+    let points: Vec<(f32, f32)> = (0..1000).map(|i| (i as f32, (i % 2) as f32)).collect();
+
+    let chunked = Polygon::new(points.clone()).fill_rgb(0.0, 0.0, 0.0);
+    let output = chunked.to_postscript_string();
+    assert!(output.starts_with("newpath 0 0 moveto "));
+    assert_eq!(output.matches("242 { lineto } repeat").count(), 4);
+    assert!(output.contains("31 { lineto } repeat closepath 0 0 0 fillrgb "));
+
+    let single = Polygon::new(points)
+        .language_level(LanguageLevel::Level3)
+        .fill_rgb(0.0, 0.0, 0.0);
+    assert!(single
+        .to_postscript_string()
+        .ends_with("999 0 0 poly 0 0 0 fillrgb "));
+}
+
+#[test]
+fn test_vm_usage() -> Result<(), Error> {
+    let mut output = Vec::new();
+    {
+        let mut doc = DocumentBuilder::builder()
+            .writer(BufWriter::new(&mut output))
+            .load_procedures(ProcedureRegistry::with_builtins())
+            .language_level(LanguageLevel::Level1)
+            .build();
+        assert!(doc.vm_usage() > 0);
+
+        let mut small = Page::new(400, 400);
+        small.add(&Rect::new(10.0, 10.0, 20.0, 20.0).fill_rgb(0.0, 0.0, 0.0))?;
+        doc.add(&small)?;
+        assert!(doc.warnings().is_empty());
+
+        let mut large = Page::new(400, 400);
+        for i in 0..4000 {
+            let (x, y) = ((i % 80) as f32 * 5.0, (i / 80) as f32 * 5.0);
+            large.add(&Rect::new(x, y, 5.0, 5.0).fill_rgb(0.0, 0.0, 0.0))?;
+        }
+        doc.add(&large)?;
+        assert_eq!(doc.warnings().len(), 1);
+        doc.close()?;
+    }
+
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("%%LanguageLevel: 1\n"));
+    assert_eq!(output.matches("/pslib_pagesave save def").count(), 1);
+    assert!(output.contains("pslib_pagesave restore\nshowpage"));
+
+    Ok(())
+}
+
+#[test]
+fn test_per_page_resources() -> Result<(), Error> {
+    let mut letterhead = Form::new("letterhead", 612, 792);
+    letterhead.add(&Rect::new(0.0, 742.0, 612.0, 50.0).fill_rgb(0.0, 0.0, 0.5));
+    let mut forms = FormRegistry::new();
+    forms.add_form(letterhead);
+
+    let mut output = Vec::new();
+    {
+        let mut doc = DocumentBuilder::builder()
+            .writer(BufWriter::new(&mut output))
+            .load_procedures(ProcedureRegistry::with_builtins())
+            .resource_policy(ResourcePolicy::PerPage)
+            .load_forms(forms)
+            .build();
+        let mut page = Page::new(612, 792);
+        page.set_background("letterhead")?;
+        doc.add(&page)?;
+        doc.add(&page)?;
+        doc.add(&Page::new(612, 792))?;
+        doc.close()?;
+    }
+
+    let output = String::from_utf8(output).unwrap();
+    let (prolog, _) = output.split_once("%%Page: 1 1").unwrap();
+    assert!(!prolog.contains("/letterhead"));
+    assert_eq!(
+        output
+            .matches("%%BeginResource: form letterhead\n/letterhead << /FormType 1")
+            .count(),
+        2
+    );
+    assert!(output.contains(
+        "%%PageBoundingBox: 0 0 612 792\n%%BeginPageSetup\n/pslib_resourcesave save def\n%%BeginResource: form letterhead\n"
+    ));
+    assert!(output.contains("showpage\npslib_resourcesave restore\n%%Page: 2 2"));
+    let (_, last) = output.split_once("%%Page: 3 3").unwrap();
+    assert!(!last.contains("pslib_resourcesave"));
+
+    Ok(())
+}
+
+#[test]
+fn test_per_page_fonts() -> Result<(), Error> {
+    let mut fonts = FontRegistry::new();
+    fonts.add_font(TrueTypeFont::from_bytes("BrandSans", &minimal_ttf())?);
+    let mut label = Form::new("label", 200, 100);
+    label.add(&Text::new("AA", 10.0, 10.0).font("BrandSans", 12.0));
+    let mut forms = FormRegistry::new();
+    forms.add_form(label);
+
+    let mut output = Vec::new();
+    {
+        let mut doc = DocumentBuilder::builder()
+            .writer(BufWriter::new(&mut output))
+            .resource_policy(ResourcePolicy::PerPage)
+            .load_fonts(fonts)
+            .load_forms(forms)
+            .build();
+        let mut page = Page::new(612, 792);
+        page.add(&Text::new("AB", 72.0, 720.0).font("BrandSans", 12.0))?;
+        doc.add(&page)?;
+        doc.add(&Page::new(612, 792))?;
+        let mut page = Page::new(612, 792);
+        page.set_background("label")?;
+        doc.add(&page)?;
+        doc.close()?;
+    }
+
+    let output = String::from_utf8(output).unwrap();
+    let (prolog, pages) = output.split_once("%%Page: 1 1").unwrap();
+    assert!(!prolog.contains("%%BeginResource: font BrandSans"));
+    let (first, last) = pages.split_once("%%Page: 2 2").unwrap();
+    assert_eq!(
+        first.matches("%%BeginResource: font BrandSans\n").count(),
+        1
+    );
+    assert!(first.contains("%%BeginPageSetup\n/pslib_resourcesave save def\n%%BeginResource: font"));
+    assert!(first.contains("%%EndResource\n%%EndPageSetup\n"));
+    assert!(!first.contains("%%BeginResource: form"));
+    let (second, third) = last.split_once("%%Page: 3 3").unwrap();
+    assert!(!second.contains("BrandSans"));
+
+    // The font is defined on the page along with the form that uses it.
+    assert!(third.contains("%%BeginResource: font BrandSans\n"));
+    assert!(third.contains("%%BeginResource: form label\n"));
+
+    Ok(())
+}
+
+#[test]
+fn test_error_handler() -> Result<(), Error> {
+    let mut output = Vec::new();
+    {
+        let mut doc = DocumentBuilder::builder()
+            .writer(BufWriter::new(&mut output))
+            .load_procedures(ProcedureRegistry::with_builtins())
+            .error_handler(true)
+            .build();
+        doc.add(&Page::new(612, 792))?;
+        doc.add(&Page::new(612, 792))?;
+        doc.close()?;
+    }
+
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("%%BeginProcSet: pslib_errorhandler\n"));
+    assert!(output.contains("errordict /handleerror {"));
+    assert!(output.contains("%%Page: 2 2\n%%PageBoundingBox: 0 0 612 792\n%%BeginPageSetup\n/pslib_page 2 def\n%%EndPageSetup\n"));
+
+    Ok(())
+}
+
+#[test]
+fn test_job_parameters() -> Result<(), Error> {
+    let mut output = Vec::new();
+    {
+        let doc = DocumentBuilder::builder()
+            .writer(BufWriter::new(&mut output))
+            .job_name("Invoices (March)")
+            .job_timeout(600)
+            .wait_timeout(300)
+            .build();
+        doc.close()?;
+    }
+
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("%%Title: Invoices (March)\n"));
+    assert!(output.contains(
+        "<< /JobName (Invoices \\(March\\)) /JobTimeout 600 /WaitTimeout 300 >> setuserparams"
+    ));
+    assert!(output.contains("statusdict begin 600 setjobtimeout end"));
+
+    Ok(())
+}
+
+#[test]
+fn test_dpi() -> Result<(), Error> {
+    let mut output = Vec::new();
+    {
+        let doc = DocumentBuilder::builder()
+            .writer(BufWriter::new(&mut output))
+            .dpi(144.0)
+            .build();
+        doc.close()?;
+    }
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains(
+        "%%BeginSetup\n<< /BeginPage { pop 0.5 0.5 scale } >> setpagedevice\n%%EndSetup\n"
+    ));
+
+    let mut output = Vec::new();
+    {
+        let doc = DocumentBuilder::builder()
+            .writer(BufWriter::new(&mut output))
+            .document_type(DocumentType::EPS)
+            .bounding_box(100, 100)
+            .dpi(144.0)
+            .build();
+        doc.close()?;
+    }
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("%%BeginSetup\n0.5 0.5 scale\n%%EndSetup\n"));
+
+    Ok(())
+}
+
+#[test]
+fn test_digest() -> Result<(), Error> {
+    let mut output = Vec::new();
+    let report = {
+        let mut doc = DocumentBuilder::builder()
+            .writer(BufWriter::new(&mut output))
+            .digest(true)
+            .build();
+        doc.add(&Page::new(612, 792))?;
+        doc.close()?
+    };
+    let digest = report.digest().unwrap();
+    assert_eq!(digest.len(), 64);
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.ends_with(&format!(
+        "%%Trailer\n%%Pages: 1\n%%PslibDigest: SHA-256 {}\n%%EOF",
+        digest
+    )));
+
+    // The digest covers everything up to the digest comment, checked against the sidecar hash
+    let covered = &output[..output.find("%%PslibDigest").unwrap()];
+    let mut check = Vec::new();
+    {
+        let doc = DocumentBuilder::builder()
+            .writer(BufWriter::new(&mut check))
+            .sidecar("doc.ps", "application/postscript", covered.as_bytes())
+            .build();
+        doc.close()?;
+    }
+    assert!(String::from_utf8(check)
+        .unwrap()
+        .contains(&format!("SHA-256 {}\n", digest)));
+
+    // FIPS 180-4 known answers, including padding that spills into a second block and input
+    // spanning many blocks
+    let known_answers: [(Vec<u8>, &str); 4] = [
+        (
+            b"".to_vec(),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        ),
+        (
+            b"abc".to_vec(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+        ),
+        (
+            b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq".to_vec(),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+        ),
+        (
+            vec![b'a'; 1_000_000],
+            "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0",
+        ),
+    ];
+    for (data, expected) in known_answers {
+        let mut check = Vec::new();
+        {
+            let doc = DocumentBuilder::builder()
+                .writer(BufWriter::new(&mut check))
+                .sidecar("data", "text/plain", &data)
+                .build();
+            doc.close()?;
+        }
+        assert!(String::from_utf8(check).unwrap().contains(&format!(
+            "{} SHA-256 {}\n",
+            data.len(),
+            expected
+        )));
+    }
+
+    let mut output = Vec::new();
+    let doc = DocumentBuilder::builder()
+        .writer(BufWriter::new(&mut output))
+        .build();
+    assert_eq!(doc.close()?.digest(), None);
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.ends_with("%%Trailer\n%%Pages: 0\n%%EOF"));
+
+    // EPS files don't have pages
+    let mut output = Vec::new();
+    let doc = DocumentBuilder::builder()
+        .writer(BufWriter::new(&mut output))
+        .document_type(DocumentType::EPS)
+        .build();
+    doc.close()?;
+    assert!(String::from_utf8(output)
+        .unwrap()
+        .ends_with("\n%%Trailer\n%%EOF"));
+
+    Ok(())
+}
+
+#[test]
+fn test_report() -> Result<(), Error> {
+    let mut letterhead = Form::new("letterhead", 612, 792);
+    letterhead.add(&Rect::new(0.0, 742.0, 612.0, 50.0));
+    let mut forms = FormRegistry::new();
+    forms.add_form(letterhead);
+
+    let mut output = Vec::new();
+    let report = {
+        let mut doc = DocumentBuilder::builder()
+            .writer(BufWriter::new(&mut output))
+            .language_level(LanguageLevel::Level1)
+            .load_procedures(ProcedureRegistry::with_builtins())
+            .load_forms(forms)
+            .build();
+        let mut page = Page::new(612, 792);
+        page.add(&Text::new(&"x".repeat(300_000), 0.0, 0.0))?;
+        doc.add(&page)?;
+        doc.add_blank_page(612, 792)?;
+        doc.close()?
+    };
+
+    assert_eq!(report.page_count(), 2);
+    assert_eq!(report.bytes_written(), output.len());
+    assert_eq!(report.warnings().len(), 1);
+    assert!(report.resources().contains(&"rect".to_string()));
+    assert_eq!(report.resources().last().unwrap(), "letterhead");
+    assert_eq!(report.digest(), None);
+
+    Ok(())
+}
+
+#[test]
+fn test_vm_deferred_resources() -> Result<(), Error> {
+    let mut logo = Form::new("logo", 100, 100);
+    logo.add(&Rect::new(0.0, 0.0, 100.0, 100.0));
+    let mut catalog = Form::new("catalog", 612, 792);
+    catalog.add(&Text::new(&"x".repeat(300_000), 0.0, 0.0));
+    let mut logos = FormRegistry::new();
+    logos.add_form(logo);
+    let mut catalogs = FormRegistry::new();
+    catalogs.add_form(catalog);
+
+    let mut output = Vec::new();
+    let report = {
+        let mut doc = DocumentBuilder::builder()
+            .writer(BufWriter::new(&mut output))
+            .language_level(LanguageLevel::Level1)
+            .build();
+        doc.load_forms(logos)?;
+        doc.load_forms(catalogs)?;
+        let mut page = Page::new(612, 792);
+        page.set_background("catalog")?;
+        doc.add(&page)?;
+        doc.add(&Page::new(612, 792))?;
+        doc.close()?
+    };
+
+    assert_eq!(
+        report.warnings(),
+        [
+            "Resources from catalog on are defined on each page that uses them to stay within the typical LanguageLevel 1 VM of 240000 bytes.",
+            "Page 1 may exceed the typical LanguageLevel 1 VM of 240000 bytes.",
+        ]
+    );
+    let output = String::from_utf8(output).unwrap();
+    let (prolog, pages) = output.split_once("%%Page: 1 1").unwrap();
+    assert!(prolog.contains("/logo <<"));
+    assert!(!prolog.contains("/catalog <<"));
+    let (first, last) = pages.split_once("%%Page: 2 2").unwrap();
+    assert!(first.contains("%%BeginResource: form catalog\n/catalog <<"));
+    assert!(first.contains("showpage\npslib_resourcesave restore\n"));
+    assert!(!last.contains("catalog"));
+
+    Ok(())
+}
+
+#[test]
+fn test_report_element_warnings() -> Result<(), Error> {
+    let mut fonts = FontRegistry::new();
+    fonts.add_font(TrueTypeFont::from_bytes("BrandSans", &minimal_ttf())?);
+    let mut metrics = fonts.metrics();
+    metrics.set_fallbacks("BrandSans", vec![Font::Helvetica]);
+
+    let mut notice = Form::new("notice", 612, 100);
+    notice.add(&RichText::new(72.0, 0.0, 100.0, 20.0).span(Span::new(
+        "one two three four five six seven eight nine ten",
+    )));
+    let mut forms = FormRegistry::new();
+    forms.add_form(notice);
+
+    let mut output = Vec::new();
+    let report = {
+        let mut doc = DocumentBuilder::builder()
+            .writer(BufWriter::new(&mut output))
+            .load_forms(forms)
+            .build();
+        let mut page = Page::new(612, 792);
+        page.add(&Paragraph::new(
+            "The quick brown fox jumps over the lazy dog.",
+            72.0,
+            700.0,
+            60.0,
+            20.0,
+        ))?;
+        page.add(&Text::new("\u{3A9}hm", 72.0, 600.0))?;
+        page.add(
+            &Text::new("AB", 72.0, 500.0)
+                .font("BrandSans", 12.0)
+                .metrics(&metrics),
+        )?;
+        page.add(&Text::new("Fits", 72.0, 400.0))?;
+        doc.add(&page)?;
+        doc.close()?
+    };
+
+    assert_eq!(
+        report.warnings(),
+        &[
+            "Form notice: Rich text overflows its box; 2 lines are not shown.".to_string(),
+            "Page 1: Paragraph overflows its columns; 4 lines are not shown.".to_string(),
+            "Page 1: Helvetica has no glyphs for \"\u{3A9}\".".to_string(),
+            "Page 1: Helvetica is substituted for BrandSans to show \"B\".".to_string(),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_page_procedures() -> Result<(), Error> {
+    let mut output = Vec::new();
+    {
+        let mut doc = DocumentBuilder::builder()
+            .writer(BufWriter::new(&mut output))
+            .build();
+        let mut page = Page::new(612, 792);
+        page.add_procedure(Procedure {
+            name: "bar".to_string(),
+            body: "/bar { 0 exch rlineto } def".to_string(),
+        });
+        page.add_setup("0.5 setgray");
+        doc.add(&page)?;
+        doc.add(&Page::new(612, 792))?;
+        doc.close()?;
+    }
+
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains(
+        "%%BeginPageSetup\n<< /PageSize [612 792] >> setpagedevice\n/pslib_pagesetup save def\n\
+         /bar { 0 exch rlineto } def\n0.5 setgray\n%%EndPageSetup\n"
+    ));
+    assert!(output.contains("showpage\npslib_pagesetup restore\n%%Page: 2 2"));
+    assert_eq!(output.matches("/bar {").count(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_progress() -> Result<(), Error> {
+    let progress = Rc::new(RefCell::new(Vec::new()));
+    let mut output = Vec::new();
+    {
+        let log = Rc::clone(&progress);
+        let mut doc = DocumentBuilder::builder()
+            .writer(BufWriter::new(&mut output))
+            .on_progress(move |pages, bytes| log.borrow_mut().push((pages, bytes)))
+            .build();
+        doc.add(&Page::new(612, 792))?;
+        doc.flush()?;
+        doc.add(&Page::new(612, 792))?;
+        assert_eq!(progress.borrow()[1].1, doc.bytes_written());
+        doc.close()?;
+    }
+
+    let progress = progress.borrow();
+    assert_eq!(progress.len(), 2);
+    assert_eq!(progress[0].0, 1);
+    assert_eq!(progress[1].0, 2);
+    assert!(progress[0].1 < progress[1].1);
+    assert_eq!(
+        progress[1].1 + "%%Trailer\n%%Pages: 2\n%%EOF".len(),
+        output.len()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_cancellation() -> Result<(), Error> {
+    let cancel = std::sync::Arc::new(AtomicBool::new(false));
+    let mut output = Vec::new();
+    let mut doc = DocumentBuilder::builder()
+        .writer(BufWriter::new(&mut output))
+        .cancel_flag(std::sync::Arc::clone(&cancel))
+        .build();
+    doc.add(&Page::new(612, 792))?;
+    cancel.store(true, Ordering::Relaxed);
+    let error = doc.add(&Page::new(612, 792)).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Other);
+    assert!(error.get_ref().is_some_and(|inner| inner.is::<Cancelled>()));
+
+    Ok(())
+}
+
+// Sets the cancel flag once it has received the given number of bytes.
+struct CancellingSink {
+    received: usize,
+    limit: usize,
+    cancel: std::sync::Arc<AtomicBool>,
+}
+
+impl Write for CancellingSink {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        self.received += buf.len();
+        if self.received >= self.limit {
+            self.cancel.store(true, Ordering::Relaxed);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_cancellation_within_image() -> Result<(), Error> {
+    let cancel = std::sync::Arc::new(AtomicBool::new(false));
+    let mut greymap = b"P5 400 400 255\n".to_vec();
+    greymap.extend((0..160000).map(|i| (i % 251) as u8));
+    let mut page = Page::new(612, 792);
+    page.add(&InlineImage::from_bytes(&greymap, 0.0, 0.0, 400.0, 400.0)?)?;
+
+    let sink = CancellingSink {
+        received: 0,
+        limit: 65536,
+        cancel: std::sync::Arc::clone(&cancel),
+    };
+    let mut doc = DocumentBuilder::builder()
+        .writer(BufWriter::new(sink))
+        .cancel_flag(std::sync::Arc::clone(&cancel))
+        .build();
+    let error = doc.add(&page).unwrap_err();
+    assert!(error.get_ref().is_some_and(|inner| inner.is::<Cancelled>()));
+    assert!(doc.bytes_written() < 200000);
+
+    Ok(())
+}
+
+#[test]
+fn test_tee_writer() -> Result<(), Error> {
+    let mut archive = Vec::new();
+    let mut spool = Vec::new();
+    {
+        let tee = TeeWriter::new()
+            .with_sink(&mut archive)
+            .with_sink(&mut spool);
+        let mut doc = DocumentBuilder::builder()
+            .writer(BufWriter::new(tee))
+            .build();
+        doc.add(&Page::new(612, 792))?;
+        doc.close()?;
+    }
+
+    assert!(!archive.is_empty());
+    assert_eq!(archive, spool);
+
+    // After a sink fails the tee can't be retried, as the earlier sinks have the data already
+    let mut archive = Vec::new();
+    {
+        let mut tee = TeeWriter::new()
+            .with_sink(&mut archive)
+            .with_sink(SlowSink {
+                received: Vec::new(),
+                writes: Vec::new(),
+                full: true,
+            });
+        assert!(tee.write(b"%!PS").is_err());
+        let error = tee.write(b"%!PS").err().unwrap();
+        assert_eq!(error.kind(), ErrorKind::BrokenPipe);
+        assert!(tee.flush().is_err());
+    }
+    assert_eq!(archive, b"%!PS");
+
+    Ok(())
+}
+
+// A sink like a printer with a small buffer: it accepts a few bytes at a time and is full on
+// every other write.
+struct SlowSink {
+    received: Vec<u8>,
+    writes: Vec<usize>,
+    full: bool,
+}
+
+impl Write for SlowSink {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        self.full = !self.full;
+        if self.full {
+            return Err(Error::new(ErrorKind::WouldBlock, "Buffer full."));
+        }
+        let count = buf.len().min(3);
+        self.received.extend_from_slice(&buf[..count]);
+        self.writes.push(buf.len());
+        Ok(count)
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_paced_writer() -> Result<(), Error> {
+    let sink = SlowSink {
+        received: Vec::new(),
+        writes: Vec::new(),
+        full: false,
+    };
+    let started = Instant::now();
+    let mut writer = PacedWriter::new(sink)
+        .chunk_size(4)
+        .rate(100)
+        .retry(2, Duration::from_millis(1));
+    writer.write_all(b"0123456789")?;
+    let sink = writer.into_inner();
+    assert_eq!(sink.received, b"0123456789");
+    assert_eq!(sink.writes, [4, 4, 4, 1]);
+    assert!(started.elapsed() >= Duration::from_millis(90));
+
+    // Writes are abandoned once the sink stays full for every retry.
+    let mut writer = PacedWriter::new(SlowSink {
+        received: Vec::new(),
+        writes: Vec::new(),
+        full: false,
+    })
+    .retry(0, Duration::from_millis(1));
+    let error = writer.write_all(b"abcd").err().unwrap();
+    assert_eq!(error.kind(), ErrorKind::WouldBlock);
+    assert!(writer.into_inner().received.is_empty());
+
+    // Bytes the sink accepted are reported even when flushing them fails, and the flush error is
+    // returned by the next call so nothing is written twice.
+    let mut writer = PacedWriter::new(FailingFlush {
+        received: Vec::new(),
+        failed: false,
+    });
+    assert_eq!(writer.write(b"abcd")?, 4);
+    let error = writer.write(b"efgh").err().unwrap();
+    assert_eq!(error.kind(), ErrorKind::BrokenPipe);
+    assert_eq!(writer.write(b"efgh")?, 4);
+    assert_eq!(writer.into_inner().received, b"abcdefgh");
+
+    Ok(())
+}
+
+// A sink that accepts every write but fails its first flush.
+struct FailingFlush {
+    received: Vec<u8>,
+    failed: bool,
+}
+
+impl Write for FailingFlush {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        self.received.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        if self.failed {
+            return Ok(());
+        }
+        self.failed = true;
+        Err(Error::new(ErrorKind::BrokenPipe, "Connection reset."))
+    }
+}
+
+#[test]
+fn test_numbering() {
+    let mut serials = Numbering::new(1)
+        .prefix("TK-")
+        .padding(6)
+        .check_digit(CheckDigit::Luhn);
+    assert_eq!(serials.issued(), None);
+    assert_eq!(serials.next().unwrap(), "TK-0000018");
+    assert_eq!(serials.next().unwrap(), "TK-0000026");
+    assert_eq!(serials.issued(), Some((1, 2)));
+    assert_eq!(serials.issued_count(), 2);
+
+    let mut serials = Numbering::new(100).step(5).suffix("/A");
+    serials.next();
+    assert_eq!(serials.next().unwrap(), "105/A");
+    assert_eq!(serials.issued(), Some((100, 105)));
+
+    let serials = Numbering::new(0).check_digit(CheckDigit::Mod10);
+    assert_eq!(serials.format(629104150021), "6291041500213");
+    assert_eq!(serials.issued(), None);
+
+    let serials = Numbering::new(0).check_digit(CheckDigit::Mod7);
+    assert_eq!(serials.format(20), "206");
+}
+
+// A TrueType font with an empty .notdef glyph and an empty glyph mapped to "A".
+fn minimal_ttf() -> Vec<u8> {
+    minimal_ttf_with_kerning(None)
+}
+
+// The minimal font with a kern table adjusting the pair "AA".
+fn minimal_ttf_with_kerning(kern: Option<i16>) -> Vec<u8> {
+    let mut head = vec![0u8; 54];
+    head[0..4].copy_from_slice(&0x00010000u32.to_be_bytes());
+    head[12..16].copy_from_slice(&0x5F0F3CF5u32.to_be_bytes());
+    head[18..20].copy_from_slice(&1000u16.to_be_bytes());
+    head[40..42].copy_from_slice(&500i16.to_be_bytes());
+    head[42..44].copy_from_slice(&700i16.to_be_bytes());
+    let mut hhea = vec![0u8; 36];
+    hhea[34..36].copy_from_slice(&2u16.to_be_bytes());
+    let hmtx = [0, 0, 0, 0, 2, 88, 0, 0].to_vec();
+    let maxp = [0, 0, 80, 0, 0, 2].to_vec();
+    let loca = vec![0u8; 6];
+    let glyf = Vec::new();
+    let mut cmap = Vec::new();
+    for value in [0u16, 1, 3, 1, 0, 12] {
+        cmap.extend_from_slice(&value.to_be_bytes());
+    }
+    for value in [
+        4u16,
+        32,
+        0,
+        4,
+        4,
+        1,
+        0,
+        65,
+        0xFFFF,
+        0,
+        65,
+        0xFFFF,
+        1u16.wrapping_sub(65),
+        1,
+        0,
+        0,
+    ] {
+        cmap.extend_from_slice(&value.to_be_bytes());
+    }
+
+    let mut tables: Vec<(&[u8; 4], Vec<u8>)> = vec![
+        (b"cmap", cmap),
+        (b"glyf", glyf),
+        (b"head", head),
+        (b"hhea", hhea),
+        (b"hmtx", hmtx),
+        (b"loca", loca),
+        (b"maxp", maxp),
+    ];
+    if let Some(value) = kern {
+        let mut table = Vec::new();
+        for value in [0u16, 1, 0, 20, 0x0001, 1, 6, 0, 0, 1, 1, value as u16] {
+            table.extend_from_slice(&value.to_be_bytes());
+        }
+        tables.insert(5, (b"kern", table));
+    }
+    assemble_ttf(&tables)
+}
+
+// A font with a simple glyph for "A" and a composite glyph for "B" built from it.
+fn composite_ttf() -> Vec<u8> {
+    let mut head = vec![0u8; 54];
+    head[0..4].copy_from_slice(&0x00010000u32.to_be_bytes());
+    head[18..20].copy_from_slice(&1000u16.to_be_bytes());
+    let mut hhea = vec![0u8; 36];
+    hhea[34..36].copy_from_slice(&3u16.to_be_bytes());
+    let hmtx = [0, 0, 0, 0, 2, 88, 0, 0, 2, 88, 0, 0].to_vec();
+    let maxp = [0, 0, 80, 0, 0, 3].to_vec();
+    let loca = [0, 0, 0, 0, 0, 6, 0, 14].to_vec();
+    let mut glyf = vec![0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0xDE, 0xAD];
+    glyf.extend_from_slice(&[0xFF, 0xFF, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0]);
+    let mut cmap = Vec::new();
+    for value in [
+        0u16,
+        1,
+        3,
+        1,
+        0,
+        12,
+        4,
+        32,
+        0,
+        4,
+        4,
+        1,
+        0,
+        66,
+        0xFFFF,
+        0,
+        65,
+        0xFFFF,
+        1u16.wrapping_sub(65),
+        1,
+        0,
+        0,
+    ] {
+        cmap.extend_from_slice(&value.to_be_bytes());
+    }
+    assemble_ttf(&[
+        (b"cmap", cmap),
+        (b"glyf", glyf),
+        (b"head", head),
+        (b"hhea", hhea),
+        (b"hmtx", hmtx),
+        (b"loca", loca),
+        (b"maxp", maxp),
+    ])
+}
+
+// A font with a quadratic outline for "A" and a composite glyph for "B" that moves it right.
+fn outline_ttf() -> Vec<u8> {
+    outline_ttf_with(&[-1, 0, 0, 0, 0, 3, 1, 100, 0])
+}
+
+// The outline font with "B" replaced by the given composite glyph data.
+fn outline_ttf_with(composite: &[i16]) -> Vec<u8> {
+    let mut head = vec![0u8; 54];
+    head[0..4].copy_from_slice(&0x00010000u32.to_be_bytes());
+    head[18..20].copy_from_slice(&1000u16.to_be_bytes());
+    let mut hhea = vec![0u8; 36];
+    hhea[34..36].copy_from_slice(&3u16.to_be_bytes());
+    let hmtx = [0, 0, 0, 0, 2, 88, 0, 0, 2, 88, 0, 0].to_vec();
+    let maxp = [0, 0, 80, 0, 0, 3].to_vec();
+    let mut loca = [0, 0, 0, 0, 0, 16].to_vec();
+    loca.extend_from_slice(&(16 + composite.len() as u16).to_be_bytes());
+    let mut glyf = Vec::new();
+    for value in [1i16, 0, 0, 500, 700, 2, 0] {
+        glyf.extend_from_slice(&value.to_be_bytes());
+    }
+    glyf.extend_from_slice(&[1, 0, 1]);
+    for value in [0i16, 250, 250, 0, 700, -700] {
+        glyf.extend_from_slice(&value.to_be_bytes());
+    }
+    glyf.extend_from_slice(&[0, 0, 0]);
+    for value in composite {
+        glyf.extend_from_slice(&value.to_be_bytes());
+    }
+    let mut cmap = Vec::new();
+    for value in [
+        0u16,
+        1,
+        3,
+        1,
+        0,
+        12,
+        4,
+        32,
+        0,
+        4,
+        4,
+        1,
+        0,
+        66,
+        0xFFFF,
+        0,
+        65,
+        0xFFFF,
+        1u16.wrapping_sub(65),
+        1,
+        0,
+        0,
+    ] {
+        cmap.extend_from_slice(&value.to_be_bytes());
+    }
+    assemble_ttf(&[
+        (b"cmap", cmap),
+        (b"glyf", glyf),
+        (b"head", head),
+        (b"hhea", hhea),
+        (b"hmtx", hmtx),
+        (b"loca", loca),
+        (b"maxp", maxp),
+    ])
+}
+
+fn assemble_ttf(tables: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+    let mut font = Vec::new();
+    for value in [1u16, 0, tables.len() as u16, 64, 2, 48] {
+        font.extend_from_slice(&value.to_be_bytes());
+    }
+    let mut offset = 12 + tables.len() * 16;
+    for (tag, table) in tables {
+        font.extend_from_slice(*tag);
+        font.extend_from_slice(&[0; 4]);
+        font.extend_from_slice(&(offset as u32).to_be_bytes());
+        font.extend_from_slice(&(table.len() as u32).to_be_bytes());
+        offset += (table.len() + 3) & !3;
+    }
+    for (_, table) in tables {
+        font.extend_from_slice(table);
+        while font.len() % 4 != 0 {
+            font.push(0);
+        }
+    }
+    font
+}
+
+#[test]
+fn test_truetype_font() -> Result<(), Error> {
+    let font = TrueTypeFont::from_bytes("Brand Sans", &minimal_ttf())?;
+    assert_eq!(font.name(), "BrandSans");
+    let definition = font.definition();
+    assert!(definition.starts_with("%%BeginResource: font BrandSans\n"));
+    assert!(definition.contains("/FontType 42 def"));
+    assert!(definition.contains("/FontBBox [0 0 0.5 0.7] def"));
+    assert!(definition.contains("dup 65 /uni0041 put\n"));
+    assert!(definition.contains("/CharStrings 2 dict dup begin\n/.notdef 0 def\n/uni0041 1 def\n"));
+    assert!(definition.contains("/sfnts [\n<00010000"));
+    assert!(definition.ends_with("definefont pop\n%%EndResource"));
+
+    let error = TrueTypeFont::from_bytes("Brand", b"OTTO\0\0\0\0")
+        .err()
+        .unwrap();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+    let mut fonts = FontRegistry::new();
+    fonts.add_font(font);
+    assert!(fonts.get_font("BrandSans").is_some());
+
+    let mut output = Vec::new();
+    {
+        let mut doc = DocumentBuilder::builder()
+            .writer(BufWriter::new(&mut output))
+            .load_fonts(fonts)
+            .build();
+        let mut page = Page::new(612, 792);
+        page.add(&Text::new("A", 72.0, 720.0).font("BrandSans", 12.0))?;
+        doc.add(&page)?;
+        doc.close()?;
+    }
+    let output = String::from_utf8(output).unwrap();
+    let resource = output.find("%%BeginResource: font BrandSans").unwrap();
+    assert!(resource < output.find("%%Page: 1 1").unwrap());
+    assert!(output.contains("/BrandSans findfont 12 scalefont setfont"));
+
+    Ok(())
+}
+
+#[test]
+fn test_glyph_outlines() -> Result<(), Error> {
+    let font = TrueTypeFont::from_bytes("Brand", &outline_ttf())?;
+    let path = font
+        .outline("AB", 72.0, 700.0, 10.0)?
+        .fill_rgb(0.0, 0.0, 0.0);
+    assert_eq!(
+        path.to_postscript_string(),
+        "newpath 72 700 moveto 74.5 707 77 700 quadto closepath 79 700 moveto 81.5 707 84 700 quadto closepath 0 0 0 fillrgb "
+    );
+
+    // Characters the font doesn't have use its empty .notdef glyph.
+    let path = font.outline("?A", 0.0, 0.0, 10.0)?.fill_rgb(0.0, 0.0, 0.0);
+    assert_eq!(
+        path.to_postscript_string(),
+        "newpath 0 0 moveto 2.5 7 5 0 quadto closepath 0 0 0 fillrgb "
+    );
+
+    // A composite glyph cut off before its byte offsets
+    let font = TrueTypeFont::from_bytes("Brand", &outline_ttf_with(&[-1, 0, 0, 0, 0, 2, 1]))?;
+    let error = font.outline("B", 0.0, 0.0, 10.0).err().unwrap();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+    Ok(())
+}
+
+#[test]
+fn test_subset_fonts() -> Result<(), Error> {
+    let document_with_form = |text: &str, form_text: &str| -> Result<String, Error> {
+        let mut fonts = FontRegistry::new();
+        fonts.add_font(TrueTypeFont::from_bytes("BrandSans", &composite_ttf())?);
+        let mut letterhead = Form::new("letterhead", 612, 792);
+        letterhead.add(&Text::new(form_text, 72.0, 760.0).font("BrandSans", 12.0));
+        let mut forms = FormRegistry::new();
+        forms.add_form(letterhead);
+        let progress = Rc::new(RefCell::new(0));
+        let progress_clone = progress.clone();
+        let mut output = Vec::new();
+        let mut doc = DocumentBuilder::builder()
+            .writer(BufWriter::new(&mut output))
+            .subset_fonts(true)
+            .load_fonts(fonts)
+            .load_forms(forms)
+            .on_progress(move |_, bytes| *progress_clone.borrow_mut() = bytes)
+            .build();
+        let mut page = Page::new(612, 792);
+        page.add(&Text::new(text, 72.0, 720.0).font("BrandSans", 12.0))?;
+        doc.add(&page)?;
+        // Held output is counted as it is generated.
+        assert!(*progress.borrow() > 0);
+        assert_eq!(doc.bytes_written(), *progress.borrow());
+        let report = doc.close()?;
+        assert!(report.resources().contains(&"BrandSans".to_string()));
+        Ok(String::from_utf8(output).unwrap())
+    };
+    let document = |text: &str| document_with_form(text, "");
+
+    let output = document("A")?;
+    let setup = output.find("%%BeginSetup\n").unwrap();
+    let resource = output.find("%%BeginResource: font BrandSans").unwrap();
+    assert!(output.find("/letterhead <<").unwrap() < setup);
+    assert!(setup < resource);
+    assert!(resource < output.find("%%EndSetup\n").unwrap());
+    assert!(output.find("%%EndSetup\n").unwrap() < output.find("%%Page: 1 1").unwrap());
+    assert!(output.contains("/CharStrings 2 dict dup begin\n/.notdef 0 def\n/uni0041 1 def\nend"));
+    let hex = output.replace('\n', "");
+    assert!(hex.contains("00010000000000000000DEAD"));
+    assert!(!hex.contains("FFFF00000000000000000000000100"));
+
+    // The composite glyph keeps the glyph it is built from.
+    let output = document("B")?;
+    assert!(output.contains("/CharStrings 2 dict dup begin\n/.notdef 0 def\n/uni0042 2 def\nend"));
+    let hex = output.replace('\n', "");
+    assert!(hex.contains("00010000000000000000DEAD"));
+    assert!(hex.contains("FFFF00000000000000000000000100"));
+
+    let output = document("")?;
+    assert!(output.contains("/CharStrings 1 dict dup begin\n/.notdef 0 def\nend"));
+    assert!(!output.replace('\n', "").contains("DEAD"));
+
+    // Text in forms defined in the prolog is counted too.
+    let output = document_with_form("A", "B")?;
+    assert!(output.contains(
+        "/CharStrings 3 dict dup begin\n/.notdef 0 def\n/uni0041 1 def\n/uni0042 2 def\nend"
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_cid_keyed_font() -> Result<(), Error> {
+    let font = TrueTypeFont::from_bytes("BrandCJK", &minimal_ttf())?.cid_keyed();
+    let definition = font.definition();
+    assert!(definition.starts_with(
+        "%%BeginResource: font BrandCJK\n12 dict begin\n/CIDFontName /BrandCJK-CID def\n/CIDFontType 2 def\n"
+    ));
+    assert!(definition.contains("/CIDCount 2 def\n/GDBytes 2 def\n/CIDMap 0 def\n"));
+    assert!(definition.contains(
+        "/CharStrings 1 dict dup begin\n/.notdef 0 def\nend readonly def\n/sfnts [\n<00010000"
+    ));
+    assert!(definition.ends_with(
+        "CIDFontName currentdict end /CIDFont defineresource pop\n/BrandCJK /Identity-H [/BrandCJK-CID /CIDFont findresource] composefont pop\n%%EndResource"
+    ));
+
+    let mut fonts = FontRegistry::new();
+    fonts.add_font(font);
+    let metrics = fonts.metrics();
+    assert_eq!(
+        Text::new("AAé", 0.0, 0.0)
+            .font("BrandCJK", 10.0)
+            .metrics(&metrics)
+            .to_postscript_string(),
+        "gsave 0 setgray /BrandCJK findfont 10 scalefont setfont 0 0 moveto <000100010000> show grestore "
+    );
+    assert!(Paragraph::new("A A", 0.0, 0.0, 100.0, 20.0)
+        .font("BrandCJK", 10.0)
+        .word_spacing(2.0)
+        .metrics(&metrics)
+        .to_postscript_string()
+        .contains("2 0 0 0 0 <000100000001> awidthshow"));
+
+    // Text shown without the glyph indexes is rejected by the document.
+    let document = |level: LanguageLevel, with_metrics: bool| -> Result<Vec<String>, Error> {
+        let mut fonts = FontRegistry::new();
+        fonts.add_font(TrueTypeFont::from_bytes("BrandCJK", &minimal_ttf())?.cid_keyed());
+        let metrics = fonts.metrics();
+        let mut output = Vec::new();
+        let mut doc = DocumentBuilder::builder()
+            .writer(BufWriter::new(&mut output))
+            .language_level(level)
+            .load_fonts(fonts)
+            .build();
+        let mut text = Text::new("AA", 0.0, 0.0).font("BrandCJK", 10.0);
+        if with_metrics {
+            text = text.metrics(&metrics);
+        }
+        let mut page = Page::new(612, 792);
+        page.add(&text)?;
+        doc.add(&page)?;
+        Ok(doc.close()?.warnings().to_vec())
+    };
+    assert!(document(LanguageLevel::Level3, true)?.is_empty());
+    assert_eq!(
+        document(LanguageLevel::Level2, true)?,
+        ["The CID-keyed font BrandCJK requires LanguageLevel 3."]
+    );
+    let error = document(LanguageLevel::Level3, false).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidInput);
+    assert_eq!(
+        error.to_string(),
+        "Text in the CID-keyed font BrandCJK must be given the font's metrics."
+    );
+    Ok(())
+}
+
+#[test]
+fn test_font_metrics() -> Result<(), Error> {
+    let metrics = FontMetrics::new();
+    assert_eq!(metrics.measure("Hello", Font::Helvetica, 10.0), Some(22.78));
+    assert_eq!(
+        metrics.measure("Hello", "Helvetica-Oblique", 10.0),
+        Some(22.78)
+    );
+    assert_eq!(metrics.measure("abc", Font::CourierBold, 12.0), Some(21.6));
+    assert_eq!(metrics.measure("AW", Font::TimesBold, 1000.0), Some(1722.0));
+    assert_eq!(
+        metrics.measure("A", Font::HelveticaNarrow, 1000.0),
+        Some(547.0)
+    );
+    assert_eq!(metrics.measure("é", Font::Helvetica, 1000.0), Some(556.0));
+    assert_eq!(
+        metrics.measure("Hello", Font::PalatinoRoman, 10.0),
+        Some(24.39)
+    );
+    assert_eq!(
+        metrics.measure("a\u{2014}b", Font::Helvetica, 1000.0),
+        Some(2112.0)
+    );
+    assert_eq!(
+        metrics.measure("\u{00EF}\u{00DF}", Font::TimesRoman, 1000.0),
+        Some(778.0)
+    );
+    assert_eq!(
+        metrics.measure(
+            "\u{00AB}Qui\u{00BB}",
+            Font::ZapfChanceryMediumItalic,
+            1000.0
+        ),
+        Some(2020.0)
+    );
+    assert_eq!(
+        metrics.measure("\u{201C}", Font::HelveticaNarrowBold, 1000.0),
+        Some(410.0)
+    );
+    assert_eq!(
+        metrics.measure("a", Font::ZapfDingbats, 1000.0),
+        Some(789.0)
+    );
+
+    // Helvetica and Times-Roman are kerned with their AFM pairs when given metrics.
+    assert_eq!(metrics.measure("AV", Font::Helvetica, 1000.0), Some(1264.0));
+    assert_eq!(
+        metrics.measure("AV", Font::HelveticaNarrow, 1000.0),
+        Some(1037.0)
+    );
+    assert_eq!(
+        metrics.measure("To", Font::TimesRoman, 1000.0),
+        Some(1031.0)
+    );
+    assert_eq!(metrics.measure("To", Font::TimesBold, 1000.0), Some(1167.0));
+    assert_eq!(
+        Text::new("AV", 0.0, 0.0)
+            .metrics(&metrics)
+            .to_postscript_string(),
+        "gsave 0 setgray /Helvetica findfont 12 scalefont setfont 0 0 moveto (A) show -0.84 0 rmoveto (V) show grestore "
+    );
+    assert_eq!(
+        Text::new("AV", 0.0, 0.0).to_postscript_string(),
+        "gsave 0 setgray /Helvetica findfont 12 scalefont setfont 0 0 moveto (AV) show grestore "
+    );
+
+    let mut fonts = FontRegistry::new();
+    fonts.add_font(TrueTypeFont::from_bytes("BrandSans", &minimal_ttf())?);
+    let metrics = fonts.metrics();
+    assert_eq!(metrics.measure("AA", "BrandSans", 10.0), Some(12.0));
+    assert_eq!(metrics.measure("AB", "BrandSans", 10.0), Some(6.0));
+
+    Ok(())
+}
+
+#[test]
+fn test_font_fallbacks() -> Result<(), Error> {
+    let mut fonts = FontRegistry::new();
+    fonts.add_font(TrueTypeFont::from_bytes("BrandSans", &minimal_ttf())?);
+    let mut metrics = fonts.metrics();
+    metrics.set_fallbacks("BrandSans", vec![Font::Helvetica, Font::Symbol]);
+    assert_eq!(metrics.measure("AB", "BrandSans", 10.0), Some(12.67));
+
+    assert_eq!(
+        Text::new("AB \u{3A9}", 0.0, 0.0)
+            .font("BrandSans", 10.0)
+            .metrics(&metrics)
+            .to_postscript_string(),
+        "gsave 0 setgray /BrandSans findfont 10 scalefont setfont 0 0 moveto (A) show \
+currentfont /Helvetica findfont 10 scalefont setfont (B ) show setfont \
+currentfont /Symbol findfont 10 scalefont setfont /Omega glyphshow setfont grestore "
+    );
+
+    metrics.set_fallbacks("BrandSans", Vec::new());
+    assert_eq!(metrics.measure("AB", "BrandSans", 10.0), Some(6.0));
+    Ok(())
+}
+
+#[test]
+fn test_paragraph() {
+    let paragraph = Paragraph::new(
+        "The quick brown fox jumps over the lazy dog.\n\nSupercalifragilistic",
+        72.0,
+        600.0,
+        100.0,
+        100.0,
+    )
+    .font(Font::Helvetica, 10.0);
+    assert_eq!(
+        paragraph.to_postscript_string(),
+        "gsave 0 setgray /Helvetica findfont 10 scalefont setfont 72 690 moveto (The quick brown fox) show 72 678 moveto (jumps over the lazy) show 72 666 moveto (dog.) show 72 642 moveto (Supercalifragilistic) show grestore "
+    );
+
+    // Long words are broken and lines below the box are dropped.
+    let paragraph = Paragraph::new("Supercalifragilistic", 0.0, 0.0, 30.0, 20.0)
+        .font(Font::Helvetica, 10.0)
+        .leading(10.0)
+        .fill_rgb(1.0, 0.0, 0.0);
+    assert_eq!(
+        paragraph.to_postscript_string(),
+        "gsave 1 0 0 setrgbcolor /Helvetica findfont 10 scalefont setfont 0 10 moveto (Super) show 0 0 moveto (califra) show grestore "
+    );
+
+    assert_eq!(
+        Paragraph::new("Hello", 0.0, 0.0, 100.0, 5.0).to_postscript_string(),
+        ""
+    );
+}
+
+#[test]
+fn test_sidecar() -> Result<(), Error> {
+    let mut output = Vec::new();
+    {
+        let doc = DocumentBuilder::builder()
+            .writer(BufWriter::new(&mut output))
+            .sidecar("invoice 1042.xml", "application/xml", b"abc")
+            .build();
+        doc.close()?;
+    }
+    let output = String::from_utf8(output).unwrap();
+    let comment = "%%PslibSidecar: (invoice 1042.xml) (application/xml) 3 SHA-256 ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad\n";
+    assert!(output.find(comment).unwrap() < output.find("%%EndComments").unwrap());
+
+    Ok(())
+}
+
+#[test]
+fn test_accounting() -> Result<(), Error> {
+    let mut output = Vec::new();
+    {
+        let doc = DocumentBuilder::builder()
+            .writer(BufWriter::new(&mut output))
+            .accounting(
+                Accounting::new()
+                    .client("Acme (Billing)")
+                    .routing("Mailroom\nBin 4")
+                    .cost_center("CC-4100")
+                    .copies(3)
+                    .comment("Project Code", "Q3 mailer")
+                    .comment("EndComments", "early"),
+            )
+            .build();
+        doc.close()?;
+    }
+    let output = String::from_utf8(output).unwrap();
+    let comments = "%%For: (Acme \\(Billing\\))\n%%Routing: (Mailroom Bin 4)\n%%PslibCostCenter: (CC-4100)\n%%Requirements: numcopies(3)\n%%PslibProjectCode: (Q3 mailer)\n%%PslibEndComments: (early)\n";
+    assert!(output.find(comments).unwrap() < output.find("%%EndComments").unwrap());
+    assert_eq!(output.matches("%%EndComments").count(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_comment_sanitization() -> Result<(), Error> {
+    let mut form = Form::new("logo\n%%EOF", 10, 10);
+    form.add(&Rect::new(0.0, 0.0, 10.0, 10.0).fill_rgb(0.0, 0.0, 0.0));
+    let mut forms = FormRegistry::new();
+    forms.add_form(form);
+
+    let mut output = Vec::new();
+    {
+        let mut doc = DocumentBuilder::builder()
+            .writer(BufWriter::new(&mut output))
+            .job_name("Q3\r\n%%EOF\u{7} report")
+            .accounting(Accounting::new().client("Acme\t%%%Trailer"))
+            .custom_color("Spot\n%%Page: 9 9", 0.0, 1.0, 0.0, 0.0)
+            .max_line_length(40)
+            .load_forms(forms)
+            .build();
+        let mut page = Page::new(612, 792);
+        page.add(&Text::new(&"%".repeat(100), 72.0, 720.0))?;
+        doc.add(&page)?;
+        doc.close()?;
+    }
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("%%Title: Q3  %EOF  report\n"));
+    assert!(output.contains("%%For: (Acme %Trailer)\n"));
+    assert!(output.contains("%%DocumentCustomColors: (Spot %Page: 9 9)\n"));
+    assert!(output.contains("/logoEOF << /FormType 1 "));
+
+    // Long strings are never continued on a line starting with '%'.
+    assert!(output.contains("\\\n\\045%"));
+    let structure: Vec<&str> = output
+        .lines()
+        .filter(|line| line.starts_with("%%") && !line.starts_with("%%+"))
+        .filter(|line| line.starts_with("%%EOF") || line.starts_with("%%Page:"))
+        .collect();
+    assert_eq!(structure, ["%%Page: 1 1", "%%EOF"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_text_encoding() {
+    assert_eq!(
+        Text::new("Café – 5€ (net)", 10.0, 10.0).to_postscript_string(),
+        "gsave 0 setgray /Helvetica-Latin1 /Helvetica latin1font 12 scalefont setfont 10 10 moveto (Caf\\351 ) show /endash glyphshow ( 5) show /Euro glyphshow ( \\(net\\)) show grestore "
+    );
+    assert_eq!(
+        Text::new("αβ", 0.0, 0.0)
+            .font(Font::Symbol, 10.0)
+            .to_postscript_string(),
+        "gsave 0 setgray /Symbol findfont 10 scalefont setfont 0 0 moveto /alpha glyphshow /beta glyphshow grestore "
+    );
+    assert!(Paragraph::new("Grüße", 0.0, 0.0, 100.0, 20.0)
+        .to_postscript_string()
+        .contains("/Helvetica-Latin1 /Helvetica latin1font 12 scalefont setfont 0 8 moveto (Gr\\374\\337e) show "));
+    assert!(ProcedureRegistry::with_builtins()
+        .get_procedure("latin1font")
+        .is_some());
+}
+
+#[test]
+fn test_paragraph_columns() {
+    let paragraph = Paragraph::new("aaa bbb ccc ddd eee", 0.0, 0.0, 70.0, 20.0)
+        .font(Font::Courier, 10.0)
+        .leading(10.0)
+        .columns(2, 10.0)
+        .column_rule(0.5);
+    assert_eq!(
+        paragraph.to_postscript_string(),
+        "gsave 0 setgray /Courier findfont 10 scalefont setfont 0 10 moveto (aaa) show 0 0 moveto (bbb) show \
+40 10 moveto (ccc) show 40 0 moveto (ddd) show newpath 35 0 moveto 0 20 rlineto 0.5 setlinewidth stroke grestore "
+    );
+}
+
+#[test]
+fn test_tab_stops() {
+    let paragraph = Paragraph::new("ab\t1.5\nPage\t12\tx", 0.0, 0.0, 200.0, 20.0)
+        .font(Font::Courier, 10.0)
+        .leading(10.0)
+        .tab_stop(TabStop::new(120.0, TabAlignment::Right).leader('.'))
+        .tab_stop(TabStop::new(50.0, TabAlignment::Decimal));
+    assert_eq!(
+        paragraph.to_postscript_string(),
+        "gsave 0 setgray /Courier findfont 10 scalefont setfont 0 10 moveto (ab) show 44 10 moveto (1.5) show \
+0 0 moveto (Page) show 38 0 moveto (12) show 54 0 moveto (..........) show 114 0 moveto (x) show grestore "
+    );
+
+    // Leaders aren't underlined and text past the last stop follows a space.
+    let paragraph = Paragraph::new("a\tb\tc", 0.0, 0.0, 200.0, 20.0)
+        .font(Font::Courier, 10.0)
+        .tab_stop(TabStop::new(30.0, TabAlignment::Center).leader('-'))
+        .underline()
+        .to_postscript_string();
+    assert!(paragraph.contains("9 10 moveto (---) show 27 10 moveto currentpoint pop (b) show"));
+    assert!(paragraph.contains("39 10 moveto currentpoint pop (c) show"));
+
+    // Without stops tabs are treated as spaces.
+    let paragraph = Paragraph::new("a\tb", 0.0, 0.0, 200.0, 20.0).font(Font::Courier, 10.0);
+    assert!(paragraph.to_postscript_string().contains("(a b) show"));
+}
+
+#[test]
+fn test_hyphenation() {
+    let syllables = |word: &str| match word {
+        "hyphenation" => vec![2, 6, 7],
+        _ => Vec::new(),
+    };
+    let paragraph = Paragraph::new("aa hyphenation", 0.0, 0.0, 60.0, 20.0)
+        .font(Font::Courier, 10.0)
+        .leading(10.0)
+        .hyphenator(syllables);
+    assert_eq!(
+        paragraph.to_postscript_string(),
+        "gsave 0 setgray /Courier findfont 10 scalefont setfont 0 10 moveto (aa hyphen-) show 0 0 moveto (ation) show grestore "
+    );
+
+    // Words are still broken between characters when no break point fits.
+    let paragraph = Paragraph::new("aaaa hyphenation", 0.0, 0.0, 30.0, 30.0)
+        .font(Font::Courier, 10.0)
+        .leading(10.0)
+        .hyphenator(syllables)
+        .to_postscript_string();
+    assert!(paragraph.contains("(aaaa) show 0 10 moveto (hy-) show 0 0 moveto (phen-) show "));
+}
+
+#[test]
+fn test_shrink_to_fit() {
+    let text = Text::new("WWWW", 0.0, 0.0).font(Font::Courier, 12.0);
+    assert!(text
+        .fit_box(20.0, 20.0)
+        .to_postscript_string()
+        .contains("/Courier findfont 8 scalefont"));
+    let text = Text::new("WWWW", 0.0, 0.0).font(Font::Courier, 12.0);
+    assert!(text
+        .fit_box(5.0, 20.0)
+        .min_size(4.0)
+        .to_postscript_string()
+        .contains("/Courier findfont 4 scalefont"));
+    let text = Text::new("WWWW", 0.0, 0.0).font(Font::Courier, 12.0);
+    assert!(text
+        .fit_box(100.0, 9.0)
+        .to_postscript_string()
+        .contains("/Courier findfont 9 scalefont"));
+
+    let paragraph = Paragraph::new("aaaa bbbb cccc", 0.0, 0.0, 30.0, 20.0)
+        .font(Font::Courier, 10.0)
+        .shrink_to_fit();
+    assert!(paragraph
+        .to_postscript_string()
+        .contains("/Courier findfont 6 scalefont"));
+    let paragraph = Paragraph::new("aaaa bbbb cccc", 0.0, 0.0, 30.0, 20.0)
+        .font(Font::Courier, 10.0)
+        .shrink_to_fit()
+        .min_size(4.0);
+    let paragraph = paragraph.to_postscript_string();
+    assert!(paragraph.contains("5.5 scalefont setfont 0 14.5 moveto (aaaa bbbb) show "));
+    assert!(paragraph.contains("(cccc) show grestore "));
+}
+
+#[cfg(feature = "bidi")]
+#[test]
+fn test_bidi() {
+    assert_eq!(
+        Text::new("\u{5D0}\u{5D1}\u{5D2} 123 def", 0.0, 0.0).to_postscript_string(),
+        "gsave 0 setgray /Helvetica findfont 12 scalefont setfont 0 0 moveto (def 123 ) show /uni05D2 glyphshow /uni05D1 glyphshow /uni05D0 glyphshow grestore "
+    );
+    assert!(Text::new("Invoice (\u{5D0}\u{5D1}) 42", 0.0, 0.0)
+        .to_postscript_string()
+        .contains("(Invoice \\() show /uni05D1 glyphshow /uni05D0 glyphshow (\\) 42) show"));
+    assert!(Text::new("\u{5D0} (\u{5D1})", 0.0, 0.0)
+        .to_postscript_string()
+        .contains("(\\() show /uni05D1 glyphshow (\\) ) show /uni05D0 glyphshow"));
+
+    let paragraph = Paragraph::new("\u{5D0}\u{5D1} cd\n\u{5D2} 7", 0.0, 0.0, 200.0, 40.0);
+    assert!(paragraph
+        .to_postscript_string()
+        .contains("(cd ) show /uni05D1 glyphshow /uni05D0 glyphshow"));
+}
+
+#[test]
+fn test_truncate() {
+    let text = Text::new("ABCDEFGHIJ", 0.0, 0.0)
+        .font(Font::Courier, 10.0)
+        .truncate(40.0);
+    assert_eq!(
+        text.to_postscript_string(),
+        "gsave 0 setgray /Courier findfont 10 scalefont setfont 0 0 moveto (ABCDE) show /ellipsis glyphshow grestore "
+    );
+    let text = Text::new("ABCD EFGH", 0.0, 0.0)
+        .font(Font::Courier, 10.0)
+        .truncate(36.0);
+    assert!(text
+        .to_postscript_string()
+        .contains("(ABCD) show /ellipsis glyphshow"));
+    let text = Text::new("ABCD", 0.0, 0.0)
+        .font(Font::Courier, 10.0)
+        .truncate(24.0);
+    assert!(text.to_postscript_string().contains("(ABCD) show"));
+    let text = Text::new("ABCD", 0.0, 0.0)
+        .font(Font::Courier, 10.0)
+        .truncate(3.0);
+    assert_eq!(text.to_postscript_string(), "");
+
+    let metrics = FontMetrics::new();
+    assert_eq!(
+        metrics.measure("\u{2026}", Font::Helvetica, 10.0),
+        Some(10.0)
+    );
+}
+
+#[test]
+fn test_rich_text() {
+    let text = RichText::new(72.0, 600.0, 120.0, 100.0)
+        .span(Span::new("Invoice: ").font(Font::Helvetica, 10.0).bold())
+        .span(
+            Span::new("1042")
+                .font(Font::Helvetica, 10.0)
+                .fill_rgb(1.0, 0.0, 0.0),
+        )
+        .span(Span::new(" Total").font(Font::TimesRoman, 14.0))
+        .span(Span::new(": ").font(Font::TimesRoman, 10.0))
+        .span(Span::new("50 due within thirty days\nThanks").font(Font::Helvetica, 10.0));
+    assert_eq!(
+        text.to_postscript_string(),
+        "gsave 72 686 moveto 0 setgray /Helvetica-Bold findfont 10 scalefont setfont (Invoice: ) show 1 0 0 setrgbcolor /Helvetica findfont 10 scalefont setfont (1042) show 0 setgray /Times-Roman findfont 14 scalefont setfont ( Total) show /Times-Roman findfont 10 scalefont setfont (: ) show /Helvetica findfont 10 scalefont setfont (50) show 72 674 moveto (due within thirty days) show 72 662 moveto (Thanks) show grestore "
+    );
+
+    // Text in neighbouring spans is kept together and lines below the box are dropped.
+    let text = RichText::new(0.0, 0.0, 30.0, 10.0)
+        .span(Span::new("Total").font(Font::Courier, 10.0).bold())
+        .span(Span::new(": 12").font(Font::Courier, 10.0));
+    assert_eq!(
+        text.to_postscript_string(),
+        "gsave 0 0 moveto 0 setgray /Courier-Bold findfont 10 scalefont setfont (Total) show /Courier findfont 10 scalefont setfont (:) show grestore "
+    );
+    assert_eq!(
+        RichText::new(0.0, 0.0, 30.0, 10.0).to_postscript_string(),
+        ""
+    );
+}
+
+#[test]
+fn test_text_decoration() {
+    let text = Text::new("Sale", 10.0, 20.0)
+        .font(Font::Helvetica, 10.0)
+        .underline()
+        .strikethrough();
+    assert_eq!(
+        text.to_postscript_string(),
+        "gsave 0 setgray /Helvetica findfont 10 scalefont setfont 10 20 moveto currentpoint pop (Sale) show currentpoint \
+newpath 2 index 19 moveto 1 index 19 lineto 0.5 setlinewidth 0 setlinecap stroke \
+newpath 2 index 22.615 moveto 1 index 22.615 lineto 0.5 setlinewidth 0 setlinecap stroke moveto pop grestore "
+    );
+
+    let text = Text::new("Sale", 0.0, 0.0)
+        .font(Font::CourierBold, 10.0)
+        .strikethrough();
+    assert!(text.to_postscript_string().contains("2 index 2.195 moveto"));
+
+    let rich_text = RichText::new(0.0, 0.0, 200.0, 20.0)
+        .span(Span::new("Was "))
+        .span(Span::new("$10").underline());
+    assert!(rich_text.to_postscript_string().contains(
+        "(Was ) show currentpoint pop ($10) show currentpoint newpath 2 index 6.8 moveto"
+    ));
+
+    let paragraph = Paragraph::new("one two", 0.0, 0.0, 20.0, 30.0)
+        .font(Font::Courier, 10.0)
+        .leading(10.0)
+        .underline();
+    let paragraph = paragraph.to_postscript_string();
+    assert!(paragraph.contains(
+        "0 20 moveto currentpoint pop (one) show currentpoint newpath 2 index 19 moveto"
+    ));
+    assert!(paragraph
+        .contains("0 10 moveto currentpoint pop (two) show currentpoint newpath 2 index 9 moveto"));
+}
+
+#[test]
+fn test_tracking_and_kerning() -> Result<(), Error> {
+    assert_eq!(
+        Text::new("Sale", 0.0, 0.0)
+            .tracking(1.5)
+            .to_postscript_string(),
+        "gsave 0 setgray /Helvetica findfont 12 scalefont setfont 0 0 moveto 1.5 0 (Sale) ashow grestore "
+    );
+    assert_eq!(
+        Text::new("Big sale", 0.0, 0.0)
+            .word_spacing(4.0)
+            .to_postscript_string(),
+        "gsave 0 setgray /Helvetica findfont 12 scalefont setfont 0 0 moveto 4 0 32 0 0 (Big sale) awidthshow grestore "
+    );
+
+    // Tracking and word spacing are included when wrapping.
+    let paragraph = Paragraph::new("aaaa aaaa", 0.0, 0.0, 55.0, 24.0).font(Font::Courier, 10.0);
+    assert!(paragraph
+        .to_postscript_string()
+        .contains("(aaaa aaaa) show"));
+    let paragraph = paragraph.tracking(1.0);
+    assert!(paragraph
+        .to_postscript_string()
+        .contains("0 14 moveto 1 0 (aaaa) ashow 0 2 moveto 1 0 (aaaa) ashow"));
+
+    let mut fonts = FontRegistry::new();
+    fonts.add_font(TrueTypeFont::from_bytes(
+        "BrandSans",
+        &minimal_ttf_with_kerning(Some(-50)),
+    )?);
+    let metrics = fonts.metrics();
+    assert_eq!(metrics.measure("AAA", "BrandSans", 10.0), Some(17.0));
+    assert_eq!(
+        Text::new("AAA", 0.0, 0.0)
+            .font("BrandSans", 10.0)
+            .metrics(&metrics)
+            .to_postscript_string(),
+        "gsave 0 setgray /BrandSans findfont 10 scalefont setfont 0 0 moveto (A) show -0.5 0 rmoveto (A) show -0.5 0 rmoveto (A) show grestore "
+    );
+    let span = Span::new("AA").font("BrandSans", 10.0).tracking(1.0);
+    assert!(RichText::new(0.0, 0.0, 100.0, 20.0)
+        .span(span)
+        .metrics(&metrics)
+        .to_postscript_string()
+        .contains("1 0 (A) ashow -0.5 0 rmoveto 1 0 (A) ashow"));
+
+    Ok(())
+}